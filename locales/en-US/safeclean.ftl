@@ -0,0 +1,40 @@
+# English messages for safeclean. A translation goes in
+# locales/<lang>/safeclean.ftl, registered in LOCALES in src/i18n.rs; ids it
+# leaves out fall back to these.
+
+## Scan results and summaries
+
+no-dirs-found = No cleanable directories found.
+found-dirs = Found { $count } cleanable directories ({ $size })
+found-dirs-unsized = Found { $count } cleanable directories
+dry-run-header = Dry run - nothing will be deleted:
+total = Total:
+cancelled = Cancelled.
+nothing-selected = Nothing selected.
+deleting = Deleting
+trashing = Trashing
+removing-dirs = { $verb } { $count } directories...
+failed-to-delete = Failed to delete:
+done = Done!
+cleaned = { $done } Cleaned { $size } in { $count } directories
+
+## Selector footer
+
+selector-summary = { $count } selected, { $size } will be freed
+selector-instructions = { $nav } navigate  { $toggle } toggle  { $expand } expand/collapse  { $sort } sort ({ $order })  { $confirm } confirm  { $help } help
+prompt-criterion = Select items older/larger than:{" "}
+prompt-filter = Filter (text, or /regex/):{" "}
+
+## Line-based selection, when stderr isn't a terminal
+
+prompt-line = Numbers or ranges to toggle (1 3-5), a all, n none, l list, q quit, Enter to confirm:{" "}
+
+## Selector confirmation
+
+confirm-delete = About to delete { $count } directories, freeing { $size }
+confirm-trash = About to move to the trash { $count } directories, freeing { $size }
+confirm-keys = { $yes } delete  { $no } back to selection
+confirm-over-cap = This is more than the --max-delete cap of { $cap }.
+confirm-large = This is a large clean.
+confirm-type-word = Type { $word } and press Enter to go ahead, Esc to go back: { $typed }
+prompt-type-word = Type { $word } to go ahead, anything else to go back:{" "}
@@ -0,0 +1,78 @@
+use crate::cleaner::Method;
+use crate::history::date_of;
+use crate::scanner::FoundDir;
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One line of the audit log: a single directory safeclean tried to remove.
+#[derive(Serialize)]
+struct Record<'a> {
+    /// UTC, as `YYYY-MM-DDTHH:MM:SSZ`
+    time: String,
+    path: &'a Path,
+    #[serde(rename = "type")]
+    project_type: &'a str,
+    bytes: u64,
+    /// "deleted", "trashed" or "failed"
+    outcome: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    user: String,
+}
+
+/// The audit log holds one JSON object per removal attempt, oldest first.
+/// Unlike the history it also records failures and is never summarized.
+fn log_file() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("safeclean").join("history.log"))
+}
+
+fn timestamp(now: SystemTime) -> String {
+    let secs = now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64);
+    let (year, month, day) = date_of(secs);
+    let of_day = secs.rem_euclid(86_400);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        of_day / 3600,
+        of_day % 3600 / 60,
+        of_day % 60
+    )
+}
+
+fn user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("LOGNAME"))
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Append the outcome of removing `dir` to the audit log.
+pub fn record(dir: &FoundDir, method: Method, outcome: Result<(), &io::Error>) -> io::Result<()> {
+    let Some(file) = log_file() else {
+        return Ok(());
+    };
+    let record = Record {
+        time: timestamp(SystemTime::now()),
+        path: &dir.path,
+        project_type: dir.project_type.name(),
+        bytes: dir.size_bytes,
+        outcome: match (outcome, method) {
+            (Err(_), _) => "failed",
+            (Ok(()), Method::Delete) => "deleted",
+            (Ok(()), Method::Trash) => "trashed",
+        },
+        error: outcome.err().map(|e| e.to_string()),
+        user: user(),
+    };
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(&record).map_err(io::Error::other)?;
+    line.push('\n');
+    OpenOptions::new().create(true).append(true).open(file)?.write_all(line.as_bytes())
+}
@@ -0,0 +1,115 @@
+//! `cargo safeclean`: safeclean for the Cargo workspace the current
+//! directory belongs to, cleaning only Rust build output unless told
+//! otherwise. Every other argument is passed on to `safeclean`.
+
+use colored::Colorize;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const EXIT_ERROR: i32 = 1;
+
+/// Where the workspace is and where it builds to, per `cargo metadata`.
+struct Workspace {
+    root: PathBuf,
+    target_dir: PathBuf,
+}
+
+fn main() {
+    // Cargo runs `cargo-safeclean safeclean ARGS...`
+    let mut args: Vec<OsString> = std::env::args_os().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "safeclean") {
+        args.remove(0);
+    }
+
+    let workspace = match workspace() {
+        Ok(workspace) => workspace,
+        Err(e) => {
+            eprintln!("{} {}", "error:".red().bold(), e);
+            std::process::exit(EXIT_ERROR);
+        }
+    };
+
+    let mut safeclean = Command::new(safeclean_exe());
+    safeclean.args(&args);
+    // Through the environment so a path or --types on the command line still
+    // wins, without parsing safeclean's arguments here
+    if std::env::var_os("SAFECLEAN_TYPES").is_none() {
+        safeclean.env("SAFECLEAN_TYPES", "rust");
+    }
+    if std::env::var_os("SAFECLEAN_PATH").is_none() {
+        if workspace.target_dir.starts_with(&workspace.root) {
+            safeclean.env("SAFECLEAN_PATH", &workspace.root);
+        } else {
+            // CARGO_TARGET_DIR or build.target-dir elsewhere: nothing builds
+            // into the workspace, so scan the target directory, which no
+            // Cargo.toml sits next to, as a directory named on purpose
+            safeclean.env("SAFECLEAN_PATH", &workspace.target_dir);
+            if let Some(name) = workspace.target_dir.file_name().and_then(|name| name.to_str()) {
+                let include = match std::env::var("SAFECLEAN_INCLUDE_DIR") {
+                    Ok(rules) if !rules.is_empty() => format!("{},{}", rules, name),
+                    _ => name.to_string(),
+                };
+                safeclean.env("SAFECLEAN_INCLUDE_DIR", include);
+            }
+        }
+    }
+    std::process::exit(run(safeclean));
+}
+
+/// The workspace containing the current directory.
+fn workspace() -> Result<Workspace, String> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let output = Command::new(&cargo)
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .output()
+        .map_err(|e| format!("could not run {}: {}", Path::new(&cargo).display(), e))?;
+    if !output.status.success() {
+        // cargo has already said why on its stderr, e.g. no Cargo.toml found
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.trim().trim_start_matches("error: ").to_string());
+    }
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("could not read cargo metadata: {}", e))?;
+    let path = |key: &str| {
+        metadata[key]
+            .as_str()
+            .map(PathBuf::from)
+            .ok_or_else(|| format!("cargo metadata has no {}", key))
+    };
+    Ok(Workspace {
+        root: path("workspace_root")?,
+        target_dir: path("target_directory")?,
+    })
+}
+
+/// The `safeclean` installed alongside this binary, else the one on `PATH`.
+fn safeclean_exe() -> PathBuf {
+    let name = format!("safeclean{}", std::env::consts::EXE_SUFFIX);
+    std::env::current_exe()
+        .ok()
+        .map(|exe| exe.with_file_name(&name))
+        .filter(|exe| exe.is_file())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+/// Run safeclean in place of this process, returning only if it couldn't be
+/// started.
+#[cfg(unix)]
+fn run(mut safeclean: Command) -> i32 {
+    use std::os::unix::process::CommandExt;
+    let e = safeclean.exec();
+    eprintln!("{} could not run {}: {}", "error:".red().bold(), safeclean.get_program().to_string_lossy(), e);
+    EXIT_ERROR
+}
+
+#[cfg(not(unix))]
+fn run(mut safeclean: Command) -> i32 {
+    match safeclean.status() {
+        Ok(status) => status.code().unwrap_or(EXIT_ERROR),
+        Err(e) => {
+            eprintln!("{} could not run {}: {}", "error:".red().bold(), safeclean.get_program().to_string_lossy(), e);
+            EXIT_ERROR
+        }
+    }
+}
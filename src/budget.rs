@@ -0,0 +1,32 @@
+use crate::risk::{Risk, RiskLevel};
+use crate::scanner::FoundDir;
+use std::cmp::Reverse;
+use std::path::PathBuf;
+
+/// Candidates picked to free a target amount of space.
+pub struct Budget {
+    pub paths: Vec<PathBuf>,
+    /// Space the picked candidates take up, which may fall short of the
+    /// target when there isn't enough low-risk space
+    pub bytes: u64,
+}
+
+/// Pick the largest candidates, oldest first among equals, until `target`
+/// bytes would be freed. High-risk directories are never picked.
+pub fn pick(found: &[FoundDir], target: u64) -> Budget {
+    let mut candidates: Vec<&FoundDir> = found
+        .iter()
+        .filter(|d| Risk::of(d).level() != RiskLevel::High)
+        .collect();
+    candidates.sort_by_key(|d| (Reverse(d.size_bytes), Reverse(d.age_days())));
+
+    let mut budget = Budget { paths: Vec::new(), bytes: 0 };
+    for dir in candidates {
+        if budget.bytes >= target {
+            break;
+        }
+        budget.paths.push(dir.path.clone());
+        budget.bytes += dir.size_bytes;
+    }
+    budget
+}
@@ -0,0 +1,478 @@
+use crate::projects::ProjectType;
+use crate::units;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// A per-user tool cache, shared by every project using the tool and
+/// refilled by downloading again.
+pub struct GlobalCache {
+    pub tool: &'static str,
+    pub path: PathBuf,
+    pub entries: Entries,
+    /// The group it is offered in: [`ProjectType::GlobalCache`], the type of
+    /// project using it, or its own when it needs handling of its own
+    pub project_type: ProjectType,
+}
+
+/// How a cache is split into entries offered one by one, so the recently
+/// used ones can stay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entries {
+    /// Offered whole
+    Whole,
+    /// Everything this many levels down
+    AtDepth(usize),
+    /// Each directory holding a file with this extension, such as a Maven
+    /// artifact version with its `.pom`
+    Holding(&'static str),
+    /// Each file with this extension, anywhere inside
+    Files(&'static str),
+}
+
+/// `$name` if set, else `default` under the home directory.
+pub(crate) fn tool_home(name: &str, default: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(default)))
+}
+
+/// Where the well-known caches live on this machine, whether or not they
+/// exist. Those that do are given by their real path, wherever a symlink
+/// such as a relocated `~/.cache` puts them, as scanned directories are.
+pub fn known_caches() -> Vec<GlobalCache> {
+    let mut caches = Vec::new();
+    let mut add = |tool, path: Option<PathBuf>, entries| {
+        if let Some(path) = path {
+            caches.push(GlobalCache {
+                tool,
+                path,
+                entries,
+                project_type: ProjectType::GlobalCache,
+            });
+        }
+    };
+
+    // Downloaded crates by registry, their unpacked sources, and git
+    // dependencies by repository and revision
+    let cargo = tool_home("CARGO_HOME", ".cargo");
+    add("cargo", cargo.as_ref().map(|c| c.join("registry").join("cache")), Entries::AtDepth(2));
+    add("cargo", cargo.as_ref().map(|c| c.join("registry").join("src")), Entries::AtDepth(2));
+    add("cargo", cargo.as_ref().map(|c| c.join("git").join("checkouts")), Entries::AtDepth(2));
+    add("npm", dirs::home_dir().map(|home| home.join(".npm").join("_cacache")), Entries::Whole);
+    add("yarn", dirs::cache_dir().map(|cache| cache.join("yarn")), Entries::Whole);
+    add("pip", dirs::cache_dir().map(|cache| cache.join("pip")), Entries::Whole);
+    add(
+        "uv",
+        std::env::var_os("UV_CACHE_DIR")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| dirs::cache_dir().map(|cache| cache.join("uv"))),
+        Entries::Whole,
+    );
+    add("poetry", poetry_cache().map(|poetry| poetry.join("cache")), Entries::Whole);
+    add("poetry", poetry_cache().map(|poetry| poetry.join("artifacts")), Entries::Whole);
+    add("pipenv", dirs::cache_dir().map(|cache| cache.join("pipenv")), Entries::Whole);
+    // Gradle's per-version caches and transforms, downloaded dependencies
+    // by group, module and version, and its daemons' logs; Maven's
+    // artifacts by version
+    let gradle = tool_home("GRADLE_USER_HOME", ".gradle");
+    add("gradle", gradle.as_ref().map(|g| g.join("caches")), Entries::AtDepth(1));
+    add(
+        "gradle",
+        gradle.as_ref().map(|g| g.join("caches").join("modules-2").join("files-2.1")),
+        Entries::AtDepth(3),
+    );
+    add("gradle", gradle.as_ref().map(|g| g.join("daemon")), Entries::Files("log"));
+    add("maven", dirs::home_dir().map(|home| home.join(".m2").join("repository")), Entries::Holding("pom"));
+    add("go", dirs::cache_dir().map(|cache| cache.join("go-build")), Entries::Whole);
+    // Browsers downloaded for testing by build, and Electron's and
+    // electron-builder's downloads by release and tool version
+    add("playwright", tool_dir("PLAYWRIGHT_BROWSERS_PATH", "ms-playwright"), Entries::AtDepth(1));
+    add("puppeteer", tool_home("PUPPETEER_CACHE_DIR", ".cache/puppeteer"), Entries::AtDepth(2));
+    add("electron", tool_dir("ELECTRON_CACHE", "electron"), Entries::AtDepth(1));
+    add("electron-builder", tool_dir("ELECTRON_BUILDER_CACHE", "electron-builder"), Entries::AtDepth(2));
+    // Xcode's build products and indexes by project, archived builds by day,
+    // and symbols copied from each iOS version a device was attached with
+    if cfg!(target_os = "macos") {
+        let xcode = dirs::home_dir().map(|home| home.join("Library").join("Developer").join("Xcode"));
+        add("xcode", xcode.as_ref().map(|x| x.join("DerivedData")), Entries::AtDepth(1));
+        add("xcode", xcode.as_ref().map(|x| x.join("Archives")), Entries::AtDepth(2));
+        add("xcode", xcode.as_ref().map(|x| x.join("iOS DeviceSupport")), Entries::AtDepth(1));
+    }
+    // Compiled objects, which the tools trim to a size limit themselves
+    add("sccache", tool_dir("SCCACHE_DIR", "sccache"), Entries::Whole);
+    add("ccache", ccache_dir(), Entries::Whole);
+    // NuGet's packages by id and version, and its cache of feed responses
+    // and downloads, offered with the .NET artifacts
+    let nuget = [
+        (tool_home("NUGET_PACKAGES", ".nuget/packages"), Entries::AtDepth(2)),
+        (nuget_http_cache(), Entries::Files("dat")),
+    ];
+    for (path, entries) in nuget {
+        if let Some(path) = path {
+            caches.push(GlobalCache {
+                tool: "nuget",
+                path,
+                entries,
+                project_type: ProjectType::DotNet,
+            });
+        }
+    }
+    if let Some(path) = go_modcache() {
+        caches.push(GlobalCache {
+            tool: "go",
+            path,
+            entries: Entries::Whole,
+            project_type: ProjectType::GoModules,
+        });
+    }
+    for cache in &mut caches {
+        if let Ok(path) = cache.path.canonicalize() {
+            cache.path = path;
+        }
+    }
+    caches
+}
+
+/// Go's module cache: `$GOMODCACHE`, else `pkg/mod` in the first `$GOPATH`
+/// entry, which defaults to `~/go`.
+fn go_modcache() -> Option<PathBuf> {
+    if let Some(modcache) = std::env::var_os("GOMODCACHE").filter(|v| !v.is_empty()) {
+        return Some(PathBuf::from(modcache));
+    }
+    let gopath = std::env::var_os("GOPATH")
+        .and_then(|paths| std::env::split_paths(&paths).find(|p| !p.as_os_str().is_empty()))
+        .or_else(|| dirs::home_dir().map(|home| home.join("go")))?;
+    Some(gopath.join("pkg").join("mod"))
+}
+
+impl GlobalCache {
+    /// The size its tool is configured to keep it under, for the compiler
+    /// caches that have one.
+    pub fn size_limit(&self) -> Option<u64> {
+        match self.tool {
+            "ccache" => ccache_limit(&self.path),
+            "sccache" => sccache_limit(),
+            _ => None,
+        }
+    }
+
+    /// Whether it is offered entry by entry rather than whole.
+    pub fn by_entry(&self) -> bool {
+        self.entries != Entries::Whole
+    }
+
+    /// Whether `path` is this cache, or one of its entries if it is offered
+    /// entry by entry.
+    pub fn holds(&self, path: &Path) -> bool {
+        if self.by_entry() && self.other_caches().iter().any(|other| other.starts_with(path)) {
+            return false;
+        }
+        match self.entries {
+            Entries::Whole => path == self.path,
+            Entries::AtDepth(depth) => path.ancestors().nth(depth) == Some(self.path.as_path()),
+            Entries::Holding(extension) => path != self.path && path.starts_with(&self.path) && has_file(path, extension),
+            Entries::Files(extension) => {
+                path.starts_with(&self.path) && path.extension().is_some_and(|ext| ext == extension)
+            }
+        }
+    }
+
+    /// Entries unused for at least `keep`, or every entry without a `keep`.
+    pub fn stale_entries(&self, keep: Option<Duration>) -> Vec<PathBuf> {
+        let walk = WalkDir::new(&self.path).min_depth(1);
+        let entries: Vec<PathBuf> = match self.entries {
+            Entries::Whole => return Vec::new(),
+            Entries::AtDepth(depth) => walk
+                .min_depth(depth)
+                .max_depth(depth)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .map(|e| e.into_path())
+                .collect(),
+            Entries::Holding(extension) => {
+                let mut entries = Vec::new();
+                let mut walk = walk.into_iter();
+                while let Some(entry) = walk.next() {
+                    let Ok(entry) = entry else {
+                        continue;
+                    };
+                    if entry.file_type().is_dir() && has_file(entry.path(), extension) {
+                        entries.push(entry.into_path());
+                        walk.skip_current_dir();
+                    }
+                }
+                entries
+            }
+            Entries::Files(extension) => walk
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|ext| ext == extension))
+                .map(|e| e.into_path())
+                .collect(),
+        };
+        let others = self.other_caches();
+        let now = SystemTime::now();
+        entries
+            .into_iter()
+            .filter(|entry| !others.iter().any(|other| other.starts_with(entry)))
+            .filter(|entry| {
+                keep.is_none_or(|keep| {
+                    last_used(entry).is_none_or(|used| now.duration_since(used).unwrap_or_default() >= keep)
+                })
+            })
+            .collect()
+    }
+
+    /// Every other known cache's path. An entry holding one, such as the
+    /// directory Gradle keeps its downloaded dependencies in, is left to it.
+    fn other_caches(&self) -> Vec<PathBuf> {
+        known_caches()
+            .into_iter()
+            .map(|cache| cache.path)
+            .filter(|path| *path != self.path)
+            .collect()
+    }
+}
+
+/// The size limit of the compiler cache at `path`, if it is one.
+pub fn size_limit(path: &Path) -> Option<u64> {
+    known_caches().iter().find(|cache| cache.path == path)?.size_limit()
+}
+
+/// `max_size` from `$CCACHE_MAXSIZE` or ccache's configuration, 5 GB when
+/// unset; `None` when set to 0, which is no limit. A bare number is in GB.
+fn ccache_limit(dir: &Path) -> Option<u64> {
+    let configured = std::env::var("CCACHE_MAXSIZE").ok().or_else(|| {
+        let mut files = vec![dir.join("ccache.conf")];
+        files.extend(std::env::var_os("CCACHE_CONFIGPATH").map(PathBuf::from));
+        files.extend(dirs::config_dir().map(|config| config.join("ccache").join("ccache.conf")));
+        files.iter().find_map(|file| {
+            let contents = fs::read_to_string(file).ok()?;
+            contents.lines().find_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                (key.trim() == "max_size").then(|| value.trim().to_string())
+            })
+        })
+    });
+    let max_size = configured.unwrap_or_else(|| "5G".to_string());
+    let limit = match units::parse_size(&max_size) {
+        Ok(limit) => limit,
+        Err(_) => units::parse_size(&format!("{}G", max_size)).ok()?,
+    };
+    (limit > 0).then_some(limit)
+}
+
+/// sccache's local cache size from `$SCCACHE_CACHE_SIZE` or the `[cache.disk]`
+/// table of its configuration, 10 GiB when unset.
+fn sccache_limit() -> Option<u64> {
+    if let Ok(size) = std::env::var("SCCACHE_CACHE_SIZE") {
+        return units::parse_size(&size).ok();
+    }
+    let config = std::env::var_os("SCCACHE_CONF")
+        .map(PathBuf::from)
+        .or_else(|| dirs::config_dir().map(|config| config.join("sccache").join("config")));
+    let configured = config
+        .and_then(|file| fs::read_to_string(file).ok())
+        .and_then(|contents| contents.parse::<toml::Table>().ok())
+        .and_then(|table| table.get("cache")?.get("disk")?.get("size")?.as_integer());
+    Some(configured.map_or(10 << 30, |size| size.max(0) as u64))
+}
+
+/// Hand the compiler cache at `path` to its own tool before it is deleted:
+/// ccache is emptied with `ccache --clear`, which keeps its configuration
+/// and statistics, and sccache's server is stopped so it doesn't write into
+/// a half-deleted cache. Returns `None`, leaving the directory to be deleted
+/// as usual, when `path` isn't a compiler cache or the tool isn't installed.
+pub fn clear_with_tool(path: &Path) -> Option<io::Result<()>> {
+    let cache = known_caches().into_iter().find(|cache| cache.path == path)?;
+    let run = |args: &[&str], env: &str| {
+        Command::new(cache.tool)
+            .args(args)
+            .env(env, path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+    };
+    match cache.tool {
+        "ccache" => match run(&["--clear"], "CCACHE_DIR") {
+            Ok(status) if status.success() => Some(Ok(())),
+            Ok(status) => Some(Err(io::Error::other(format!("ccache --clear failed ({})", status)))),
+            Err(_) => None,
+        },
+        "sccache" => {
+            // Fails harmlessly when no server is running
+            let _ = run(&["--stop-server"], "SCCACHE_DIR");
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Whether `dir` directly holds a file with `extension`.
+fn has_file(dir: &Path, extension: &str) -> bool {
+    fs::read_dir(dir).is_ok_and(|entries| {
+        entries
+            .filter_map(|e| e.ok())
+            .any(|e| e.path().extension().is_some_and(|ext| ext == extension))
+    })
+}
+
+/// When `entry`, or a file directly inside it, was last read or written.
+/// Directories' access times are left out, since listing them (as a scan
+/// does) counts as access.
+fn last_used(entry: &Path) -> Option<SystemTime> {
+    let times = |meta: fs::Metadata| {
+        let accessed = meta.is_file().then(|| meta.accessed().ok()).flatten();
+        [accessed, meta.modified().ok()]
+    };
+    let own = fs::symlink_metadata(entry).ok().map(times);
+    let inside = fs::read_dir(entry)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok()?.metadata().ok())
+        .map(times);
+    own.into_iter().chain(inside).flatten().flatten().max()
+}
+
+/// `$name` if set, else `default` under the user's cache directory.
+fn tool_dir(name: &str, default: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::cache_dir().map(|cache| cache.join(default)))
+}
+
+/// ccache's directory: `$CCACHE_DIR`, else `~/.ccache` where an older
+/// version left one, else under the user's cache directory.
+fn ccache_dir() -> Option<PathBuf> {
+    if std::env::var_os("CCACHE_DIR").is_none_or(|v| v.is_empty())
+        && let Some(legacy) = dirs::home_dir().map(|home| home.join(".ccache")).filter(|dir| dir.is_dir())
+    {
+        return Some(legacy);
+    }
+    tool_dir("CCACHE_DIR", "ccache")
+}
+
+/// NuGet's HTTP cache: `$NUGET_HTTP_CACHE_PATH`, else under the local
+/// application data folder on Windows and `~/.local/share` elsewhere.
+fn nuget_http_cache() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("NUGET_HTTP_CACHE_PATH").filter(|v| !v.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    let data = if cfg!(windows) {
+        dirs::data_local_dir()
+    } else {
+        dirs::home_dir().map(|home| home.join(".local").join("share"))
+    };
+    data.map(|data| data.join("NuGet").join("v3-cache"))
+}
+
+/// Poetry's cache directory, which also holds the environments it creates.
+fn poetry_cache() -> Option<PathBuf> {
+    std::env::var_os("POETRY_CACHE_DIR")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::cache_dir().map(|cache| cache.join("pypoetry")))
+}
+
+/// A virtualenv kept outside its project whose project is gone.
+pub struct OrphanedEnv {
+    pub tool: &'static str,
+    pub path: PathBuf,
+    /// The project it was made for, which no longer exists
+    pub project: PathBuf,
+    /// The file naming the project
+    pub marker: PathBuf,
+}
+
+/// Python virtualenvs made by pipenv, virtualenvwrapper or Poetry whose
+/// projects no longer exist. Environments that don't say which project
+/// they belong to are left alone.
+pub fn orphaned_envs() -> Vec<OrphanedEnv> {
+    let workon = tool_home("WORKON_HOME", ".virtualenvs");
+    let pipenv = dirs::data_local_dir().map(|data| data.join("virtualenvs"));
+    let poetry = poetry_cache().map(|poetry| poetry.join("virtualenvs"));
+    let mut envs = Vec::new();
+    for (tool, home) in [("pipenv", pipenv), ("virtualenvwrapper", workon), ("poetry", poetry)] {
+        let Some(Ok(entries)) = home.and_then(|home| home.canonicalize().ok()).map(fs::read_dir) else {
+            continue;
+        };
+        for env in entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|env| env.is_dir()) {
+            if let Some((project, marker)) = env_project(&env)
+                && !project.exists()
+            {
+                envs.push(OrphanedEnv { tool, path: env, project, marker });
+            }
+        }
+    }
+    envs
+}
+
+/// The project a virtualenv belongs to, and the file saying so: `.project`
+/// as pipenv and virtualenvwrapper write it, else the path file an editable
+/// install of the project (as Poetry does) leaves in site-packages.
+fn env_project(env: &Path) -> Option<(PathBuf, PathBuf)> {
+    let marker = env.join(".project");
+    if let Ok(project) = fs::read_to_string(&marker) {
+        return Some((PathBuf::from(project.trim()), marker));
+    }
+    // Path dependencies installed as editable leave path files too, so only
+    // the one named after the project the environment is named for counts
+    let project_name = poetry_env_project(&env.file_name()?.to_string_lossy())?;
+    let lib = if cfg!(windows) { env.join("Lib") } else { env.join("lib") };
+    let site_packages: Vec<PathBuf> = if cfg!(windows) {
+        vec![lib.join("site-packages")]
+    } else {
+        fs::read_dir(&lib)
+            .ok()?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path().join("site-packages"))
+            .collect()
+    };
+    let mut found = site_packages
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|file| file.extension().is_some_and(|ext| ext == "pth"))
+        .filter(|file| file.file_stem().is_some_and(|stem| module_name(&stem.to_string_lossy()) == project_name))
+        .filter_map(|file| {
+            let contents = fs::read_to_string(&file).ok()?;
+            let line = contents.lines().map(str::trim).find(|line| Path::new(line).is_absolute())?;
+            Some((PathBuf::from(line), file))
+        });
+    let project = found.next()?;
+    // Which is the project's is unclear, so the environment isn't offered
+    found.next().is_none().then_some(project)
+}
+
+/// The project part of a Poetry environment's name, `{project}-{hash}-py{version}`,
+/// as a module name.
+fn poetry_env_project(env_name: &str) -> Option<String> {
+    let (rest, version) = env_name.rsplit_once("-py")?;
+    // The hash is 8 characters of URL-safe base64, which may include a `-`
+    let (project, hash) = rest.split_at_checked(rest.len().checked_sub(8)?)?;
+    let project = project.strip_suffix('-').filter(|project| !project.is_empty())?;
+    let is_version = version.split('.').all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+    let is_hash = hash.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    (is_version && is_hash).then(|| module_name(project))
+}
+
+/// `name` as Python spells it as a module, which is how editable installs
+/// name their path files: lowercase, with `_` for `-`, `.` and spaces.
+fn module_name(name: &str) -> String {
+    name.to_lowercase().replace(['-', '.', ' '], "_")
+}
+
+/// Well-known caches that exist on this machine.
+pub fn global_caches() -> Vec<GlobalCache> {
+    known_caches().into_iter().filter(|cache| cache.path.is_dir()).collect()
+}
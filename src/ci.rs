@@ -0,0 +1,80 @@
+use crate::cleaner::CleanResult;
+use crate::scanner::FoundDir;
+use safeclean::units::format_size;
+use std::fmt::Write as _;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// A foldable group in the GitHub Actions log, closed when dropped.
+pub struct Group;
+
+/// Start a log group titled `title`.
+pub fn group(title: &str) -> Group {
+    println!("::group::{}", title);
+    Group
+}
+
+impl Drop for Group {
+    fn drop(&mut self) {
+        println!("::endgroup::");
+    }
+}
+
+/// Add a markdown summary of what was `found` and, if a clean ran, what it
+/// freed to the job summary, when running as a GitHub Actions step.
+pub fn write_summary(found: &[FoundDir], cleaned: Option<&CleanResult>) -> io::Result<()> {
+    let Some(file) = std::env::var_os("GITHUB_STEP_SUMMARY").filter(|file| !file.is_empty()) else {
+        return Ok(());
+    };
+    append(Path::new(&file), &summary(found, cleaned))
+}
+
+fn summary(found: &[FoundDir], cleaned: Option<&CleanResult>) -> String {
+    let mut out = String::from("### safeclean\n\n");
+    let Some(result) = cleaned else {
+        let total = found.iter().map(|d| d.size_bytes).sum();
+        let _ = writeln!(
+            out,
+            "Found **{}** in {} directories; nothing was deleted.\n",
+            format_size(total),
+            found.len()
+        );
+        by_type(&mut out, "Reclaimable", found);
+        return out;
+    };
+
+    let _ = writeln!(
+        out,
+        "Freed **{}** from {} directories.\n",
+        format_size(result.total_cleaned()),
+        result.deleted.len()
+    );
+    by_type(&mut out, "Freed", &result.deleted);
+    if !result.failed.is_empty() {
+        let _ = writeln!(out, "**{} could not be deleted:**\n", result.failed.len());
+        for (dir, e) in &result.failed {
+            let _ = writeln!(out, "- `{}`: {}", dir.path.display(), e);
+        }
+    }
+    out
+}
+
+/// A table of `dirs` by project type, with their total size under `heading`.
+fn by_type(out: &mut String, heading: &str, dirs: &[FoundDir]) {
+    if dirs.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "| Type | Directories | {} |", heading);
+    let _ = writeln!(out, "| --- | ---: | ---: |");
+    for (project_type, dirs) in crate::group_by_type(dirs) {
+        let size = dirs.iter().map(|d| d.size_bytes).sum();
+        let name = project_type.name().replace('|', "\\|");
+        let _ = writeln!(out, "| {} | {} | {} |", name, dirs.len(), format_size(size));
+    }
+    out.push('\n');
+}
+
+fn append(file: &Path, summary: &str) -> io::Result<()> {
+    OpenOptions::new().create(true).append(true).open(file)?.write_all(summary.as_bytes())
+}
@@ -1,6 +1,14 @@
+use crate::audit;
+use crate::caches;
+use crate::docker;
+use crate::logging::verbose;
+use crate::projects::ProjectType;
+use crate::protect;
 use crate::scanner::FoundDir;
 use std::fs;
 use std::io;
+use std::path::Path;
+use walkdir::WalkDir;
 
 pub struct CleanResult {
     pub deleted: Vec<FoundDir>,
@@ -13,14 +21,129 @@ impl CleanResult {
     }
 }
 
-pub fn clean(dirs: Vec<FoundDir>) -> CleanResult {
+/// How directories are removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Delete,
+    /// Move to the platform trash so they can be restored
+    Trash,
+}
+
+impl Method {
+    fn remove(self, path: &Path) -> io::Result<()> {
+        // A directory swapped for a symlink since the scan: remove the link,
+        // never what it points at
+        let meta = path.symlink_metadata()?;
+        if meta.file_type().is_symlink() {
+            return fs::remove_file(path).or_else(|_| fs::remove_dir(path));
+        }
+        match self {
+            // A cache entry such as a downloaded crate
+            Method::Delete if meta.is_file() => fs::remove_file(path),
+            // Unlinks symlinks inside rather than following them
+            Method::Delete => fs::remove_dir_all(path),
+            Method::Trash => trash::delete(path).map_err(io::Error::other),
+        }
+    }
+}
+
+/// Give the owner write access to every directory in `path`, as `go clean
+/// -modcache` does, so a tree Go left read-only can be removed.
+fn make_writable(path: &Path) -> io::Result<()> {
+    for entry in WalkDir::new(path) {
+        let entry = entry.map_err(io::Error::other)?;
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let mut permissions = entry.metadata().map_err(io::Error::other)?.permissions();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if permissions.mode() & 0o200 != 0 {
+                continue;
+            }
+            permissions.set_mode(permissions.mode() | 0o200);
+        }
+        #[cfg(not(unix))]
+        {
+            if !permissions.readonly() {
+                continue;
+            }
+            permissions.set_readonly(false);
+        }
+        fs::set_permissions(entry.path(), permissions)?;
+    }
+    Ok(())
+}
+
+/// The first directory above `path` that is a symlink, if any. Scanned paths,
+/// global caches' included, are canonical, so one there means the tree
+/// changed since the scan.
+fn linked_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.symlink_metadata().is_ok_and(|meta| meta.file_type().is_symlink()))
+}
+
+/// Reported by [`clean_with_progress`] as each directory is processed.
+pub enum CleanProgress<'a> {
+    Started(&'a Path),
+    Deleted(&'a Path),
+    Failed(&'a Path, &'a io::Error),
+}
+
+pub fn clean(dirs: Vec<FoundDir>, method: Method) -> CleanResult {
+    clean_with_progress(dirs, method, |_| {})
+}
+
+pub fn clean_with_progress(
+    dirs: Vec<FoundDir>,
+    method: Method,
+    mut on_progress: impl FnMut(CleanProgress),
+) -> CleanResult {
     let mut deleted = Vec::new();
     let mut failed = Vec::new();
 
     for dir in dirs {
-        match fs::remove_dir_all(&dir.path) {
-            Ok(()) => deleted.push(dir),
-            Err(e) => failed.push((dir, e)),
+        on_progress(CleanProgress::Started(&dir.path));
+        let refusal = protect::check(&dir.path).or_else(|| {
+            linked_ancestor(&dir.path).map(|link| format!("{} is now a symlink", link.display()))
+        });
+        let outcome = match refusal {
+            Some(reason) => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("refusing to delete: {}", reason),
+            )),
+            None if dir.project_type == ProjectType::GoModules => {
+                make_writable(&dir.path).and_then(|()| method.remove(&dir.path))
+            }
+            // Docker objects can't go to the trash; the daemon removes them
+            None if dir.project_type == ProjectType::Docker => docker::remove(&dir.path),
+            // ccache may keep its configuration in its cache, so it empties
+            // the cache itself when deleting for good
+            None if method == Method::Delete
+                && dir.global
+                && let Some(cleared) = caches::clear_with_tool(&dir.path) =>
+            {
+                cleared
+            }
+            None => method.remove(&dir.path),
+        };
+        let method = if dir.project_type == ProjectType::Docker { Method::Delete } else { method };
+        if let Err(e) = audit::record(&dir, method, outcome.as_ref().map(|_| ())) {
+            verbose!("could not write the audit log: {}", e);
+        }
+        match outcome {
+            Ok(()) => {
+                verbose!("deleted {}", dir.path.display());
+                on_progress(CleanProgress::Deleted(&dir.path));
+                deleted.push(dir);
+            }
+            Err(e) => {
+                verbose!("could not delete {}: {}", dir.path.display(), e);
+                on_progress(CleanProgress::Failed(&dir.path, &e));
+                failed.push((dir, e));
+            }
         }
     }
 
@@ -0,0 +1,331 @@
+use crate::projects::{CleanableDir, ProjectType};
+use crate::units;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+/// Contents of `config.toml` as written by the user. Profiles use the same
+/// keys, and override the top-level settings they set.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawConfig {
+    types: Option<Vec<String>>,
+    exclude: Option<Vec<PathBuf>>,
+    protect: Option<Vec<PathBuf>>,
+    roots: Option<Vec<PathBuf>>,
+    older_than: Option<String>,
+    min_size: Option<String>,
+    cache_keep: Option<String>,
+    max_delete: Option<String>,
+    confirm_over: Option<String>,
+    confirm_count: Option<usize>,
+    trash: Option<bool>,
+    detectors: Option<Vec<RawDetector>>,
+    profiles: BTreeMap<String, RawConfig>,
+}
+
+/// A `[[detectors]]` entry: a custom rule for a build system safeclean
+/// doesn't know.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawDetector {
+    label: String,
+    dir: String,
+    sibling: Option<String>,
+    contains: Option<String>,
+    #[serde(default = "selected_by_default")]
+    selected: bool,
+}
+
+fn selected_by_default() -> bool {
+    true
+}
+
+impl RawConfig {
+    /// These settings with `profile`'s laid over them.
+    fn overlay(&self, profile: &RawConfig) -> RawConfig {
+        RawConfig {
+            types: profile.types.clone().or_else(|| self.types.clone()),
+            exclude: profile.exclude.clone().or_else(|| self.exclude.clone()),
+            protect: profile.protect.clone().or_else(|| self.protect.clone()),
+            roots: profile.roots.clone().or_else(|| self.roots.clone()),
+            older_than: profile.older_than.clone().or_else(|| self.older_than.clone()),
+            min_size: profile.min_size.clone().or_else(|| self.min_size.clone()),
+            cache_keep: profile.cache_keep.clone().or_else(|| self.cache_keep.clone()),
+            max_delete: profile.max_delete.clone().or_else(|| self.max_delete.clone()),
+            confirm_over: profile.confirm_over.clone().or_else(|| self.confirm_over.clone()),
+            confirm_count: profile.confirm_count.or(self.confirm_count),
+            trash: profile.trash.or(self.trash),
+            detectors: profile.detectors.clone().or_else(|| self.detectors.clone()),
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+/// Contents of a `--rules` file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawRules {
+    protect: Vec<PathBuf>,
+    detectors: Vec<RawDetector>,
+}
+
+/// Detectors and protected paths shared through a `--rules` file.
+#[derive(Debug, Default)]
+pub struct Rules {
+    pub detectors: Vec<CleanableDir>,
+    /// Paths never offered for cleaning nor deleted, resolved against the
+    /// file's directory
+    pub protect: Vec<PathBuf>,
+}
+
+/// Defaults loaded from the config file. Command-line flags override them.
+#[derive(Debug, Default)]
+pub struct Config {
+    /// Project types to clean; empty means all
+    pub types: Vec<ProjectType>,
+    /// Paths never offered for cleaning, on top of the exclude file
+    pub exclude: Vec<PathBuf>,
+    /// Trees nothing is ever deleted under, whatever a scan or plan says
+    pub protect: Vec<PathBuf>,
+    /// Directories scanned when no path is given
+    pub roots: Vec<PathBuf>,
+    pub older_than: Option<Duration>,
+    pub min_size: Option<u64>,
+    /// Cache entries used more recently than this stay cached
+    pub cache_keep: Option<Duration>,
+    /// Deleting more than this in one run needs an extra confirmation
+    pub max_delete: Option<u64>,
+    /// Deleting more than this, or more directories than `confirm_count`,
+    /// takes typing a word to confirm
+    pub confirm_over: Option<u64>,
+    pub confirm_count: Option<usize>,
+    /// Move directories to the trash instead of deleting them
+    pub trash: bool,
+    /// Custom rules, checked before the built-in ones
+    pub detectors: Vec<CleanableDir>,
+}
+
+/// Written by `config edit` when there is no config file yet.
+const TEMPLATE: &str = r#"# safeclean configuration. Command-line flags override these settings.
+
+# Project types to clean (rust, node, python, java, gradle, dotnet, next, nuxt).
+# Leave empty for all.
+# types = ["rust", "node"]
+
+# Directories scanned when no path is given.
+# roots = ["~/projects"]
+
+# Paths never offered for cleaning, on top of the exclude file.
+# exclude = ["~/projects/keep-me"]
+
+# Trees nothing is ever deleted under, even from a plan file. System
+# directories and the home directory itself are always protected.
+# protect = ["/srv/production"]
+
+# Only offer directories untouched for longer than this (by default 2h, so a
+# build still running is left alone; "0" offers everything), or larger than this.
+# older_than = "30d"
+# min_size = "100MB"
+
+# With --global, keep cache entries such as downloaded crates or jars used
+# within this long (by default 30d; "0" offers every entry).
+# cache_keep = "30d"
+
+# Ask again before deleting more than this in one run; unattended runs refuse.
+# max_delete = "50GB"
+
+# Confirm by typing a word, not just pressing Enter, before deleting more than
+# this or more than this many directories.
+# confirm_over = "20GB"
+# confirm_count = 100
+
+# Move directories to the trash instead of deleting them.
+# trash = true
+
+# Custom rules for build systems safeclean doesn't know. `dir` may use * and ?
+# wildcards; `sibling` (a file next to it) or `contains` (a file inside it)
+# narrows the match, and `selected = false` starts its directories unselected.
+# [[detectors]]
+# label = "Bazel"
+# dir = "bazel-out"
+# sibling = "WORKSPACE"
+# selected = false
+
+# Named profiles override the settings above when picked with --profile.
+# [profiles.conservative]
+# older_than = "90d"
+# trash = true
+#
+# [profiles.aggressive]
+# older_than = "0d"
+# min_size = "0B"
+"#;
+
+pub fn config_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("safeclean").join("config.toml"))
+}
+
+/// Expand a leading `~` to the home directory.
+fn expand_home(path: PathBuf) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path,
+    }
+}
+
+/// Load the config file, applying `profile` if given. A missing file is an
+/// empty config; one that can't be parsed, or lacks the profile, is an error
+/// naming the file.
+pub fn load(profile: Option<&str>) -> Result<Config, String> {
+    let file = config_file();
+    let contents = file
+        .as_ref()
+        .and_then(|file| fs::read_to_string(file).ok())
+        .unwrap_or_default();
+    let name = file.map_or_else(|| "config".to_string(), |file| file.display().to_string());
+    parse(&contents, profile).map_err(|e| format!("{}: {}", name, e))
+}
+
+fn parse(contents: &str, profile: Option<&str>) -> Result<Config, String> {
+    let raw: RawConfig = toml::from_str(contents).map_err(|e| e.message().to_string())?;
+    for (name, settings) in &raw.profiles {
+        if !settings.profiles.is_empty() {
+            return Err(format!("profile '{}' can't contain profiles", name));
+        }
+    }
+
+    let Some(profile) = profile else {
+        return convert(raw);
+    };
+    match raw.profiles.get(profile) {
+        Some(settings) => convert(raw.overlay(settings)),
+        None if raw.profiles.is_empty() => Err(format!("no profile '{}' (none are defined)", profile)),
+        None => Err(format!(
+            "no profile '{}' (available: {})",
+            profile,
+            raw.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+/// Load a rules file. Errors name the file.
+pub fn load_rules(file: &Path) -> Result<Rules, String> {
+    let name = file.display();
+    let contents = fs::read_to_string(file).map_err(|e| format!("{}: {}", name, e))?;
+    let raw: RawRules = toml::from_str(&contents).map_err(|e| format!("{}: {}", name, e.message()))?;
+    let base = file
+        .canonicalize()
+        .ok()
+        .and_then(|file| file.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    let detectors = raw
+        .detectors
+        .into_iter()
+        .map(|d| convert_detector(d).map_err(|e| format!("{}: {}", name, e)))
+        .collect::<Result<_, _>>()?;
+    // Scanned paths are canonical, so protect canonical paths where they exist
+    let protect = raw
+        .protect
+        .into_iter()
+        .map(|path| base.join(expand_home(path)))
+        .map(|path| path.canonicalize().unwrap_or(path))
+        .collect();
+    Ok(Rules { detectors, protect })
+}
+
+fn convert_detector(d: RawDetector) -> Result<CleanableDir, String> {
+    CleanableDir::detector(&d.label, &d.dir, d.sibling, d.contains, d.selected)
+}
+
+fn convert(raw: RawConfig) -> Result<Config, String> {
+    let types = raw
+        .types
+        .unwrap_or_default()
+        .iter()
+        .map(|name| ProjectType::parse_flag(name))
+        .collect::<Result<_, _>>()?;
+    let detectors = raw
+        .detectors
+        .unwrap_or_default()
+        .into_iter()
+        .map(convert_detector)
+        .collect::<Result<_, _>>()?;
+
+    // Scanned paths are canonical, so exclude and protect canonical paths
+    // where they exist
+    let canonical = |paths: Option<Vec<PathBuf>>| -> Vec<PathBuf> {
+        paths
+            .unwrap_or_default()
+            .into_iter()
+            .map(expand_home)
+            .map(|path| path.canonicalize().unwrap_or(path))
+            .collect()
+    };
+
+    Ok(Config {
+        types,
+        exclude: canonical(raw.exclude),
+        protect: canonical(raw.protect),
+        roots: raw.roots.unwrap_or_default().into_iter().map(expand_home).collect(),
+        older_than: raw.older_than.as_deref().map(units::parse_duration).transpose()?,
+        min_size: raw.min_size.as_deref().map(units::parse_size).transpose()?,
+        cache_keep: raw.cache_keep.as_deref().map(units::parse_duration).transpose()?,
+        max_delete: raw.max_delete.as_deref().map(units::parse_size).transpose()?,
+        confirm_over: raw.confirm_over.as_deref().map(units::parse_size).transpose()?,
+        confirm_count: raw.confirm_count,
+        trash: raw.trash.unwrap_or(false),
+        detectors,
+    })
+}
+
+/// Print where the config file lives and what it contains.
+pub fn show(file: &Path) -> io::Result<()> {
+    match fs::read_to_string(file) {
+        Ok(contents) => {
+            println!("# {}", file.display());
+            print!("{}", contents);
+            Ok(())
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            println!("No config file at {} (`safeclean config edit` creates one)", file.display());
+            Ok(())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Open the config file in the user's editor, starting from a commented
+/// template if it doesn't exist, and check that it still parses afterwards.
+pub fn edit(file: &Path) -> io::Result<()> {
+    if !file.exists() {
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(file, TEMPLATE)?;
+    }
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    // Editors are often configured with arguments, e.g. "code --wait"
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = Command::new(program).args(words).arg(file).status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("{} exited with {}", program, status)));
+    }
+
+    // Check the profiles too, so a typo in one shows up now
+    let contents = fs::read_to_string(file)?;
+    let raw: RawConfig = toml::from_str(&contents).map_err(|e| io::Error::other(e.message().to_string()))?;
+    parse(&contents, None).map_err(io::Error::other)?;
+    for name in raw.profiles.keys() {
+        parse(&contents, Some(name)).map_err(|e| io::Error::other(format!("profile '{}': {}", name, e)))?;
+    }
+    Ok(())
+}
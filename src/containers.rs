@@ -0,0 +1,79 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Container engines asked for their running containers, in turn.
+const ENGINES: &[&str] = &["docker", "podman"];
+
+/// What `inspect` reports about a container, as far as mounts go.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Inspected {
+    name: String,
+    #[serde(default)]
+    mounts: Vec<Mount>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Mount {
+    source: PathBuf,
+}
+
+/// Host paths mounted into running containers, gathered once per scan.
+#[derive(Debug, Default)]
+pub struct Mounts {
+    /// Mount sources, with the name of the container using each
+    sources: Vec<(PathBuf, String)>,
+}
+
+impl Mounts {
+    /// Ask each container engine that is installed and running. Engines that
+    /// aren't, or that this user may not talk to, add nothing.
+    pub fn load() -> Mounts {
+        let sources = ENGINES
+            .iter()
+            .flat_map(|engine| running(engine))
+            .flat_map(|container| {
+                let name = container.name.trim_start_matches('/').to_string();
+                container.mounts.into_iter().map(move |mount| (mount.source, name.clone()))
+            })
+            .collect();
+        Mounts { sources }
+    }
+
+    /// The running container with `path`, something inside it or a
+    /// directory holding it mounted, if any.
+    pub fn container_for(&self, path: &Path) -> Option<&str> {
+        self.sources
+            .iter()
+            .find(|(source, _)| source.starts_with(path) || path.starts_with(source))
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+fn output(engine: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(engine)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Running containers of `engine` with their mounts.
+fn running(engine: &str) -> Vec<Inspected> {
+    let Some(ids) = output(engine, &["ps", "--quiet", "--no-trunc"]) else {
+        return Vec::new();
+    };
+    let ids: Vec<&str> = ids.split_whitespace().collect();
+    if ids.is_empty() {
+        return Vec::new();
+    }
+    let mut args = vec!["inspect"];
+    args.extend(ids);
+    output(engine, &args)
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,302 @@
+use crate::EXIT_ERROR;
+use crate::cleaner::{self, CleanProgress, Method};
+use crate::lock::{self, LockError};
+use crate::logging::{info, verbose};
+use crate::output::{CleanSummary, Record};
+use crate::scanner::{self, FoundDir, ScanEvent, ScanOptions};
+use colored::Colorize;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+/// How long a write to a connection may block. Progress goes out while a scan
+/// or clean holds the daemon, so a client that stops reading is dropped
+/// rather than left to stall it.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A JSON-RPC error code and message.
+type RpcError = (i64, String);
+
+/// The socket's write half, shared by the connection's responses and the
+/// progress notifications other connections' requests send it.
+type Writer = Arc<Mutex<UnixStream>>;
+
+/// Where the daemon listens unless `--socket` says otherwise.
+pub fn default_socket() -> Option<PathBuf> {
+    dirs::runtime_dir().map(|dir| dir.join("safeclean.sock")).or_else(|| {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| dir.join("safeclean").join("daemon.sock"))
+    })
+}
+
+/// One request: a line of JSON. Requests without an `id` are notifications
+/// and get no response.
+#[derive(Deserialize)]
+struct Request {
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct ScanParams {
+    /// Directories to scan instead of the daemon's own roots
+    roots: Option<Vec<PathBuf>>,
+}
+
+#[derive(Deserialize)]
+struct CleanParams {
+    /// Directories from the last scan to remove
+    paths: Vec<PathBuf>,
+    /// Move them to the trash, or delete them, whatever the daemon was
+    /// started with
+    trash: Option<bool>,
+}
+
+/// What the last scan found, less what has been cleaned since.
+#[derive(Default)]
+struct LastScan {
+    roots: Vec<PathBuf>,
+    found: Vec<FoundDir>,
+}
+
+struct Daemon {
+    roots: Vec<PathBuf>,
+    options: ScanOptions,
+    method: Method,
+    last: Mutex<LastScan>,
+    /// Connections that asked for progress notifications
+    subscribers: Mutex<Vec<Writer>>,
+    /// Held while scanning or cleaning, so only one runs at a time
+    busy: Mutex<()>,
+}
+
+/// Serve scans and cleans of `roots` on `socket` until killed. Returns the
+/// exit status if it couldn't start.
+pub fn run(socket: &Path, roots: Vec<PathBuf>, options: ScanOptions, method: Method) -> i32 {
+    if UnixStream::connect(socket).is_ok() {
+        eprintln!("{} a daemon is already listening on {}", "error:".red().bold(), socket.display());
+        return EXIT_ERROR;
+    }
+    // Left behind by a daemon that didn't get to clean up; anything else
+    // there is the user's
+    if fs::symlink_metadata(socket).is_ok_and(|meta| meta.file_type().is_socket()) {
+        let _ = fs::remove_file(socket);
+    }
+    if let Some(parent) = socket.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let listener = match UnixListener::bind(socket) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("{} could not listen on {}: {}", "error:".red().bold(), socket.display(), e);
+            return EXIT_ERROR;
+        }
+    };
+    // Only this user may make it delete things
+    if let Err(e) = fs::set_permissions(socket, fs::Permissions::from_mode(0o600)) {
+        eprintln!("{} could not restrict {}: {}", "warning:".yellow().bold(), socket.display(), e);
+    }
+    info!("Listening on {}", socket.display());
+
+    let daemon = Daemon {
+        roots,
+        options,
+        method,
+        last: Mutex::new(LastScan::default()),
+        subscribers: Mutex::new(Vec::new()),
+        busy: Mutex::new(()),
+    };
+    thread::scope(|s| {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let daemon = &daemon;
+                    s.spawn(move || daemon.serve(stream));
+                }
+                Err(e) => verbose!("could not accept a connection: {}", e),
+            }
+        }
+    });
+    0
+}
+
+/// Write `message` as one line.
+fn send(writer: &Writer, message: &Value) -> io::Result<()> {
+    let line = format!("{}\n", message);
+    writer.lock().unwrap().write_all(line.as_bytes())
+}
+
+fn parse<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| (INVALID_PARAMS, e.to_string()))
+}
+
+fn records(found: &[FoundDir]) -> Value {
+    Value::from_iter(found.iter().map(|dir| json!(Record::new(dir))))
+}
+
+impl Daemon {
+    /// Answer one connection's requests until it closes.
+    fn serve(&self, stream: UnixStream) {
+        // The timeout is the socket's, so the clone writing to it has it too
+        let writer: Writer = match stream.set_write_timeout(Some(WRITE_TIMEOUT)).and_then(|()| stream.try_clone()) {
+            Ok(clone) => Arc::new(Mutex::new(clone)),
+            Err(e) => {
+                verbose!("could not serve a connection: {}", e);
+                return;
+            }
+        };
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<Request>(&line) {
+                Err(e) => Some(error(Value::Null, (PARSE_ERROR, e.to_string()))),
+                Ok(request) => {
+                    let outcome = self.call(&request.method, request.params, &writer);
+                    request.id.map(|id| match outcome {
+                        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                        Err(e) => error(id, e),
+                    })
+                }
+            };
+            if let Some(response) = response
+                && send(&writer, &response).is_err()
+            {
+                break;
+            }
+        }
+    }
+
+    fn call(&self, method: &str, params: Value, writer: &Writer) -> Result<Value, RpcError> {
+        match method {
+            "scan" => self.scan(parse(params)?),
+            "list" => Ok(records(&self.last.lock().unwrap().found)),
+            "clean" => self.clean(parse(params)?),
+            "subscribe" => {
+                self.subscribers.lock().unwrap().push(Arc::clone(writer));
+                Ok(json!(true))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method '{}'", method))),
+        }
+    }
+
+    /// Send a progress notification to every subscriber, dropping those that
+    /// went away or stopped reading.
+    fn notify(&self, params: Value) {
+        let message = json!({ "jsonrpc": "2.0", "method": "progress", "params": params });
+        self.subscribers.lock().unwrap().retain(|writer| send(writer, &message).is_ok());
+    }
+
+    /// Scan, replacing the last scan's results, and return what was found.
+    fn scan(&self, params: ScanParams) -> Result<Value, RpcError> {
+        let roots = match params.roots {
+            Some(roots) => roots
+                .iter()
+                .map(|root| root.canonicalize().map_err(|e| (INVALID_PARAMS, format!("{}: {}", root.display(), e))))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => self.roots.clone(),
+        };
+        let _busy = self.busy.lock().unwrap();
+        let (events, receiver) = mpsc::channel();
+        let mut found = Vec::new();
+        thread::scope(|s| {
+            let roots = &roots;
+            s.spawn(move || scanner::scan_streaming(roots, &self.options, events));
+            for event in receiver {
+                match event {
+                    ScanEvent::Found(dir) => self.notify(json!({ "event": "found", "path": dir.path })),
+                    ScanEvent::Sized(dir) => {
+                        self.notify(json!({ "event": "sized", "path": dir.path, "size_bytes": dir.size_bytes }));
+                        found.push(dir);
+                    }
+                    ScanEvent::Unwanted(dir) => self.notify(json!({ "event": "skipped", "path": dir.path })),
+                    ScanEvent::Done(_) => self.notify(json!({ "event": "scanned", "count": found.len() })),
+                }
+            }
+        });
+        found.sort_by_key(|dir| std::cmp::Reverse(dir.size_bytes));
+        crate::remember_scan(&roots, &found);
+        let result = records(&found);
+        *self.last.lock().unwrap() = LastScan { roots, found };
+        Ok(result)
+    }
+
+    /// Remove directories the last scan found, and report how it went.
+    fn clean(&self, params: CleanParams) -> Result<Value, RpcError> {
+        let _busy = self.busy.lock().unwrap();
+        let (roots, to_clean) = {
+            let last = self.last.lock().unwrap();
+            let to_clean = params
+                .paths
+                .iter()
+                .map(|path| {
+                    last.found
+                        .iter()
+                        .find(|dir| dir.path == *path)
+                        .cloned()
+                        .ok_or_else(|| (INVALID_PARAMS, format!("{} was not found by the last scan", path.display())))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            (last.roots.clone(), to_clean)
+        };
+        let _locks = match lock::lock_roots(&roots) {
+            Ok(locks) => Some(locks),
+            Err(LockError::Held(root)) => {
+                return Err((
+                    SERVER_ERROR,
+                    format!("another safeclean run is cleaning {} or something in it", root.display()),
+                ));
+            }
+            Err(LockError::Io(e)) => {
+                verbose!("could not lock the scanned directories: {}", e);
+                None
+            }
+        };
+        let method = match params.trash {
+            Some(true) => Method::Trash,
+            Some(false) => Method::Delete,
+            None => self.method,
+        };
+
+        let started = SystemTime::now();
+        let result = cleaner::clean_with_progress(to_clean, method, |progress| match progress {
+            CleanProgress::Started(path) => self.notify(json!({ "event": "cleaning", "path": path })),
+            CleanProgress::Deleted(path) => self.notify(json!({ "event": "deleted", "path": path })),
+            CleanProgress::Failed(path, e) => {
+                self.notify(json!({ "event": "failed", "path": path, "error": e.to_string() }))
+            }
+        });
+        crate::record_clean(method, started, &result);
+        self.last
+            .lock()
+            .unwrap()
+            .found
+            .retain(|dir| !result.deleted.iter().any(|deleted| deleted.path == dir.path));
+        serde_json::to_value(CleanSummary::new(&result)).map_err(|e| (SERVER_ERROR, e.to_string()))
+    }
+}
+
+fn error(id: Value, (code, message): RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
@@ -0,0 +1,76 @@
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Open `path` in the platform's file manager without waiting for it.
+pub fn open_in_file_manager(path: &Path) -> io::Result<()> {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+
+    Command::new(program)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+/// Clipboard commands to try, in order, for the current platform.
+fn clipboard_commands() -> &'static [&'static [&'static str]] {
+    if cfg!(target_os = "macos") {
+        &[&["pbcopy"]]
+    } else if cfg!(windows) {
+        &[&["clip"]]
+    } else {
+        &[
+            &["wl-copy"],
+            &["xclip", "-selection", "clipboard"],
+            &["xsel", "--clipboard", "--input"],
+        ]
+    }
+}
+
+/// Copy `text` to the system clipboard using the first available helper.
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    for command in clipboard_commands() {
+        let Ok(mut child) = Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no clipboard tool found",
+    ))
+}
+
+/// Show a desktop notification: through the notification service on Linux
+/// and the BSDs, Notification Center on macOS and a toast on Windows.
+pub fn notify(title: &str, body: &str) -> io::Result<()> {
+    notify_rust::Notification::new()
+        .appname("safeclean")
+        .summary(title)
+        .body(body)
+        .show()
+        .map(|_| ())
+        .map_err(io::Error::other)
+}
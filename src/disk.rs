@@ -0,0 +1,154 @@
+use crate::format_size;
+use crate::logging::info;
+use crate::scanner::FoundDir;
+use colored::Colorize;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Free space on the filesystem holding `path`.
+pub struct DiskFree {
+    /// The first scanned path on this filesystem
+    pub path: PathBuf,
+    pub available: u64,
+}
+
+/// Which filesystem `path` is on.
+#[cfg(unix)]
+fn filesystem(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).ok().map(|meta| meta.dev().to_string())
+}
+
+/// Which filesystem `path` is on, going by its drive or share.
+#[cfg(not(unix))]
+fn filesystem(path: &Path) -> Option<String> {
+    path.components()
+        .next()
+        .map(|prefix| prefix.as_os_str().to_string_lossy().into_owned())
+}
+
+/// Where the filesystem holding `path` is mounted: its highest ancestor on
+/// the same device. A path since deleted counts as on its parent's.
+#[cfg(unix)]
+pub fn mount_point(path: &Path) -> Option<PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+    let existing = path.ancestors().find(|dir| dir.exists())?;
+    let device = std::fs::metadata(existing).ok()?.dev();
+    let mount = existing
+        .ancestors()
+        .take_while(|dir| std::fs::metadata(dir).is_ok_and(|meta| meta.dev() == device))
+        .last()?;
+    Some(mount.to_path_buf())
+}
+
+/// The drive or share holding `path`.
+#[cfg(not(unix))]
+pub fn mount_point(path: &Path) -> Option<PathBuf> {
+    path.ancestors().last().map(Path::to_path_buf)
+}
+
+/// Space taken by some of the found directories on one filesystem.
+#[derive(Debug, Serialize)]
+pub struct MountTotal {
+    pub mount: PathBuf,
+    pub size_bytes: u64,
+    pub dirs: usize,
+    /// Free space on the filesystem now, if it could be queried
+    pub available_bytes: Option<u64>,
+}
+
+/// Total size of `dirs` per filesystem, largest first.
+pub fn by_mount<'a>(dirs: impl IntoIterator<Item = &'a FoundDir>) -> Vec<MountTotal> {
+    let mut totals: Vec<MountTotal> = Vec::new();
+    for dir in dirs {
+        let Some(mount) = mount_point(&dir.path) else {
+            continue;
+        };
+        match totals.iter_mut().find(|total| total.mount == mount) {
+            Some(total) => {
+                total.size_bytes += dir.size_bytes;
+                total.dirs += 1;
+            }
+            None => totals.push(MountTotal {
+                available_bytes: fs4::available_space(&mount).ok(),
+                mount,
+                size_bytes: dir.size_bytes,
+                dirs: 1,
+            }),
+        }
+    }
+    totals.sort_by_key(|total| std::cmp::Reverse(total.size_bytes));
+    totals
+}
+
+/// Print how `dirs` split across filesystems, when they span more than
+/// one.
+pub fn print_by_mount(dirs: &[FoundDir]) {
+    let totals = by_mount(dirs);
+    if totals.len() < 2 {
+        return;
+    }
+    for total in totals {
+        let free = total
+            .available_bytes
+            .map_or_else(String::new, |available| format!(" ({} free)", format_size(available)));
+        info!(
+            "  {:>10} on {}{}",
+            format_size(total.size_bytes).green(),
+            total.mount.display(),
+            free.dimmed()
+        );
+    }
+}
+
+/// Free space on each filesystem holding one of `paths`, once per
+/// filesystem. Paths whose filesystem can't be queried are left out.
+pub fn free_space(paths: &[PathBuf]) -> Vec<DiskFree> {
+    let mut seen = Vec::new();
+    let mut disks = Vec::new();
+    for path in paths {
+        let Some(id) = filesystem(path) else {
+            continue;
+        };
+        if seen.contains(&id) {
+            continue;
+        }
+        if let Ok(available) = fs4::available_space(path) {
+            seen.push(id);
+            disks.push(DiskFree { path: path.clone(), available });
+        }
+    }
+    disks
+}
+
+/// Label for a filesystem's line: bare when there's only one.
+fn label(disk: &DiskFree, count: usize) -> String {
+    if count == 1 {
+        "Disk free:".to_string()
+    } else {
+        format!("Disk free ({}):", disk.path.display())
+    }
+}
+
+/// Print the free space measured before a scan.
+pub fn print_free(disks: &[DiskFree]) {
+    for disk in disks {
+        info!("{} {}", label(disk, disks.len()).bold(), format_size(disk.available));
+    }
+}
+
+/// Measure the same filesystems again and print how their free space
+/// changed since `before`.
+pub fn print_change(before: &[DiskFree]) {
+    for disk in before {
+        let Ok(available) = fs4::available_space(&disk.path) else {
+            continue;
+        };
+        info!(
+            "{} {} → {}",
+            label(disk, before.len()).bold(),
+            format_size(disk.available),
+            format_size(available).green().bold()
+        );
+    }
+}
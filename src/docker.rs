@@ -0,0 +1,284 @@
+use crate::logging::verbose;
+use serde::Deserialize;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Start of the paths Docker objects are reported under, such as
+/// `docker:image/3f2a9c1b8d0e`, since they have no path of their own.
+pub const PREFIX: &str = "docker:";
+
+/// How long to wait for the daemon, which sizes everything for `/system/df`.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Kinds of object the daemon can free space by removing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// An untagged image no container uses
+    Image,
+    /// A container that isn't running
+    Container,
+    /// A build cache record no build is using
+    BuildCache,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Image => "image",
+            Kind::Container => "container",
+            Kind::BuildCache => "build-cache",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Kind> {
+        [Kind::Image, Kind::Container, Kind::BuildCache]
+            .into_iter()
+            .find(|kind| kind.name() == name)
+    }
+}
+
+/// An unused object the daemon could remove.
+#[derive(Debug)]
+pub struct Object {
+    pub kind: Kind,
+    pub id: String,
+    /// What it is, e.g. "exited container web-1 (nginx:1.27)"
+    pub description: String,
+    /// Space removing it frees
+    pub size_bytes: u64,
+    /// When it was created, or for build cache, last used
+    pub modified: Option<SystemTime>,
+}
+
+impl Object {
+    /// The path it is reported under.
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(format!("{}{}/{}", PREFIX, self.kind.name(), self.id))
+    }
+}
+
+/// The kind and ID of the object reported under `path`, if it is one.
+pub fn parse(path: &Path) -> Option<(Kind, &str)> {
+    let (kind, id) = path.to_str()?.strip_prefix(PREFIX)?.split_once('/')?;
+    Some((Kind::from_name(kind)?, id))
+}
+
+/// What `/system/df` reports, as far as pruning goes. Empty lists come as
+/// `null`.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DiskUsage {
+    images: Option<Vec<Image>>,
+    containers: Option<Vec<Container>>,
+    build_cache: Option<Vec<BuildRecord>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Image {
+    id: String,
+    created: i64,
+    size: i64,
+    shared_size: i64,
+    repo_tags: Option<Vec<String>>,
+    containers: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Container {
+    id: String,
+    names: Vec<String>,
+    image: String,
+    state: String,
+    size_rw: Option<i64>,
+    created: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct BuildRecord {
+    #[serde(rename = "ID")]
+    id: String,
+    #[serde(rename = "Type")]
+    record_type: String,
+    size: i64,
+    in_use: bool,
+    shared: bool,
+    description: Option<String>,
+    last_used_at: Option<String>,
+    created_at: String,
+}
+
+/// Dangling images, stopped containers and idle build cache, largest first.
+/// Nothing when the daemon isn't running or this user may not talk to it.
+pub fn unused() -> Vec<Object> {
+    let usage: DiskUsage = match request("GET", "/system/df").and_then(|body| {
+        serde_json::from_str(&body).map_err(io::Error::other)
+    }) {
+        Ok(usage) => usage,
+        Err(e) => {
+            verbose!("skip docker: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut objects = Vec::new();
+    for image in usage.images.unwrap_or_default() {
+        let tagged = image.repo_tags.iter().flatten().any(|tag| tag != "<none>:<none>");
+        if tagged || image.containers > 0 {
+            continue;
+        }
+        let id = image.id.trim_start_matches("sha256:");
+        objects.push(Object {
+            kind: Kind::Image,
+            id: short(id),
+            description: "dangling image".to_string(),
+            size_bytes: (image.size - image.shared_size.max(0)).max(0) as u64,
+            modified: unix_time(image.created),
+        });
+    }
+    for container in usage.containers.unwrap_or_default() {
+        if !matches!(container.state.as_str(), "exited" | "created" | "dead") {
+            continue;
+        }
+        let name = container.names.first().map_or("", |name| name.trim_start_matches('/'));
+        objects.push(Object {
+            kind: Kind::Container,
+            id: short(&container.id),
+            description: format!("{} container {} ({})", container.state, name, container.image),
+            size_bytes: container.size_rw.unwrap_or(0).max(0) as u64,
+            modified: unix_time(container.created),
+        });
+    }
+    for record in usage.build_cache.unwrap_or_default() {
+        // Shared records free nothing until everything sharing them goes
+        if record.in_use || record.shared {
+            continue;
+        }
+        let used = record.last_used_at.as_deref().unwrap_or(&record.created_at);
+        objects.push(Object {
+            kind: Kind::BuildCache,
+            description: match record.description {
+                Some(description) if !description.is_empty() => format!("build cache: {}", description),
+                _ => format!("build cache ({})", record.record_type),
+            },
+            id: record.id,
+            size_bytes: record.size.max(0) as u64,
+            modified: parse_time(used),
+        });
+    }
+    objects.sort_by_key(|object| std::cmp::Reverse(object.size_bytes));
+    objects
+}
+
+/// Remove the object reported under `path`. Images still used by a
+/// container and containers started since the scan are refused by the
+/// daemon.
+pub fn remove(path: &Path) -> io::Result<()> {
+    let (kind, id) = parse(path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a Docker object"))?;
+    match kind {
+        Kind::Image => request("DELETE", &format!("/images/{}", id)),
+        Kind::Container => request("DELETE", &format!("/containers/{}", id)),
+        Kind::BuildCache => {
+            let filters = format!("{{\"id\":[\"{}\"]}}", id);
+            request("POST", &format!("/build/prune?filters={}", encode(&filters)))
+        }
+    }
+    .map(|_| ())
+}
+
+/// Image and container IDs as `docker` shows them.
+fn short(id: &str) -> String {
+    id.chars().take(12).collect()
+}
+
+fn unix_time(seconds: i64) -> Option<SystemTime> {
+    (seconds > 0).then(|| UNIX_EPOCH + Duration::from_secs(seconds as u64))
+}
+
+/// An RFC 3339 time such as `2026-03-14T09:26:53.58979Z`, to the second.
+/// Offsets other than `Z` are taken as UTC, which is close enough for ages
+/// counted in days.
+fn parse_time(time: &str) -> Option<SystemTime> {
+    let field = |range: std::ops::Range<usize>| time.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    // Howard Hinnant's days-from-civil algorithm
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    unix_time(days * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+/// Percent-encode a query parameter.
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// The daemon's socket: `$DOCKER_HOST` if it names one, else the system
+/// socket, else a rootless daemon's. `None` when `$DOCKER_HOST` points
+/// elsewhere, such as at a TCP address.
+fn socket() -> Option<PathBuf> {
+    if let Some(host) = std::env::var("DOCKER_HOST").ok().filter(|host| !host.is_empty()) {
+        return host.strip_prefix("unix://").map(PathBuf::from);
+    }
+    let system = PathBuf::from("/var/run/docker.sock");
+    if system.exists() {
+        return Some(system);
+    }
+    dirs::runtime_dir()
+        .map(|dir| dir.join("docker.sock"))
+        .filter(|rootless| rootless.exists())
+        .or(Some(system))
+}
+
+/// Make an Engine API request, returning the response body.
+#[cfg(unix)]
+fn request(method: &str, endpoint: &str) -> io::Result<String> {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+
+    let socket = socket().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::Unsupported, "DOCKER_HOST is not a unix socket")
+    })?;
+    let mut stream = UnixStream::connect(&socket)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    // HTTP/1.0 so the daemon closes the connection after an unchunked body
+    let request = format!("{} {} HTTP/1.0\r\nHost: docker\r\nContent-Length: 0\r\n\r\n", method, endpoint);
+    stream.write_all(request.as_bytes())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| io::Error::other("malformed response from the Docker daemon"))?;
+    let status = head.lines().next().unwrap_or_default();
+    if status.split_whitespace().nth(1).is_some_and(|code| code.starts_with('2')) {
+        return Ok(body.to_string());
+    }
+    // Errors come as {"message": "..."}
+    let message = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|json| json["message"].as_str().map(str::to_string))
+        .unwrap_or_else(|| status.to_string());
+    Err(io::Error::other(message))
+}
+
+#[cfg(not(unix))]
+fn request(_method: &str, _endpoint: &str) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the Docker daemon is only reached over a unix socket",
+    ))
+}
@@ -0,0 +1,218 @@
+use crate::logging::verbose;
+use crate::scanner::FoundDir;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::Hasher;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A package installed the same, byte for byte, in more than one place.
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicatePackage {
+    pub name: String,
+    pub version: String,
+    /// How many identical copies there are
+    pub copies: usize,
+    /// The size of one copy
+    pub size_bytes: u64,
+    /// Space all but one copy take up
+    pub duplicated_bytes: u64,
+}
+
+/// How much of one project's `node_modules` is installed identically
+/// elsewhere.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectShare {
+    /// The directory holding the `node_modules`
+    pub project: PathBuf,
+    pub size_bytes: u64,
+    /// Packages with an identical copy in another project's `node_modules`
+    pub shared_bytes: u64,
+    /// Days since the `node_modules` last changed, if known
+    pub age_days: Option<u64>,
+}
+
+/// Duplicated packages across every `node_modules` found.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    /// Every package installed, counted once per copy
+    pub total_bytes: u64,
+    /// Space a single shared copy of each package would save
+    pub duplicated_bytes: u64,
+    /// Most duplicated space first
+    pub packages: Vec<DuplicatePackage>,
+    /// Projects with anything shared, most shared space first
+    pub projects: Vec<ProjectShare>,
+}
+
+/// One installed copy of a package.
+struct Package {
+    name: String,
+    version: String,
+    path: PathBuf,
+    /// Which of the `node_modules` it is in
+    owner: usize,
+    size: u64,
+}
+
+/// Find the packages installed identically in several of the `node_modules`
+/// in `found`. Only packages whose name, version and size already match are
+/// read to compare their contents. Links, such as pnpm's or a workspace's,
+/// are left out, since they already share one copy.
+pub fn analyze(found: &[FoundDir]) -> Report {
+    let node_modules: Vec<&FoundDir> = found
+        .iter()
+        .filter(|dir| dir.path.file_name().is_some_and(|name| name == "node_modules"))
+        .collect();
+    let mut packages = Vec::new();
+    for (owner, dir) in node_modules.iter().enumerate() {
+        collect(&dir.path, owner, &mut packages);
+    }
+
+    let mut candidates: HashMap<(&str, &str, u64), Vec<&Package>> = HashMap::new();
+    for package in &packages {
+        candidates
+            .entry((&package.name, &package.version, package.size))
+            .or_default()
+            .push(package);
+    }
+    let mut identical: Vec<Vec<&Package>> = Vec::new();
+    for copies in candidates.into_values().filter(|copies| copies.len() > 1) {
+        let mut by_hash: HashMap<u64, Vec<&Package>> = HashMap::new();
+        for package in copies {
+            match content_hash(&package.path) {
+                Ok(hash) => by_hash.entry(hash).or_default().push(package),
+                Err(e) => verbose!("skip {}: {}", package.path.display(), e),
+            }
+        }
+        identical.extend(by_hash.into_values().filter(|copies| copies.len() > 1));
+    }
+
+    let mut shared = vec![0; node_modules.len()];
+    for copies in &identical {
+        for package in copies {
+            if copies.iter().any(|other| other.owner != package.owner) {
+                shared[package.owner] += package.size;
+            }
+        }
+    }
+    let mut sizes = vec![0; node_modules.len()];
+    for package in &packages {
+        sizes[package.owner] += package.size;
+    }
+    let mut projects: Vec<ProjectShare> = node_modules
+        .iter()
+        .enumerate()
+        .filter(|(owner, _)| shared[*owner] > 0)
+        .map(|(owner, dir)| ProjectShare {
+            project: dir.path.parent().unwrap_or(&dir.path).to_path_buf(),
+            size_bytes: sizes[owner],
+            shared_bytes: shared[owner],
+            age_days: dir.age_days(),
+        })
+        .collect();
+    projects.sort_by(|a, b| b.shared_bytes.cmp(&a.shared_bytes).then_with(|| a.project.cmp(&b.project)));
+
+    let mut duplicates: Vec<DuplicatePackage> = identical
+        .iter()
+        .map(|copies| DuplicatePackage {
+            name: copies[0].name.clone(),
+            version: copies[0].version.clone(),
+            copies: copies.len(),
+            size_bytes: copies[0].size,
+            duplicated_bytes: copies[0].size * (copies.len() as u64 - 1),
+        })
+        .collect();
+    duplicates.sort_by(|a, b| {
+        b.duplicated_bytes
+            .cmp(&a.duplicated_bytes)
+            .then_with(|| (&a.name, &a.version).cmp(&(&b.name, &b.version)))
+    });
+
+    Report {
+        total_bytes: packages.iter().map(|p| p.size).sum(),
+        duplicated_bytes: duplicates.iter().map(|d| d.duplicated_bytes).sum(),
+        packages: duplicates,
+        projects,
+    }
+}
+
+/// Add the packages in `node_modules`, and those nested in theirs, to
+/// `packages`. Dot entries such as `.bin` and `.pnpm` aren't packages.
+fn collect(node_modules: &Path, owner: usize, packages: &mut Vec<Package>) {
+    let Ok(entries) = fs::read_dir(node_modules) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') || !entry.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        if name.starts_with('@') {
+            collect(&entry.path(), owner, packages);
+            continue;
+        }
+        let path = entry.path();
+        collect(&path.join("node_modules"), owner, packages);
+        let Some((name, version)) = manifest(&path) else {
+            continue;
+        };
+        packages.push(Package {
+            name,
+            version,
+            size: files(&path).iter().map(|(_, size)| size).sum(),
+            path,
+            owner,
+        });
+    }
+}
+
+/// The name and version in a package's `package.json`.
+fn manifest(package: &Path) -> Option<(String, String)> {
+    let manifest: serde_json::Value = serde_json::from_slice(&fs::read(package.join("package.json")).ok()?).ok()?;
+    Some((
+        manifest["name"].as_str()?.to_string(),
+        manifest["version"].as_str()?.to_string(),
+    ))
+}
+
+/// A package's files, relative to it and in order, with their sizes; its
+/// own `node_modules` holds other packages.
+fn files(package: &Path) -> Vec<(PathBuf, u64)> {
+    let mut files: Vec<(PathBuf, u64)> = WalkDir::new(package)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| !(e.depth() == 1 && e.file_name() == "node_modules"))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let size = e.metadata().ok()?.len();
+            Some((e.path().strip_prefix(package).ok()?.to_path_buf(), size))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// A hash of a package's file names and contents.
+fn content_hash(package: &Path) -> io::Result<u64> {
+    let mut hasher = DefaultHasher::new();
+    let mut buf = vec![0; 64 * 1024];
+    for (file, size) in files(package) {
+        hasher.write(file.as_os_str().as_encoded_bytes());
+        hasher.write_u64(size);
+        let mut reader = File::open(package.join(&file))?;
+        loop {
+            let n = reader.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            hasher.write(&buf[..n]);
+        }
+    }
+    Ok(hasher.finish())
+}
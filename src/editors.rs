@@ -0,0 +1,151 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Editor state touched within this long counts as an open session.
+const RECENT: Duration = Duration::from_secs(60 * 60);
+
+/// Language servers, by the start of the program or script name they run as.
+const LANGUAGE_SERVERS: &[&str] = &[
+    "rust-analyzer",
+    "tsserver",
+    "typescript-language-server",
+    "vue-language-server",
+    "pyright",
+    "pylsp",
+    "jedi-language-server",
+    "gopls",
+    "clangd",
+    "jdtls",
+    "kotlin-language-server",
+    "OmniSharp",
+    "csharp-ls",
+];
+
+/// VS Code and its forks: their directory under the config directory, and
+/// the name shown for them.
+const VSCODE_FLAVORS: &[(&str, &str)] = &[
+    ("Code", "VS Code"),
+    ("Code - Insiders", "VS Code"),
+    ("VSCodium", "VSCodium"),
+    ("Cursor", "Cursor"),
+];
+
+/// Editor sessions open on this machine, gathered once per scan.
+#[derive(Debug, Default)]
+pub struct Sessions {
+    /// Folders recently open in VS Code or a fork, with the editor's name
+    workspaces: Vec<(PathBuf, &'static str)>,
+    /// Working directories of running language servers, with the server's name
+    servers: Vec<(PathBuf, &'static str)>,
+}
+
+impl Sessions {
+    pub fn load() -> Sessions {
+        Sessions {
+            workspaces: vscode_workspaces(),
+            servers: language_servers(),
+        }
+    }
+
+    /// The editor or language server that seems to have `project` open:
+    /// one running inside it, a VS Code workspace on it or on a folder
+    /// holding it, or a JetBrains workspace file saved within the hour.
+    pub fn open_in(&self, project: &Path) -> Option<&'static str> {
+        if let Some((_, server)) = self.servers.iter().find(|(cwd, _)| cwd.starts_with(project)) {
+            return Some(server);
+        }
+        let home = dirs::home_dir();
+        if let Some((_, editor)) = self.workspaces.iter().find(|(folder, _)| {
+            // A workspace on the whole home directory says nothing
+            folder.starts_with(project) || (project.starts_with(folder) && Some(folder) != home.as_ref())
+        }) {
+            return Some(editor);
+        }
+        recently_modified(&project.join(".idea").join("workspace.xml")).then_some("JetBrains IDE")
+    }
+}
+
+fn recently_modified(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .is_ok_and(|modified| SystemTime::now().duration_since(modified).unwrap_or_default() < RECENT)
+}
+
+/// Folders of VS Code workspaces whose state was saved recently. Each
+/// workspace keeps a directory under `workspaceStorage` naming its folder in
+/// `workspace.json`.
+fn vscode_workspaces() -> Vec<(PathBuf, &'static str)> {
+    let Some(config) = dirs::config_dir() else {
+        return Vec::new();
+    };
+    let mut workspaces = Vec::new();
+    for (dir, editor) in VSCODE_FLAVORS {
+        let Ok(entries) = fs::read_dir(config.join(dir).join("User").join("workspaceStorage")) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let storage = entry.path();
+            if !recently_modified(&storage.join("state.vscdb")) {
+                continue;
+            }
+            let folder = fs::read_to_string(storage.join("workspace.json"))
+                .ok()
+                .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+                .and_then(|json| json["folder"].as_str().and_then(file_url_path));
+            if let Some(folder) = folder {
+                workspaces.push((folder, *editor));
+            }
+        }
+    }
+    workspaces
+}
+
+/// The local path a `file://` URL points at.
+fn file_url_path(url: &str) -> Option<PathBuf> {
+    let path = url.strip_prefix("file://")?;
+    let mut bytes = Vec::new();
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = (byte == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+    // file:///c:/Users/... on Windows
+    let path = if cfg!(windows) { path.trim_start_matches('/') } else { &path };
+    Some(PathBuf::from(path))
+}
+
+/// Working directories of running language servers. Only available where
+/// `/proc` exists.
+fn language_servers() -> Vec<(PathBuf, &'static str)> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_str().is_some_and(|name| name.parse::<u32>().is_ok()))
+        .filter_map(|e| {
+            let cmdline = fs::read(e.path().join("cmdline")).ok()?;
+            // The server itself, or the script an interpreter like node runs
+            let server = cmdline.split(|b| *b == 0).take(2).find_map(|arg| {
+                let arg = String::from_utf8_lossy(arg);
+                let name = Path::new(arg.as_ref()).file_name()?.to_str()?.to_string();
+                LANGUAGE_SERVERS.iter().find(|server| name.starts_with(*server)).copied()
+            })?;
+            Some((fs::read_link(e.path().join("cwd")).ok()?, server))
+        })
+        .collect()
+}
@@ -0,0 +1,42 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Paths the user has asked never to be offered for cleaning, one per line.
+pub fn exclude_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("safeclean").join("exclude"))
+}
+
+pub fn load() -> Vec<PathBuf> {
+    let Some(file) = exclude_file() else {
+        return Vec::new();
+    };
+    let Ok(contents) = fs::read_to_string(file) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Append `path` to the exclude file, creating it if needed.
+pub fn add(path: &Path) -> io::Result<()> {
+    let Some(file) = exclude_file() else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no config directory available",
+        ));
+    };
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut out = OpenOptions::new().create(true).append(true).open(file)?;
+    writeln!(out, "{}", path.display())
+}
+
+pub fn is_excluded(path: &Path, excluded: &[PathBuf]) -> bool {
+    excluded.iter().any(|prefix| path.starts_with(prefix))
+}
@@ -0,0 +1,60 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Signs that a repository is in active use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GitStatus {
+    /// Uncommitted or untracked changes in the working tree
+    pub dirty: bool,
+    /// At least one stash entry
+    pub stashed: bool,
+}
+
+fn git_output(repo: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Status of the repository at `repo`, or `None` if it isn't one (or git is
+/// not installed).
+pub fn status(repo: &Path) -> Option<GitStatus> {
+    if !repo.join(".git").exists() {
+        return None;
+    }
+    let changes = git_output(repo, &["status", "--porcelain"])?;
+    let stashes = git_output(repo, &["stash", "list"]).unwrap_or_default();
+    Some(GitStatus {
+        dirty: !changes.trim().is_empty(),
+        stashed: !stashes.trim().is_empty(),
+    })
+}
+
+/// Whether `path` is covered by the ignore rules of the repository at `repo`,
+/// or `None` if git couldn't tell.
+pub fn is_ignored(repo: &Path, path: &Path) -> Option<bool> {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["check-ignore", "-q", "--"])
+        .arg(path)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .ok()?;
+    // 0: ignored, 1: not ignored, anything else: error
+    match status.code() {
+        Some(0) => Some(true),
+        Some(1) => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,98 @@
+use crate::cleaner::CleanResult;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One directory removed by a clean.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub path: PathBuf,
+    #[serde(rename = "type")]
+    pub project_type: String,
+    pub bytes: u64,
+}
+
+/// Everything a single clean removed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Clean {
+    /// Seconds since the Unix epoch when the clean started
+    pub time: i64,
+    pub entries: Vec<Entry>,
+}
+
+impl Clean {
+    pub fn bytes(&self) -> u64 {
+        self.entries.iter().map(|e| e.bytes).sum()
+    }
+}
+
+/// The history file holds one JSON object per clean, oldest first.
+fn history_file() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("safeclean").join("history.jsonl"))
+}
+
+/// Append what a clean removed to the history. `started` is when the clean
+/// began.
+pub fn record(started: SystemTime, result: &CleanResult) -> io::Result<()> {
+    let Some(file) = history_file() else {
+        return Ok(());
+    };
+    let clean = Clean {
+        time: started.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64),
+        entries: result
+            .deleted
+            .iter()
+            .map(|d| Entry {
+                path: d.path.clone(),
+                project_type: d.project_type.name().to_string(),
+                bytes: d.size_bytes,
+            })
+            .collect(),
+    };
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(&clean).map_err(io::Error::other)?;
+    line.push('\n');
+    OpenOptions::new().create(true).append(true).open(file)?.write_all(line.as_bytes())
+}
+
+/// Every recorded clean, oldest first. Lines that don't parse (say, from a
+/// clean interrupted mid-write) are skipped.
+pub fn load() -> io::Result<Vec<Clean>> {
+    let Some(file) = history_file() else {
+        return Ok(Vec::new());
+    };
+    let contents = match fs::read_to_string(file) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// The (year, month) a Unix timestamp falls in, in UTC.
+pub fn month_of(time: i64) -> (i64, u32) {
+    let (year, month, _) = date_of(time);
+    (year, month)
+}
+
+/// The (year, month, day) a Unix timestamp falls on, in UTC.
+pub fn date_of(time: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's days-to-civil algorithm
+    let days = time.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
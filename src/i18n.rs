@@ -0,0 +1,78 @@
+use fluent::concurrent::FluentBundle;
+use fluent::{FluentArgs, FluentResource};
+use std::sync::OnceLock;
+use unic_langid::LanguageIdentifier;
+
+/// Translations built into the binary, as (locale, Fluent source). The first
+/// is the fallback for ids a translation lacks.
+const LOCALES: &[(&str, &str)] = &[("en-US", include_str!("../locales/en-US/safeclean.ftl"))];
+
+static BUNDLES: OnceLock<Vec<FluentBundle<FluentResource>>> = OnceLock::new();
+
+/// The user's language from the usual locale variables, e.g. `de_DE.UTF-8`
+/// becomes `de-DE`.
+fn requested_locale() -> Option<LanguageIdentifier> {
+    let value = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())?;
+    let name = value.split(['.', '@']).next()?.replace('_', "-");
+    name.parse().ok()
+}
+
+fn bundle(locale: &str, source: &str) -> FluentBundle<FluentResource> {
+    let mut bundle = FluentBundle::new_concurrent(vec![locale.parse().expect("built-in locale id")]);
+    // Isolation marks show up as stray characters in terminals
+    bundle.set_use_isolating(false);
+    let resource = FluentResource::try_new(source.to_string()).expect("built-in messages parse");
+    bundle.add_resource(resource).expect("built-in messages are unique");
+    bundle
+}
+
+/// The bundles to look messages up in, best match first.
+fn bundles() -> &'static [FluentBundle<FluentResource>] {
+    BUNDLES.get_or_init(|| {
+        let (fallback, fallback_source) = LOCALES[0];
+        let mut bundles = Vec::new();
+        if let Some(requested) = requested_locale() {
+            let matching = LOCALES.iter().find(|(locale, _)| {
+                let locale: LanguageIdentifier = locale.parse().expect("built-in locale id");
+                locale.language == requested.language
+            });
+            if let Some((locale, source)) = matching
+                && *locale != fallback
+            {
+                bundles.push(bundle(locale, source));
+            }
+        }
+        bundles.push(bundle(fallback, fallback_source));
+        bundles
+    })
+}
+
+/// Look up message `id` in the user's language and fill in `args`. Unknown
+/// ids come back as-is so a missing message is visible rather than fatal.
+pub fn message(id: &str, args: Option<&FluentArgs>) -> String {
+    for bundle in bundles() {
+        if let Some(pattern) = bundle.get_message(id).and_then(|m| m.value()) {
+            let mut errors = Vec::new();
+            return bundle.format_pattern(pattern, args, &mut errors).into_owned();
+        }
+    }
+    id.to_string()
+}
+
+/// Translate a message: `tr!("id")`, or `tr!("id", name = value, ...)` to
+/// fill in its variables.
+macro_rules! tr {
+    ($id:literal) => {
+        $crate::i18n::message($id, None)
+    };
+    ($id:literal, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::message($id, Some(&args))
+    }};
+}
+
+pub(crate) use tr;
@@ -0,0 +1,263 @@
+use crate::schedule;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The context-menu entry's label.
+const LABEL: &str = "Clean build artifacts here…";
+
+/// Registry keys for the entry on folders and on a folder's background.
+const WINDOWS_KEYS: [(&str, &str); 2] = [
+    (r"HKCU\Software\Classes\Directory\shell\safeclean", "%1"),
+    (r"HKCU\Software\Classes\Directory\Background\shell\safeclean", "%V"),
+];
+
+/// Add an entry to the file manager's context menu (Explorer, Finder or
+/// Nautilus) that runs safeclean on the chosen folder in a terminal.
+/// Returns a description of what was installed.
+pub fn install() -> io::Result<String> {
+    let exe = std::env::current_exe()?;
+    if cfg!(target_os = "macos") {
+        let workflow = finder_workflow()?;
+        let contents = workflow.join("Contents");
+        fs::create_dir_all(&contents)?;
+        fs::write(contents.join("Info.plist"), finder_info())?;
+        fs::write(contents.join("document.wflow"), finder_document(&exe))?;
+        // Otherwise Finder only sees it after logging out and in
+        let _ = schedule::run("/System/Library/CoreServices/pbs", &["-update"]);
+        Ok(format!("Finder Quick Action {}", workflow.display()))
+    } else if cfg!(windows) {
+        let exe = exe.display().to_string();
+        for (key, folder) in WINDOWS_KEYS {
+            // /k keeps the window open to read the outcome
+            let command = format!("cmd.exe /k \"\"{}\" \"{}\"\"", exe, folder);
+            schedule::run("reg", &["add", key, "/ve", "/d", LABEL, "/f"])?;
+            schedule::run("reg", &["add", key, "/v", "Icon", "/d", &exe, "/f"])?;
+            schedule::run("reg", &["add", &format!(r"{}\command", key), "/ve", "/d", &command, "/f"])?;
+        }
+        Ok(format!("Explorer entry {}", WINDOWS_KEYS[0].0))
+    } else {
+        let script = nautilus_script()?;
+        fs::create_dir_all(script.parent().unwrap())?;
+        fs::write(&script, nautilus_contents(&exe))?;
+        make_executable(&script)?;
+        Ok(format!("Nautilus script {}", script.display()))
+    }
+}
+
+/// Remove the context-menu entry. Returns false if none was installed.
+pub fn remove() -> io::Result<bool> {
+    if cfg!(target_os = "macos") {
+        let workflow = finder_workflow()?;
+        if !workflow.exists() {
+            return Ok(false);
+        }
+        fs::remove_dir_all(workflow)?;
+        let _ = schedule::run("/System/Library/CoreServices/pbs", &["-update"]);
+        Ok(true)
+    } else if cfg!(windows) {
+        if schedule::run("reg", &["query", WINDOWS_KEYS[0].0]).is_err() {
+            return Ok(false);
+        }
+        for (key, _) in WINDOWS_KEYS {
+            let _ = schedule::run("reg", &["delete", key, "/f"]);
+        }
+        Ok(true)
+    } else {
+        let script = nautilus_script()?;
+        if !script.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(script)?;
+        Ok(true)
+    }
+}
+
+fn nautilus_script() -> io::Result<PathBuf> {
+    dirs::data_dir()
+        .map(|dir| dir.join("nautilus").join("scripts").join(LABEL))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory available"))
+}
+
+fn finder_workflow() -> io::Result<PathBuf> {
+    dirs::home_dir()
+        .map(|home| home.join("Library/Services").join(format!("{}.workflow", LABEL)))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory available"))
+}
+
+#[cfg(unix)]
+fn make_executable(file: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(file, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_file: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Single-quote an argument for `sh`.
+pub fn sh_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// A Nautilus script, run on the selected folder, or on the open one when
+/// nothing is selected. It opens whichever terminal is installed and keeps
+/// it open once safeclean exits.
+fn nautilus_contents(exe: &Path) -> String {
+    format!(
+        r#"#!/bin/sh
+# Installed by `safeclean integrate-shell`; `safeclean integrate-shell --uninstall` removes it
+dir=$(printf '%s\n' "$NAUTILUS_SCRIPT_SELECTED_FILE_PATHS" | head -n 1)
+[ -d "$dir" ] || dir=$PWD
+run='"$0" "$1"; printf "\nPress Enter to close. "; read _'
+if command -v gnome-terminal >/dev/null 2>&1; then
+    exec gnome-terminal -- sh -c "$run" {exe} "$dir"
+elif command -v x-terminal-emulator >/dev/null 2>&1; then
+    exec x-terminal-emulator -e sh -c "$run" {exe} "$dir"
+else
+    exec xterm -e sh -c "$run" {exe} "$dir"
+fi
+"#,
+        exe = sh_quote(&exe.display().to_string())
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// The Quick Action's bundle info, offering it on folders in Finder.
+fn finder_info() -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>NSServices</key>
+    <array>
+        <dict>
+            <key>NSMenuItem</key>
+            <dict>
+                <key>default</key>
+                <string>{}</string>
+            </dict>
+            <key>NSMessage</key>
+            <string>runWorkflowAsService</string>
+            <key>NSRequiredContext</key>
+            <dict>
+                <key>NSApplicationIdentifier</key>
+                <string>com.apple.finder</string>
+            </dict>
+            <key>NSSendFileTypes</key>
+            <array>
+                <string>public.folder</string>
+            </array>
+        </dict>
+    </array>
+</dict>
+</plist>
+"#,
+        xml_escape(LABEL)
+    )
+}
+
+/// The Quick Action's workflow: a shell script, given the chosen folders as
+/// arguments, that has Terminal run safeclean on each.
+fn finder_document(exe: &Path) -> String {
+    // AppleScript's `quoted form of` quotes for the shell Terminal runs
+    let exe = exe.display().to_string().replace('\\', r"\\").replace('"', "\\\"");
+    let script = format!(
+        r#"for f in "$@"; do
+osascript - "$f" <<'END'
+on run argv
+    tell application "Terminal"
+        activate
+        do script (quoted form of "{}") & " " & (quoted form of (item 1 of argv))
+    end tell
+end run
+END
+done"#,
+        exe
+    );
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>AMApplicationBuild</key>
+    <string>521</string>
+    <key>AMApplicationVersion</key>
+    <string>2.10</string>
+    <key>AMDocumentVersion</key>
+    <string>2</string>
+    <key>actions</key>
+    <array>
+        <dict>
+            <key>action</key>
+            <dict>
+                <key>AMAccepts</key>
+                <dict>
+                    <key>Container</key>
+                    <string>List</string>
+                    <key>Optional</key>
+                    <true/>
+                    <key>Types</key>
+                    <array>
+                        <string>com.apple.cocoa.string</string>
+                    </array>
+                </dict>
+                <key>AMActionVersion</key>
+                <string>2.0.3</string>
+                <key>AMProvides</key>
+                <dict>
+                    <key>Container</key>
+                    <string>List</string>
+                    <key>Types</key>
+                    <array>
+                        <string>com.apple.cocoa.string</string>
+                    </array>
+                </dict>
+                <key>ActionBundlePath</key>
+                <string>/System/Library/Automator/Run Shell Script.action</string>
+                <key>ActionName</key>
+                <string>Run Shell Script</string>
+                <key>ActionParameters</key>
+                <dict>
+                    <key>COMMAND_STRING</key>
+                    <string>{}</string>
+                    <key>CheckedForUserDefaultShell</key>
+                    <true/>
+                    <key>inputMethod</key>
+                    <integer>1</integer>
+                    <key>shell</key>
+                    <string>/bin/sh</string>
+                    <key>source</key>
+                    <string></string>
+                </dict>
+                <key>BundleIdentifier</key>
+                <string>com.apple.RunShellScript</string>
+                <key>CFBundleVersion</key>
+                <string>2.0.3</string>
+                <key>Class Name</key>
+                <string>RunShellScriptAction</string>
+            </dict>
+        </dict>
+    </array>
+    <key>connectors</key>
+    <dict/>
+    <key>workflowMetaData</key>
+    <dict>
+        <key>serviceInputTypeIdentifier</key>
+        <string>com.apple.Automator.fileSystemObject.folder</string>
+        <key>serviceOutputTypeIdentifier</key>
+        <string>com.apple.Automator.nothing</string>
+        <key>workflowTypeIdentifier</key>
+        <string>com.apple.Automator.servicesMenu</string>
+    </dict>
+</dict>
+</plist>
+"#,
+        xml_escape(&script)
+    )
+}
@@ -0,0 +1,126 @@
+//! Find and remove build artifacts and dependency caches.
+//!
+//! This is the detection and deletion logic behind the `safeclean` command,
+//! for tools and GUIs that want to embed it. A [`Scanner`] walks directories
+//! looking for anything a [`Rule`] recognizes, such as a `target/` next to a
+//! `Cargo.toml`, and reports each match as a [`FoundDir`]. A [`Cleaner`] then
+//! removes the ones the caller picked.
+//!
+//! ```no_run
+//! use safeclean::{Cleaner, Method, Scanner};
+//!
+//! let found = Scanner::new(vec!["/home/me/projects".into()]).scan();
+//! let old: Vec<_> = found.into_iter().filter(|d| d.age_days() > Some(30)).collect();
+//! let result = Cleaner::new(Method::Trash).clean(old);
+//! println!("freed {} bytes", result.total_cleaned());
+//! ```
+//!
+//! Every removal is appended to the audit log in the user's data directory,
+//! as it is for the command, and nothing under a [`protect`]ed path is ever
+//! removed.
+
+pub mod audit;
+pub mod caches;
+pub mod cleaner;
+pub mod containers;
+pub mod docker;
+pub mod duplicates;
+pub mod editors;
+pub mod exclude;
+pub mod git;
+pub mod history;
+pub mod lock;
+pub mod logging;
+pub mod owner;
+pub mod projects;
+pub mod protect;
+pub mod rebuild;
+pub mod risk;
+pub mod scanner;
+pub mod sizing;
+pub mod toolchains;
+pub mod units;
+
+pub use cleaner::{CleanProgress, CleanResult, Method};
+pub use projects::{CleanableDir as Rule, Explanation, ProjectType, Validator};
+pub use scanner::{FoundDir, ScanEvent, ScanOptions, Skipped};
+
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+
+/// Finds cleanable directories under a set of roots.
+#[derive(Debug)]
+pub struct Scanner {
+    pub roots: Vec<PathBuf>,
+    pub options: ScanOptions,
+}
+
+impl Scanner {
+    /// A scanner for every project type with the built-in rules and no
+    /// exclusions or thresholds. Adjust [`Scanner::options`] to narrow it.
+    pub fn new(roots: Vec<PathBuf>) -> Scanner {
+        Scanner {
+            roots,
+            options: ScanOptions {
+                enabled_types: ProjectType::all().into_iter().collect(),
+                excluded: Vec::new(),
+                min_size: None,
+                older_than: None,
+                extra_dirs: Vec::new(),
+                global: false,
+                docker: false,
+                toolchains: false,
+                cache_keep: None,
+                all_users: false,
+                skip_sizes: false,
+            },
+        }
+    }
+
+    /// Also match directories with `rule`, checked before the built-in rules.
+    pub fn add_rule(&mut self, rule: Rule) {
+        self.options.enabled_types.insert(rule.project_type);
+        self.options.extra_dirs.push(rule);
+    }
+
+    /// Scan and measure everything, largest first.
+    pub fn scan(&self) -> Vec<FoundDir> {
+        scanner::scan(&self.roots, &self.options).0
+    }
+
+    /// Scan, calling `on_event` as each directory is found and again once it
+    /// has been measured, then once with [`ScanEvent::Done`].
+    pub fn scan_with_progress(&self, mut on_event: impl FnMut(ScanEvent)) {
+        let (events, receiver) = mpsc::channel();
+        thread::scope(|s| {
+            s.spawn(|| scanner::scan_streaming(&self.roots, &self.options, events));
+            for event in receiver {
+                on_event(event);
+            }
+        });
+    }
+}
+
+/// Removes directories found by a [`Scanner`].
+#[derive(Debug, Clone, Copy)]
+pub struct Cleaner {
+    pub method: Method,
+}
+
+impl Cleaner {
+    pub fn new(method: Method) -> Cleaner {
+        Cleaner { method }
+    }
+
+    /// Remove `dirs`, carrying on past failures.
+    pub fn clean(&self, dirs: Vec<FoundDir>) -> CleanResult {
+        cleaner::clean(dirs, self.method)
+    }
+
+    /// Like [`Cleaner::clean`], calling `on_progress` before and after each
+    /// directory.
+    pub fn clean_with_progress(&self, dirs: Vec<FoundDir>, on_progress: impl FnMut(CleanProgress)) -> CleanResult {
+        cleaner::clean_with_progress(dirs, self.method, on_progress)
+    }
+}
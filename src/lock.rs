@@ -0,0 +1,76 @@
+use std::fs::{self, File, TryLockError};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Advisory locks on scan roots, released when dropped (or when the process
+/// exits, however it exits).
+pub struct RootLocks {
+    _files: Vec<File>,
+}
+
+/// Why the roots couldn't be locked.
+pub enum LockError {
+    /// Another run is cleaning this directory, or holds a lock inside it
+    Held(PathBuf),
+    Io(io::Error),
+}
+
+fn lock_dir() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("safeclean").join("locks"))
+}
+
+/// FNV-1a, so every build names a root's files the same way.
+pub fn hash(path: &Path) -> u64 {
+    path.as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Lock each of `roots` (canonical paths) for this run, failing without
+/// waiting if another run holds any of them or a directory above or below
+/// one. Each root is locked exclusively and every directory above it
+/// shared, so runs on nested roots conflict and runs on separate ones don't.
+pub fn lock_roots(roots: &[PathBuf]) -> Result<RootLocks, LockError> {
+    let Some(dir) = lock_dir() else {
+        return Ok(RootLocks { _files: Vec::new() });
+    };
+    fs::create_dir_all(&dir).map_err(LockError::Io)?;
+
+    // Locks conflict within a run too, so nested roots go under the outermost
+    let mut roots: Vec<&PathBuf> = roots.iter().collect();
+    roots.sort();
+    roots.dedup();
+    let outermost: Vec<&PathBuf> = roots
+        .iter()
+        .filter(|root| !roots.iter().any(|other| other != *root && root.starts_with(other)))
+        .copied()
+        .collect();
+
+    let mut files = Vec::new();
+    for root in outermost {
+        for above in root.ancestors().skip(1) {
+            let file = lock_file(&dir, above)?;
+            match file.try_lock_shared() {
+                Ok(()) => files.push(file),
+                Err(TryLockError::WouldBlock) => return Err(LockError::Held(above.to_path_buf())),
+                Err(TryLockError::Error(e)) => return Err(LockError::Io(e)),
+            }
+        }
+        let file = lock_file(&dir, root)?;
+        match file.try_lock() {
+            Ok(()) => files.push(file),
+            Err(TryLockError::WouldBlock) => return Err(LockError::Held(root.clone())),
+            Err(TryLockError::Error(e)) => return Err(LockError::Io(e)),
+        }
+    }
+    Ok(RootLocks { _files: files })
+}
+
+/// The file standing for `path` in the lock directory `dir`.
+fn lock_file(dir: &Path, path: &Path) -> Result<File, LockError> {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("root");
+    File::create(dir.join(format!("{}-{:016x}.lock", name, hash(path)))).map_err(LockError::Io)
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How much is reported besides the results themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Errors only
+    Quiet,
+    /// Progress and summaries
+    Normal,
+    /// Why each candidate was accepted or rejected
+    Verbose,
+    /// Every directory visited
+    Trace,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Normal as u8);
+
+/// Diagnostics held back while the selector owns the terminal.
+static HELD: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+    LEVEL.load(Ordering::Relaxed) >= level as u8
+}
+
+/// Write a diagnostic line to stderr, or hold it until [`release`].
+pub fn write(args: fmt::Arguments) {
+    let line = format!("safeclean: {}", args);
+    match HELD.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        Some(held) => held.push(line),
+        None => eprintln!("{}", line),
+    }
+}
+
+/// Hold diagnostics instead of writing them over a full-screen UI.
+pub fn hold() {
+    *HELD.lock().unwrap_or_else(|e| e.into_inner()) = Some(Vec::new());
+}
+
+/// Stop holding diagnostics and write out everything held so far.
+pub fn release() {
+    let held = HELD.lock().unwrap_or_else(|e| e.into_inner()).take();
+    for line in held.into_iter().flatten() {
+        eprintln!("{}", line);
+    }
+}
+
+/// Progress or summary line on stdout, silenced by `--quiet`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::Level::Normal) {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Diagnostic shown with `-v`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::Level::Verbose) {
+            $crate::logging::write(format_args!($($arg)*));
+        }
+    };
+}
+
+/// Diagnostic shown with `-vv`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::Level::Trace) {
+            $crate::logging::write(format_args!($($arg)*));
+        }
+    };
+}
+
+pub use crate::{info, trace, verbose};
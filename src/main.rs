@@ -1,25 +1,252 @@
-mod cleaner;
-mod projects;
-mod scanner;
+mod budget;
+mod ci;
+mod config;
+#[cfg(unix)]
+mod daemon;
+mod desktop;
+mod disk;
+mod i18n;
+mod integrate;
+mod metrics;
+mod output;
+mod plan;
+mod progress;
+mod prompt;
+mod remote;
+mod report;
+mod schedule;
 mod selector;
+mod snapshot;
+mod state;
+mod stats;
+mod treemap;
+mod undo;
+mod watch;
 
-use clap::Parser;
+use clap::builder::BoolishValueParser;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use colored::Colorize;
+use git::GitStatus;
 use indicatif::{ProgressBar, ProgressStyle};
-use projects::ProjectType;
-use scanner::FoundDir;
-use selector::GroupedSelector;
+use safeclean::units::{self, SizeFormat, format_size};
+use safeclean::{
+    caches, cleaner, docker, duplicates, exclude, git, history, lock, logging, projects, rebuild, risk, scanner,
+};
+use i18n::tr;
+use logging::{Level, info, verbose};
+use output::Format;
+use progress::Progress;
+use remote::Remote;
+use projects::{CleanableDir, Explanation, ProjectType};
+use scanner::{FoundDir, ScanEvent, ScanOptions, Skipped};
+use cleaner::{CleanResult, Method};
+use config::Config;
+use disk::DiskFree;
+use lock::LockError;
+use schedule::Frequency;
+use watch::WatchOptions;
+use selector::{CONFIRM_WORD, GroupedSelector, LargeDelete, Selection, SelectorOptions, SortKey};
 use std::collections::HashSet;
+use std::io::IsTerminal;
 use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Exit status when a clean or scan couldn't run, including usage errors.
+const EXIT_ERROR: i32 = 1;
+/// Exit status when there was nothing to clean.
+const EXIT_NOTHING_FOUND: i32 = 2;
+/// Exit status when at least one directory couldn't be deleted.
+const EXIT_PARTIAL_FAILURE: i32 = 3;
+/// Exit status when the user cancelled, matching an interrupted process.
+const EXIT_CANCELLED: i32 = 130;
+
+/// Entries of per-entry caches (cargo's, Gradle's and Maven's, with
+/// `--global`) used within this long stay cached unless `--cache-keep` or the
+/// config file says otherwise.
+const DEFAULT_CACHE_KEEP: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Deleting more than this, or more than this many directories, takes typing
+/// a word to confirm unless the config file or flags say otherwise.
+const DEFAULT_CONFIRM_OVER: u64 = 20 << 30;
+const DEFAULT_CONFIRM_COUNT: usize = 100;
+
+/// Artifacts modified more recently than this are left alone unless
+/// `--older-than` or the config file says otherwise, so a build still running
+/// or just finished isn't pulled out from under it.
+const DEFAULT_OLDER_THAN: Duration = Duration::from_secs(2 * 60 * 60);
 
 #[derive(Parser)]
 #[command(name = "safeclean")]
 #[command(about = "Safely clean up build artifacts and dependency caches to reclaim disk space")]
+#[command(long_about = "Safely clean up build artifacts and dependency caches to reclaim disk space.
+
+A directory is only offered for cleaning when its name matches a known \
+artifact directory (target/, node_modules/, .venv/, ...) and the project \
+file that produces it sits next to it (Cargo.toml, package.json, ...). \
+Directories inside an artifact directory are never considered separately.
+
+Nothing is deleted without confirmation unless --yes is given; --dry-run \
+only lists what would be cleaned. Paths in the exclude file and the config \
+file are never offered, and --trash moves directories to the trash instead \
+of deleting them.")]
+#[command(after_long_help = "Exit status:
+  0    cleaned successfully (or nothing was selected)
+  1    usage or scan error
+  2    nothing found to clean
+  3    some directories could not be deleted (or, with --fail-on-error,
+       could not be read)
+  130  cancelled")]
 #[command(version)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
-    /// Directory to scan (defaults to current directory)
-    #[arg(default_value = ".")]
-    path: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Options for the default `clean` command
+    #[command(flatten)]
+    args: Args,
+
+    /// Print a man page to stdout and exit
+    #[arg(long, hide = true)]
+    generate_man: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Scan, select and clean (the default when no command is given)
+    Clean(Args),
+    /// Report what could be cleaned without deleting anything
+    Scan(Args),
+    /// Summarize reclaimable space by project type
+    Stats(Args),
+    /// Rescan periodically and report when reclaimable space crosses a
+    /// threshold, without deleting anything
+    Watch(WatchArgs),
+    /// Clean the directories saved by `scan --plan`, skipping any that no
+    /// longer match a rule
+    Apply(ApplyArgs),
+    /// Serve scans and cleans to GUIs and editors as JSON-RPC over a Unix
+    /// socket
+    Daemon(DaemonArgs),
+    /// Restore the directories the last clean moved to the trash
+    Undo,
+    /// List every project type, the directories it matches and how each
+    /// match is confirmed
+    ListTypes,
+    /// Show or edit the config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Run an unattended clean on a schedule (systemd, launchd or Task
+    /// Scheduler)
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
+    /// Add "Clean build artifacts here…" to the file manager's context menu
+    /// (Explorer, Finder or Nautilus), running safeclean in a terminal
+    IntegrateShell {
+        /// Remove the entry instead
+        #[arg(long)]
+        uninstall: bool,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether colors are wanted when output goes to a terminal.
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Interactive {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Subcommand)]
+enum ScheduleAction {
+    /// Install and enable a scheduled `safeclean clean --yes --quiet`
+    Install {
+        /// How often to clean
+        #[arg(long, value_enum, default_value_t = Frequency::Weekly)]
+        every: Frequency,
+
+        /// Extra arguments for the clean, after `--` (the config file
+        /// supplies roots and filters otherwise)
+        #[arg(last = true, value_name = "CLEAN_ARGS")]
+        clean_args: Vec<String>,
+    },
+    /// Disable and delete the schedule
+    Remove,
+    /// Show whether a schedule is installed and when it runs next
+    Status,
+}
+
+#[derive(clap::Args)]
+struct WatchArgs {
+    /// Time between scans (e.g. 12h, 1d)
+    #[arg(long, value_name = "DURATION", default_value = "1h", value_parser = units::parse_duration)]
+    interval: Duration,
+
+    /// Report once at least this much space can be reclaimed
+    #[arg(long, value_name = "SIZE", default_value = "10GB", value_parser = units::parse_size)]
+    threshold: u64,
+
+    #[command(flatten)]
+    args: Args,
+}
+
+#[derive(clap::Args)]
+struct ApplyArgs {
+    /// Plan file written by `scan --plan`
+    #[arg(value_name = "PLAN")]
+    plan_file: PathBuf,
+
+    #[command(flatten)]
+    args: Args,
+}
+
+#[derive(clap::Args)]
+struct DaemonArgs {
+    /// Socket to listen on (default: safeclean.sock in the runtime directory)
+    #[arg(long, value_name = "PATH")]
+    socket: Option<PathBuf>,
+
+    #[command(flatten)]
+    args: Args,
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print the config file's location and contents
+    Show,
+    /// Open the config file in $VISUAL or $EDITOR, creating it if needed
+    Edit,
+}
+
+#[derive(clap::Args)]
+struct Args {
+    /// Directory to scan (defaults to the roots in the config file, or the
+    /// current directory)
+    #[arg(env = "SAFECLEAN_PATH")]
+    path: Option<PathBuf>,
 
     /// Show what would be deleted without deleting
     #[arg(short = 'n', long)]
@@ -57,140 +284,525 @@ struct Cli {
     #[arg(long)]
     nuxt: bool,
 
+    /// Project types to clean, comma-separated (e.g. rust,node,py); combines
+    /// with the individual type flags
+    #[arg(long, value_name = "TYPES", env = "SAFECLEAN_TYPES", value_delimiter = ',', value_parser = ProjectType::parse_flag)]
+    types: Vec<ProjectType>,
+
+    /// Also clean directories called NAME, or only those next to a FILE
+    /// when given as NAME:FILE (repeatable)
+    #[arg(long, value_name = "NAME[:FILE]", env = "SAFECLEAN_INCLUDE_DIR", value_delimiter = ',', value_parser = CleanableDir::parse_custom)]
+    include_dir: Vec<CleanableDir>,
+
+    /// Never offer anything under PATH (repeatable, or comma-separated); on
+    /// top of the exclude file and the config file
+    #[arg(long, value_name = "PATH", env = "SAFECLEAN_EXCLUDE", value_delimiter = ',')]
+    exclude: Vec<PathBuf>,
+
+    /// Also offer per-user tool caches (cargo, npm, pip, gradle, ...) and
+    /// what --docker and --toolchains would, which start unselected
+    #[arg(long, env = "SAFECLEAN_GLOBAL", value_parser = BoolishValueParser::new())]
+    global: bool,
+
+    /// Also offer dangling images, stopped containers and unused build cache
+    /// from the Docker daemon, which start unselected
+    #[arg(long, env = "SAFECLEAN_DOCKER", value_parser = BoolishValueParser::new())]
+    docker: bool,
+
+    /// Also offer rustup toolchains and nvm, fnm and pyenv versions other
+    /// than the default, active and newest ones, which start unselected
+    #[arg(long, env = "SAFECLEAN_TOOLCHAINS", value_parser = BoolishValueParser::new())]
+    toolchains: bool,
+
+    /// With --global, keep cache entries and toolchains used within this
+    /// long, such as recently used crates or jars (default 30d, 0 to offer
+    /// every entry)
+    #[arg(long, value_name = "AGE", env = "SAFECLEAN_CACHE_KEEP", value_parser = units::parse_duration)]
+    cache_keep: Option<Duration>,
+
+    /// Only offer directories untouched for longer than this (e.g. 30d, 2w;
+    /// default 2h, 0 to offer everything)
+    #[arg(long, value_name = "AGE", env = "SAFECLEAN_OLDER_THAN", value_parser = units::parse_duration)]
+    older_than: Option<Duration>,
+
+    /// Only offer directories at least this large (e.g. 100MB, 1G)
+    #[arg(long, value_name = "SIZE", env = "SAFECLEAN_MIN_SIZE", value_parser = units::parse_size)]
+    min_size: Option<u64>,
+
+    /// List directories without measuring them: the selector sizes those
+    /// highlighted or selected, and a clean without it sizes what it
+    /// deletes first. Until then ages go by each directory's own
+    /// modification time, and machine-readable output has sizes of 0
+    #[arg(
+        long,
+        visible_alias = "fast",
+        env = "SAFECLEAN_NO_SIZES",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = ["min_size", "free", "treemap", "diff", "progress", "report", "metrics_out", "ci", "remote"]
+    )]
+    no_sizes: bool,
+
+    /// Select the largest, oldest low-risk directories until this much
+    /// space would be freed (e.g. 20GB)
+    #[arg(long, value_name = "SIZE", value_parser = units::parse_size)]
+    free: Option<u64>,
+
+    /// Ask again before deleting more than this in one run (e.g. 50GB);
+    /// without a terminal to ask on, refuse
+    #[arg(long, value_name = "SIZE", env = "SAFECLEAN_MAX_DELETE", value_parser = units::parse_size)]
+    max_delete: Option<u64>,
+
+    /// Confirm by typing a word rather than pressing a key before deleting
+    /// more than this (default 20GB)
+    #[arg(long, value_name = "SIZE", env = "SAFECLEAN_CONFIRM_OVER", value_parser = units::parse_size)]
+    confirm_over: Option<u64>,
+
+    /// Confirm by typing a word before deleting more than this many
+    /// directories (default 100)
+    #[arg(long, value_name = "N", env = "SAFECLEAN_CONFIRM_COUNT")]
+    confirm_count: Option<usize>,
+
+    /// Exit with status 3 if any directory couldn't be read while scanning,
+    /// not just when a deletion fails
+    #[arg(long, env = "SAFECLEAN_FAIL_ON_ERROR", value_parser = BoolishValueParser::new())]
+    fail_on_error: bool,
+
+    /// Show a desktop notification when a clean finishes (or, for watch,
+    /// when the threshold is crossed)
+    #[arg(long, env = "SAFECLEAN_NOTIFY", value_parser = BoolishValueParser::new())]
+    notify: bool,
+
+    /// Use the settings of this profile from the config file
+    #[arg(long, value_name = "NAME", env = "SAFECLEAN_PROFILE")]
+    profile: Option<String>,
+
+    /// Also load detectors and protected paths from this rules file, e.g.
+    /// one checked into a repository
+    #[arg(long, value_name = "FILE", env = "SAFECLEAN_RULES")]
+    rules: Option<PathBuf>,
+
+    /// Also offer directories owned by other users (needs root); by default
+    /// they are skipped
+    #[arg(long, env = "SAFECLEAN_ALL_USERS", value_parser = BoolishValueParser::new())]
+    all_users: bool,
+
+    /// Move directories to the trash instead of deleting them
+    #[arg(long, env = "SAFECLEAN_TRASH", value_parser = BoolishValueParser::new(), overrides_with = "permanent")]
+    trash: bool,
+
+    /// Delete permanently even if the config file enables the trash
+    #[arg(long, env = "SAFECLEAN_PERMANENT", value_parser = BoolishValueParser::new(), overrides_with = "trash")]
+    permanent: bool,
+
     /// Skip confirmation prompt
-    #[arg(short = 'y', long)]
+    #[arg(short = 'y', long, env = "SAFECLEAN_YES", value_parser = BoolishValueParser::new())]
     yes: bool,
 
+    /// Whether to ask: auto uses the selector on a terminal and a line-based
+    /// prompt elsewhere, always forces the selector, never cleans everything
+    /// matching the filters like --yes
+    #[arg(long, value_enum, value_name = "WHEN", env = "SAFECLEAN_INTERACTIVE", default_value_t = Interactive::Auto)]
+    interactive: Interactive,
+
+    /// For CI cleanup steps: never ask (like --yes), fold the log into
+    /// groups and add a summary of the reclaimed space to the GitHub Actions
+    /// job summary
+    #[arg(long, env = "SAFECLEAN_CI", value_parser = BoolishValueParser::new())]
+    ci: bool,
+
+    /// Start with nothing selected in the interactive selector
+    #[arg(long)]
+    opt_in: bool,
+
+    /// Draw the interactive selector with ASCII characters only
+    #[arg(long)]
+    ascii: bool,
+
+    /// When to use colors; auto honors NO_COLOR and skips them when output
+    /// isn't a terminal
+    #[arg(long, value_enum, value_name = "WHEN", env = "SAFECLEAN_COLOR", default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Show sizes in powers of 1024 (GiB), powers of 1000 (GB) or as exact
+    /// byte counts
+    #[arg(long, value_enum, value_name = "UNITS", env = "SAFECLEAN_SIZE_FORMAT", default_value_t = SizeFormat::Binary)]
+    size_format: SizeFormat,
+
+    /// Same as --format json; wins over --format and SAFECLEAN_FORMAT
+    #[arg(long)]
+    json: bool,
+
+    /// Print only the paths found (or, with --yes, deleted), each ended by
+    /// a NUL byte, for `xargs -0`
+    #[arg(long, conflicts_with = "json")]
+    print0: bool,
+
+    /// Write an HTML report of what was found and cleaned to FILE
+    #[arg(long, value_name = "FILE")]
+    report: Option<PathBuf>,
+
+    /// Write metrics of what was found and cleaned to FILE, in the format
+    /// of node_exporter's textfile collector
+    #[arg(long, value_name = "FILE")]
+    metrics_out: Option<PathBuf>,
+
+    /// With stats: rank projects by everything reclaimable in them, all
+    /// types together; JSON with any --format but human
+    #[arg(long)]
+    by_project: bool,
+
+    /// With stats: compare the packages in every node_modules found and
+    /// report those installed identically in several projects, and which
+    /// projects would gain most from cleaning or pnpm; JSON with any
+    /// --format but human
+    #[arg(long, conflicts_with = "by_project")]
+    duplicates: bool,
+
+    /// Show reclaimable space as a treemap by type and project instead of
+    /// cleaning
+    #[arg(long, conflicts_with_all = ["plan", "progress", "diff"])]
+    treemap: bool,
+
+    /// Show what appeared or grew since the last scan of the same
+    /// directories instead of cleaning; JSON with any --format but human
+    #[arg(long, conflicts_with_all = ["plan", "progress", "demo"])]
+    diff: bool,
+
+    /// Save the selection to FILE for `safeclean apply` instead of cleaning
+    /// it (with --yes or --format, everything found)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["dry_run", "progress"])]
+    plan: Option<PathBuf>,
+
+    /// Report progress as JSON events on stdout instead of showing the
+    /// selector or printing --format output; cleans only with --yes
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with = "demo")]
+    progress: Option<Progress>,
+
+    /// Order of the dry-run listing and machine-readable output
+    #[arg(long, value_enum, default_value_t = SortKey::Size)]
+    sort: SortKey,
+
+    /// Only list the first N results after sorting (dry run and
+    /// machine-readable output)
+    #[arg(long, value_name = "N")]
+    top: Option<usize>,
+
+    /// Output format. Anything but human prints the scan results instead of
+    /// cleaning; with --yes it cleans and prints what was deleted
+    #[arg(long, value_enum, env = "SAFECLEAN_FORMAT", default_value_t = Format::Human)]
+    format: Format,
+
+    /// Scan USER@HOST:PATH over ssh with the safeclean installed there, and
+    /// have it delete what is picked here
+    #[arg(
+        long,
+        value_name = "USER@HOST:PATH",
+        env = "SAFECLEAN_REMOTE",
+        value_parser = Remote::parse,
+        conflicts_with_all = ["path", "demo", "progress", "plan", "diff", "treemap", "free"]
+    )]
+    remote: Option<Remote>,
+
+    /// Only print errors
+    #[arg(short, long, env = "SAFECLEAN_QUIET", value_parser = BoolishValueParser::new(), conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Explain which directories matched and why others were skipped;
+    /// repeat (-vv) to list every directory visited
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
     /// Demo mode - show UI with simulated data (nothing is deleted)
     #[arg(long)]
     demo: bool,
-}
 
-fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
-    }
+    /// Defaults from the config file, overridden by the flags above
+    #[arg(skip)]
+    config: Config,
 }
 
-fn get_enabled_types(cli: &Cli) -> HashSet<ProjectType> {
-    let any_specified =
-        cli.rust || cli.node || cli.python || cli.java || cli.gradle || cli.dotnet || cli.next || cli.nuxt;
+fn get_enabled_types(args: &Args) -> HashSet<ProjectType> {
+    let any_specified = args.rust
+        || args.node
+        || args.python
+        || args.java
+        || args.gradle
+        || args.dotnet
+        || args.next
+        || args.nuxt
+        || !args.types.is_empty();
 
+    if !any_specified && !args.config.types.is_empty() {
+        // The config's types name built-in ones; its detectors still apply
+        let mut types: HashSet<ProjectType> = args.config.types.iter().copied().collect();
+        types.extend(ProjectType::detector_types());
+        return types;
+    }
     if !any_specified {
         return ProjectType::all().into_iter().collect();
     }
 
-    let mut types = HashSet::new();
-    if cli.rust {
+    let mut types: HashSet<ProjectType> = args.types.iter().copied().collect();
+    if args.rust {
         types.insert(ProjectType::Rust);
     }
-    if cli.node {
+    if args.node {
         types.insert(ProjectType::Node);
     }
-    if cli.python {
+    if args.python {
         types.insert(ProjectType::Python);
     }
-    if cli.java {
+    if args.java {
         types.insert(ProjectType::JavaMaven);
     }
-    if cli.gradle {
+    if args.gradle {
         types.insert(ProjectType::Gradle);
     }
-    if cli.dotnet {
+    if args.dotnet {
         types.insert(ProjectType::DotNet);
     }
-    if cli.next {
+    if args.next {
         types.insert(ProjectType::NextJs);
     }
-    if cli.nuxt {
+    if args.nuxt {
         types.insert(ProjectType::NuxtJs);
     }
     types
 }
 
+fn days_ago(days: u64) -> Option<SystemTime> {
+    SystemTime::now().checked_sub(Duration::from_secs(days * 24 * 60 * 60))
+}
+
 fn generate_demo_data() -> Vec<FoundDir> {
-    vec![
+    let mut dirs = vec![
         // Rust projects
         FoundDir {
             path: "/home/user/projects/api-server/target".into(),
             project_type: ProjectType::Rust,
             size_bytes: 1_892_000_000, // 1.9 GB
+            modified: days_ago(2),
+            project_root: "/home/user/projects/api-server".into(),
+            git: Some(GitStatus { dirty: false, stashed: true }),
+            in_use: false,
+            editor: None,
+            global: false,
+            container: None,
+            rebuild: None,
+            ignored: Some(true),
+            suspicious: None,
+            matched: Explanation::default(),
         },
         FoundDir {
             path: "/home/user/projects/cli-tool/target".into(),
             project_type: ProjectType::Rust,
             size_bytes: 456_000_000, // 456 MB
+            modified: days_ago(45),
+            project_root: "/home/user/projects/cli-tool".into(),
+            git: Some(GitStatus::default()),
+            in_use: false,
+            editor: None,
+            global: false,
+            container: None,
+            rebuild: None,
+            ignored: Some(true),
+            suspicious: None,
+            matched: Explanation::default(),
         },
         FoundDir {
             path: "/home/user/projects/utils/target".into(),
             project_type: ProjectType::Rust,
             size_bytes: 234_000_000, // 234 MB
+            modified: days_ago(120),
+            project_root: "/home/user/projects/utils".into(),
+            git: Some(GitStatus::default()),
+            in_use: false,
+            editor: None,
+            global: false,
+            container: None,
+            rebuild: None,
+            ignored: Some(true),
+            suspicious: None,
+            matched: Explanation::default(),
         },
         // Node.js projects
         FoundDir {
             path: "/home/user/projects/webapp/node_modules".into(),
             project_type: ProjectType::Node,
             size_bytes: 892_000_000, // 892 MB
+            modified: days_ago(0),
+            project_root: "/home/user/projects/webapp".into(),
+            git: Some(GitStatus { dirty: true, stashed: false }),
+            in_use: true,
+            editor: Some("VS Code"),
+            global: false,
+            container: None,
+            rebuild: None,
+            ignored: Some(true),
+            suspicious: None,
+            matched: Explanation::default(),
         },
         FoundDir {
             path: "/home/user/projects/dashboard/node_modules".into(),
             project_type: ProjectType::Node,
             size_bytes: 654_000_000, // 654 MB
+            modified: days_ago(14),
+            project_root: "/home/user/projects/dashboard".into(),
+            git: Some(GitStatus::default()),
+            in_use: false,
+            editor: None,
+            global: false,
+            container: None,
+            rebuild: None,
+            ignored: Some(true),
+            suspicious: None,
+            matched: Explanation::default(),
         },
         FoundDir {
             path: "/home/user/projects/blog/node_modules".into(),
             project_type: ProjectType::Node,
             size_bytes: 423_000_000, // 423 MB
+            modified: days_ago(200),
+            project_root: "/home/user/projects/blog".into(),
+            git: Some(GitStatus::default()),
+            in_use: false,
+            editor: None,
+            global: false,
+            container: None,
+            rebuild: None,
+            ignored: Some(true),
+            suspicious: None,
+            matched: Explanation::default(),
         },
         FoundDir {
             path: "/home/user/projects/portfolio/node_modules".into(),
             project_type: ProjectType::Node,
             size_bytes: 312_000_000, // 312 MB
+            modified: days_ago(9),
+            project_root: "/home/user/projects/portfolio".into(),
+            git: Some(GitStatus::default()),
+            in_use: false,
+            editor: None,
+            global: false,
+            container: None,
+            rebuild: None,
+            ignored: Some(true),
+            suspicious: None,
+            matched: Explanation::default(),
         },
         // Python projects
         FoundDir {
             path: "/home/user/projects/ml-pipeline/.venv".into(),
             project_type: ProjectType::Python,
             size_bytes: 1_234_000_000, // 1.2 GB
+            modified: days_ago(31),
+            project_root: "/home/user/projects/ml-pipeline".into(),
+            git: Some(GitStatus::default()),
+            in_use: false,
+            editor: None,
+            global: false,
+            container: None,
+            rebuild: None,
+            ignored: Some(true),
+            suspicious: None,
+            matched: Explanation::default(),
         },
         FoundDir {
             path: "/home/user/projects/data-analysis/.venv".into(),
             project_type: ProjectType::Python,
             size_bytes: 567_000_000, // 567 MB
+            modified: days_ago(90),
+            project_root: "/home/user/projects/data-analysis".into(),
+            git: Some(GitStatus::default()),
+            in_use: false,
+            editor: None,
+            global: false,
+            container: None,
+            rebuild: None,
+            ignored: Some(true),
+            suspicious: None,
+            matched: Explanation::default(),
         },
         FoundDir {
             path: "/home/user/projects/scripts/__pycache__".into(),
             project_type: ProjectType::Python,
             size_bytes: 12_000_000, // 12 MB
+            modified: days_ago(3),
+            project_root: "/home/user/projects/scripts".into(),
+            git: Some(GitStatus::default()),
+            in_use: false,
+            editor: None,
+            global: false,
+            container: None,
+            rebuild: None,
+            ignored: Some(true),
+            suspicious: None,
+            matched: Explanation::default(),
         },
         // Next.js
         FoundDir {
             path: "/home/user/projects/webapp/.next".into(),
             project_type: ProjectType::NextJs,
             size_bytes: 345_000_000, // 345 MB
+            modified: days_ago(1),
+            project_root: "/home/user/projects/webapp".into(),
+            git: Some(GitStatus { dirty: true, stashed: false }),
+            in_use: true,
+            editor: Some("VS Code"),
+            global: false,
+            container: None,
+            rebuild: None,
+            ignored: Some(true),
+            suspicious: None,
+            matched: Explanation::default(),
         },
         // Gradle
         FoundDir {
             path: "/home/user/projects/android-app/build".into(),
             project_type: ProjectType::Gradle,
             size_bytes: 789_000_000, // 789 MB
+            modified: days_ago(60),
+            project_root: "/home/user/projects/android-app".into(),
+            git: Some(GitStatus::default()),
+            in_use: false,
+            editor: None,
+            global: false,
+            container: None,
+            rebuild: None,
+            ignored: Some(false),
+            suspicious: None,
+            matched: Explanation::default(),
         },
         FoundDir {
             path: "/home/user/projects/android-app/.gradle".into(),
             project_type: ProjectType::Gradle,
             size_bytes: 234_000_000, // 234 MB
+            modified: days_ago(60),
+            project_root: "/home/user/projects/android-app".into(),
+            git: Some(GitStatus::default()),
+            in_use: false,
+            editor: None,
+            global: false,
+            container: None,
+            rebuild: None,
+            ignored: Some(true),
+            suspicious: None,
+            matched: Explanation::default(),
         },
-    ]
+    ];
+    // Explain each the way a real scan would, minus the marker file
+    let rules = projects::get_cleanable_dirs();
+    for dir in &mut dirs {
+        let name = dir.path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if let Some(rule) = rules.iter().find(|r| r.project_type == dir.project_type && r.matches_name(name)) {
+            dir.matched = Explanation {
+                pattern: rule.dir_name.to_string(),
+                rule: rule.rule(),
+                marker: None,
+            };
+        }
+    }
+    dirs
 }
 
 fn group_by_type(dirs: &[FoundDir]) -> Vec<(ProjectType, Vec<&FoundDir>)> {
@@ -213,99 +825,1113 @@ fn group_by_type(dirs: &[FoundDir]) -> Vec<(ProjectType, Vec<&FoundDir>)> {
     result
 }
 
+/// How long ago a directory must have last changed to be offered; `None`
+/// when `--older-than 0` turns the check off.
+fn older_than(args: &Args) -> Option<Duration> {
+    Some(args.older_than.or(args.config.older_than).unwrap_or(DEFAULT_OLDER_THAN)).filter(|age| !age.is_zero())
+}
+
+fn scan_options(args: &Args) -> ScanOptions {
+    let mut excluded = exclude::load();
+    excluded.extend(args.config.exclude.iter().cloned());
+    excluded.extend(args.config.protect.iter().cloned());
+    // Scanned paths are canonical, so compare against canonical exclusions
+    excluded.extend(args.exclude.iter().map(|path| path.canonicalize().unwrap_or_else(|_| path.clone())));
+    let mut enabled_types = get_enabled_types(args);
+    // Rules asked for by name apply whichever types are selected
+    if !args.include_dir.is_empty() {
+        enabled_types.insert(ProjectType::INCLUDED);
+    }
+    ScanOptions {
+        enabled_types,
+        excluded,
+        min_size: args.min_size.or(args.config.min_size),
+        older_than: older_than(args),
+        extra_dirs: args.include_dir.iter().chain(&args.config.detectors).cloned().collect(),
+        global: args.global,
+        docker: args.docker,
+        toolchains: args.toolchains,
+        cache_keep: Some(args.cache_keep.or(args.config.cache_keep).unwrap_or(DEFAULT_CACHE_KEEP))
+            .filter(|keep| !keep.is_zero()),
+        all_users: args.all_users,
+        skip_sizes: args.no_sizes,
+    }
+}
+
+/// Directories to scan: the path argument, else the configured roots, else
+/// the current directory. Exits if any of them doesn't exist.
+fn scan_roots(args: &Args) -> Vec<PathBuf> {
+    let roots = match &args.path {
+        Some(path) => vec![path.clone()],
+        None if !args.config.roots.is_empty() => args.config.roots.clone(),
+        None => vec![PathBuf::from(".")],
+    };
+    roots
+        .iter()
+        .map(|root| {
+            root.canonicalize().unwrap_or_else(|_| {
+                eprintln!("{} Invalid path: {}", "error:".red().bold(), root.display());
+                std::process::exit(EXIT_ERROR);
+            })
+        })
+        .collect()
+}
+
+fn clean_method(args: &Args) -> Method {
+    if args.trash || (args.config.trash && !args.permanent) {
+        Method::Trash
+    } else {
+        Method::Delete
+    }
+}
+
+/// Whether to review with the full-screen selector rather than the
+/// line-based prompt.
+fn use_selector(args: &Args) -> bool {
+    args.interactive == Interactive::Always || selector::can_run()
+}
+
+fn selector_options(args: &Args) -> SelectorOptions {
+    SelectorOptions {
+        preselect: !args.opt_in,
+        ascii: args.ascii || !selector::supports_unicode(),
+        color: args.color.enabled(),
+        remember: !args.demo,
+        clean: !args.demo && args.plan.is_none(),
+        method: clean_method(args),
+        ask_word: !args.demo && args.plan.is_none(),
+        preselected: None,
+        max_delete: args.max_delete.or(args.config.max_delete),
+        size_on_demand: args.no_sizes,
+        older_than: older_than(args),
+        large: LargeDelete {
+            size: args.confirm_over.or(args.config.confirm_over).unwrap_or(DEFAULT_CONFIRM_OVER),
+            count: args.confirm_count.or(args.config.confirm_count).unwrap_or(DEFAULT_CONFIRM_COUNT),
+        },
+    }
+}
+
+/// Hold `dirs` against the `--max-delete` cap. Over it, a typed "yes" on a
+/// terminal lets the clean go ahead; anywhere else it is refused. Returns the
+/// exit status to stop with, if any.
+fn check_cap(args: &Args, dirs: &[FoundDir]) -> Option<i32> {
+    let cap = args.max_delete.or(args.config.max_delete)?;
+    let total = scanner::total_size(dirs);
+    if total <= cap {
+        return None;
+    }
+    if !std::io::stdin().is_terminal() {
+        eprintln!(
+            "{} refusing to delete {}, more than the --max-delete cap of {}",
+            "error:".red().bold(),
+            format_size(total),
+            format_size(cap)
+        );
+        return Some(EXIT_ERROR);
+    }
+    eprint!(
+        "{} this would delete {}, more than the --max-delete cap of {}. Type 'yes' to go ahead: ",
+        "warning:".yellow().bold(),
+        format_size(total),
+        format_size(cap)
+    );
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_ok() && answer.trim() == "yes" {
+        return None;
+    }
+    eprintln!("{}", tr!("cancelled").yellow());
+    Some(EXIT_CANCELLED)
+}
+
+/// Have a deletion past the `--confirm-over` or `--confirm-count` limits
+/// confirmed by typing [`CONFIRM_WORD`], as the selector would, when nothing
+/// else asked first. Returns the exit status if it isn't.
+fn confirm_large(args: &Args, dirs: &[FoundDir]) -> Option<i32> {
+    let total = scanner::total_size(dirs);
+    if !selector_options(args).large.exceeded(dirs.len(), total) {
+        return None;
+    }
+    if !std::io::stdin().is_terminal() {
+        eprintln!(
+            "{} refusing to delete {} directories ({}) unconfirmed; pass --yes to go ahead",
+            "error:".red().bold(),
+            dirs.len(),
+            format_size(total)
+        );
+        return Some(EXIT_ERROR);
+    }
+    eprintln!("{}", tr!("confirm-large"));
+    eprint!("{}", tr!("prompt-type-word", word = CONFIRM_WORD));
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_ok() && answer.trim() == CONFIRM_WORD {
+        return None;
+    }
+    eprintln!("{}", tr!("cancelled").yellow());
+    Some(EXIT_CANCELLED)
+}
+
+/// A spinner showing `message` while something slow runs.
+fn spinner(message: String) -> ProgressBar {
+    // Diagnostics would fight with the spinner for the line
+    let spinner = if logging::enabled(Level::Normal) && !logging::enabled(Level::Verbose) {
+        ProgressBar::new_spinner()
+    } else {
+        ProgressBar::hidden()
+    };
+    spinner.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.cyan} {msg}")
+            .unwrap()
+            .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏"),
+    );
+    spinner.set_message(message);
+    spinner.enable_steady_tick(std::time::Duration::from_millis(80));
+    spinner
+}
+
+/// Scan `roots` behind a spinner, returning what was found and what the scan
+/// passed over.
+fn scan_with_spinner(args: &Args, roots: &[PathBuf]) -> (Vec<FoundDir>, Skipped) {
+    let locations: Vec<String> = roots.iter().map(|root| root.display().to_string()).collect();
+    let spinner = spinner(format!("Searching for build artifacts in {}", locations.join(", ")));
+    // Not in the middle of --format output
+    let group = (args.ci && args.format == Format::Human)
+        .then(|| ci::group(&format!("Scanning {}", locations.join(", "))));
+
+    let (found, skipped) = metrics::timed(|| scanner::scan(roots, &scan_options(args)));
+
+    spinner.finish_and_clear();
+    drop(group);
+    // Unmeasured, it would all look to have shrunk
+    if !args.no_sizes {
+        remember_scan(roots, &found);
+    }
+    (found, skipped)
+}
+
+/// Measure what a `--no-sizes` scan found, for a clean that won't go
+/// through the selector, leaving out what the thresholds then rule out.
+/// What it passes over goes in `skipped`.
+fn size_found(args: &Args, found: &mut Vec<FoundDir>, skipped: &mut Skipped) {
+    let spinner = spinner(format!("Sizing {} directories", found.len()));
+    for dir in found.iter_mut() {
+        skipped.unreadable.extend(scanner::measure_size(dir));
+    }
+    spinner.finish_and_clear();
+    let options = ScanOptions {
+        skip_sizes: false,
+        ..scan_options(args)
+    };
+    found.retain(|dir| options.wanted(dir, skipped));
+    found.sort_by_key(|d| std::cmp::Reverse(d.size_bytes));
+}
+
+/// Keep what a finished scan of `roots` found for the next `--diff`.
+fn remember_scan(roots: &[PathBuf], found: &[FoundDir]) {
+    if let Err(e) = snapshot::save(roots, found) {
+        eprintln!("{} could not save the scan for --diff: {}", "warning:".yellow().bold(), e);
+    }
+}
+
+/// Scan in the background and let the user review results as they arrive.
+/// Returns `None` when nothing was found. If the scan got to finish, what it
+/// passed over goes in `skipped`.
+fn scan_and_select(args: &Args, roots: Vec<PathBuf>, skipped: &mut Skipped) -> Option<Selection> {
+    let options = scan_options(args);
+    // Unmeasured, it would all look to have shrunk
+    let remember = !options.skip_sizes;
+    let (events, receiver) = mpsc::channel();
+    let (passed_over, finished) = mpsc::channel();
+    thread::spawn(move || {
+        // Pass events on to the selector, keeping the measured directories
+        // in case the scan gets to finish
+        let (scan_events, scanned) = mpsc::channel();
+        let scan_roots = roots.clone();
+        thread::spawn(move || scanner::scan_streaming(&scan_roots, &options, scan_events));
+        let mut found = Vec::new();
+        for event in scanned {
+            if let ScanEvent::Sized(dir) = &event {
+                found.push(dir.clone());
+            }
+            // Before the selector can see the scan finish
+            if let ScanEvent::Done(scan_skipped) = &event {
+                let _ = passed_over.send(scan_skipped.clone());
+            }
+            let done = matches!(event, ScanEvent::Done(_));
+            if events.send(event).is_err() {
+                return;
+            }
+            if done && remember {
+                remember_scan(&roots, &found);
+            }
+        }
+    });
+
+    let selector = GroupedSelector::new(Vec::new(), selector_options(args));
+    let selection = selector.run_streaming(receiver);
+    if let Ok(scan_skipped) = finished.try_recv() {
+        *skipped = scan_skipped;
+    }
+    match selection {
+        Ok(selected) => selected,
+        Err(e) => {
+            eprintln!("{} {}", "error:".red().bold(), e);
+            std::process::exit(EXIT_ERROR);
+        }
+    }
+}
+
 fn main() {
-    let cli = Cli::parse();
+    let cli = Cli::try_parse().unwrap_or_else(|e| {
+        let _ = e.print();
+        // --help and --version aren't failures
+        std::process::exit(if e.use_stderr() { EXIT_ERROR } else { 0 });
+    });
+    if cli.generate_man {
+        if let Err(e) = clap_mangen::Man::new(Cli::command()).render(&mut std::io::stdout()) {
+            eprintln!("{} {}", "error:".red().bold(), e);
+            std::process::exit(EXIT_ERROR);
+        }
+        return;
+    }
 
-    let found = if cli.demo {
-        println!(
+    let scan_only = match &cli.command {
+        Some(Command::Stats(args)) => Some(args),
+        Some(Command::Watch(watch)) => Some(&watch.args),
+        Some(Command::Apply(apply)) => Some(&apply.args),
+        Some(Command::Daemon(daemon)) => Some(&daemon.args),
+        _ => None,
+    };
+    if scan_only.is_some_and(|args| args.remote.is_some()) {
+        eprintln!("{} --remote only works with clean and scan", "error:".red().bold());
+        std::process::exit(EXIT_ERROR);
+    }
+    if scan_only.is_some_and(|args| args.no_sizes) {
+        eprintln!("{} --no-sizes only works with clean and scan", "error:".red().bold());
+        std::process::exit(EXIT_ERROR);
+    }
+
+    let code = match cli.command {
+        None => run_clean(prepare(cli.args)),
+        Some(Command::Clean(args)) => run_clean(prepare(args)),
+        Some(Command::Scan(mut args)) => {
+            // Saving a plan never deletes either, but needs the selector
+            args.dry_run = args.plan.is_none();
+            run_clean(prepare(args))
+        }
+        Some(Command::Stats(args)) => run_stats(prepare(args)),
+        Some(Command::Watch(watch)) => {
+            let args = prepare(watch.args);
+            let options = WatchOptions {
+                interval: watch.interval,
+                threshold: watch.threshold,
+                notify: args.notify,
+            };
+            watch::run(&scan_roots(&args), &scan_options(&args), &options)
+        }
+        Some(Command::Apply(apply)) => run_apply(&apply.plan_file, prepare(apply.args)),
+        Some(Command::Daemon(daemon)) => run_daemon(daemon.socket, prepare(daemon.args)),
+        Some(Command::Undo) => run_undo(),
+        Some(Command::ListTypes) => run_list_types(),
+        Some(Command::Config { action }) => run_config(action),
+        Some(Command::Schedule { action }) => run_schedule(action),
+        Some(Command::IntegrateShell { uninstall }) => run_integrate_shell(uninstall),
+    };
+    std::process::exit(code);
+}
+
+/// Apply the config file and the logging flags.
+fn prepare(mut args: Args) -> Args {
+    args.config = config::load(args.profile.as_deref()).unwrap_or_else(|e| {
+        eprintln!("{} invalid config file {}", "error:".red().bold(), e);
+        std::process::exit(EXIT_ERROR);
+    });
+    if let Some(file) = &args.rules {
+        let rules = config::load_rules(file).unwrap_or_else(|e| {
+            eprintln!("{} invalid rules file {}", "error:".red().bold(), e);
+            std::process::exit(EXIT_ERROR);
+        });
+        args.config.detectors.extend(rules.detectors);
+        args.config.exclude.extend(rules.protect.iter().cloned());
+        args.config.protect.extend(rules.protect);
+    }
+    safeclean::protect::add(args.config.protect.iter().cloned());
+    if args.all_users && !safeclean::owner::privileged() {
+        eprintln!(
+            "{} --all-users needs root, since other users' directories can only be removed with their permissions",
+            "error:".red().bold()
+        );
+        std::process::exit(EXIT_ERROR);
+    }
+    match args.interactive {
+        Interactive::Always if args.yes => {
+            eprintln!("{} --interactive always can't be combined with --yes", "error:".red().bold());
+            std::process::exit(EXIT_ERROR);
+        }
+        Interactive::Never => args.yes = true,
+        _ => {}
+    }
+    if args.ci {
+        if args.interactive == Interactive::Always {
+            eprintln!("{} --interactive always can't be combined with --ci", "error:".red().bold());
+            std::process::exit(EXIT_ERROR);
+        }
+        args.yes = true;
+    }
+    if args.json {
+        args.format = Format::Json;
+    }
+    if args.print0 {
+        args.format = Format::Print0;
+    }
+    // Auto leaves terminal detection to colored and console
+    if args.color != ColorChoice::Auto || !args.color.enabled() {
+        colored::control::set_override(args.color.enabled());
+        console::set_colors_enabled(args.color.enabled());
+        console::set_colors_enabled_stderr(args.color.enabled());
+    }
+    units::set_size_format(args.size_format);
+    logging::set_level(match (args.quiet, args.verbose) {
+        (true, _) => Level::Quiet,
+        (false, 0) => Level::Normal,
+        (false, 1) => Level::Verbose,
+        (false, _) => Level::Trace,
+    });
+    args
+}
+
+/// Scan, then report or clean, holding unreadable directories against the
+/// exit status if asked to. Returns the exit status.
+fn run_clean(args: Args) -> i32 {
+    let fail_on_error = args.fail_on_error;
+    let default_age = args.older_than.is_none() && args.config.older_than.is_none() && !args.demo;
+    let mut skipped = Skipped::default();
+    let code = scan_and_clean(args, &mut skipped);
+    let too_recent = skipped.too_recent.len();
+    if default_age && too_recent > 0 && logging::enabled(Level::Normal) {
+        eprintln!(
+            "{} left alone {} directories modified in the last {} hours; --older-than 0 offers them too",
+            "note:".cyan().bold(),
+            too_recent,
+            DEFAULT_OLDER_THAN.as_secs() / 3600
+        );
+    }
+    if !fail_on_error || skipped.unreadable.is_empty() || code == EXIT_ERROR || code == EXIT_CANCELLED {
+        return code;
+    }
+
+    eprintln!("{} some paths could not be read:", "error:".red().bold());
+    for path in &skipped.unreadable {
+        eprintln!("  {}", path.display());
+    }
+    EXIT_PARTIAL_FAILURE
+}
+
+/// What the steps after the scan need to know about the run so far.
+struct Run {
+    started: SystemTime,
+    /// Everything offered for cleaning, kept for the report
+    found: Vec<FoundDir>,
+    /// Free space before the scan, to compare against afterwards
+    disk_before: Vec<DiskFree>,
+}
+
+/// Scan, then report or clean, noting what the scan passed over in `skipped`.
+/// Returns the exit status.
+fn scan_and_clean(args: Args, skipped: &mut Skipped) -> i32 {
+    if args.diff {
+        return show_diff(&args);
+    }
+    if args.treemap {
+        return show_treemap(&args);
+    }
+    if let Some(remote) = &args.remote {
+        return run_remote(&args, remote);
+    }
+    let mut run = Run {
+        started: SystemTime::now(),
+        found: Vec::new(),
+        disk_before: Vec::new(),
+    };
+    // Held until the run ends
+    let _locks;
+    if args.progress == Some(Progress::Json) {
+        let roots = scan_roots(&args);
+        _locks = lock_roots(&args, &roots);
+        let (found, scan_skipped) = metrics::timed(|| progress::scan(&roots, scan_options(&args)));
+        *skipped = scan_skipped;
+        remember_scan(&roots, &found);
+        if !args.yes || args.dry_run {
+            save_reports(&args, &found, None);
+            progress::done(&found, None);
+            return if found.is_empty() { EXIT_NOTHING_FOUND } else { 0 };
+        }
+        let found = hold_back_suspicious(found);
+        if let Some(code) = check_cap(&args, &found) {
+            return code;
+        }
+        warn_before_cleaning(&found);
+        let result = progress::clean(found.clone(), clean_method(&args));
+        run.found = found.clone();
+        after_clean(&args, &run, &result);
+        progress::done(&found, Some(&result));
+        return if !result.failed.is_empty() {
+            EXIT_PARTIAL_FAILURE
+        } else if result.deleted.is_empty() {
+            EXIT_NOTHING_FOUND
+        } else {
+            0
+        };
+    }
+
+    let machine_output = args.format != Format::Human;
+    // Unattended: clean everything found and describe the outcome for scripts
+    let machine_clean = machine_output && args.yes && !args.dry_run && !args.demo;
+    // Only a listing goes without sizes; deleting needs them for the caps
+    // and to spot hand-written files
+    let listing = args.dry_run || (machine_output && !machine_clean && args.plan.is_none());
+    let mut sized = !args.no_sizes || args.demo;
+
+    let mut found = if args.demo && machine_output {
+        generate_demo_data()
+    } else if args.demo {
+        info!(
             "{} {}\n",
             "Demo mode".yellow().bold(),
             "(simulated data - nothing will be deleted)".dimmed()
         );
         generate_demo_data()
     } else {
-        let path = cli.path.canonicalize().unwrap_or_else(|_| {
-            eprintln!("{} Invalid path: {}", "error:".red().bold(), cli.path.display());
-            std::process::exit(1);
-        });
-
-        let spinner = ProgressBar::new_spinner();
-        spinner.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.cyan} {msg}")
-                .unwrap()
-                .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏"),
-        );
-        spinner.set_message(format!("Searching for build artifacts in {}", path.display()));
-        spinner.enable_steady_tick(std::time::Duration::from_millis(80));
+        let roots = scan_roots(&args);
+        _locks = lock_roots(&args, &roots);
+        if !machine_output {
+            run.disk_before = disk::free_space(&roots);
+            disk::print_free(&run.disk_before);
+        }
 
-        let enabled_types = get_enabled_types(&cli);
-        let result = scanner::scan(&path, &enabled_types);
+        // A budget needs every size before it can pick, and reports need
+        // everything found, so neither can stream; nor can the line-based
+        // prompt used without a terminal
+        if !args.dry_run
+            && !args.yes
+            && !machine_output
+            && args.free.is_none()
+            && !wants_reports(&args)
+            && use_selector(&args)
+        {
+            return match scan_and_select(&args, roots, skipped) {
+                Some(selection) => finish_selection(&args, &run, selection),
+                None => {
+                    info!("{}", tr!("no-dirs-found").yellow());
+                    EXIT_NOTHING_FOUND
+                }
+            };
+        }
 
-        spinner.finish_and_clear();
-        result
+        let (mut found, scan_skipped) = scan_with_spinner(&args, &roots);
+        *skipped = scan_skipped;
+        if !sized && !listing {
+            size_found(&args, &mut found, skipped);
+            sized = true;
+        }
+        found
     };
 
+    let mut preselected = None;
+    if let Some(target) = args.free {
+        let budget = budget::pick(&found, target);
+        if budget.bytes < target {
+            eprintln!(
+                "{} only {} of low-risk space can be freed, short of {}",
+                "warning:".yellow().bold(),
+                format_size(budget.bytes),
+                format_size(target)
+            );
+        }
+        if args.dry_run || args.yes || machine_output {
+            found.retain(|d| budget.paths.contains(&d.path));
+        } else {
+            info!(
+                "Selected {} directories ({}) to free {}",
+                budget.paths.len().to_string().green().bold(),
+                format_size(budget.bytes).green().bold(),
+                format_size(target)
+            );
+            preselected = Some(budget.paths);
+        }
+    }
+
+    // Without a selector to pick from, a plan holds everything found
+    if args.plan.is_some() && (args.yes || machine_output) {
+        return save_plan(&args, &found);
+    }
+
+    // Reports only; what gets deleted is never narrowed down by ordering
+    if args.dry_run || (machine_output && !machine_clean) {
+        found.sort_by(|a, b| args.sort.compare(a, b));
+        if let Some(top) = args.top {
+            found.truncate(top);
+        }
+    }
+
+    if machine_clean {
+        if wants_reports(&args) {
+            run.found = found.clone();
+        }
+        return clean_unattended(&args, &run, found);
+    }
+
+    if machine_output {
+        save_reports(&args, &found, None);
+        let written = output::write(args.format, &found, &mut std::io::stdout().lock());
+        // A closed pipe (e.g. `| head`) is the reader's choice, not an error
+        if let Err(e) = written
+            && e.kind() != std::io::ErrorKind::BrokenPipe
+        {
+            eprintln!("{} {}", "error:".red().bold(), e);
+            return EXIT_ERROR;
+        }
+        return if found.is_empty() { EXIT_NOTHING_FOUND } else { 0 };
+    }
+
     if found.is_empty() {
-        println!("{}", "No cleanable directories found.".yellow());
-        return;
+        save_reports(&args, &found, None);
+        info!("{}", tr!("no-dirs-found").yellow());
+        return EXIT_NOTHING_FOUND;
     }
 
     let total_size = scanner::total_size(&found);
-    println!(
-        "Found {} cleanable directories ({})\n",
-        found.len().to_string().green().bold(),
-        format_size(total_size).green().bold()
-    );
+    if sized {
+        info!(
+            "{}",
+            tr!(
+                "found-dirs",
+                count = found.len().to_string().green().bold().to_string(),
+                size = format_size(total_size).green().bold().to_string(),
+            )
+        );
+        disk::print_by_mount(&found);
+    } else {
+        info!("{}", tr!("found-dirs-unsized", count = found.len().to_string().green().bold().to_string()));
+    }
+    info!();
 
-    if cli.dry_run {
-        println!("{}", "Dry run - nothing will be deleted:\n".yellow());
-        let grouped = group_by_type(&found);
-        for (project_type, dirs) in &grouped {
-            let group_size: u64 = dirs.iter().map(|d| d.size_bytes).sum();
-            println!(
-                "{} {} ({} items, {})",
-                "▼".dimmed(),
-                project_type.name().bold(),
-                dirs.len(),
-                format_size(group_size).green()
-            );
+    if args.dry_run {
+        save_reports(&args, &found, None);
+        print_dry_run(&found, sized);
+        return 0;
+    }
+
+    // Reports list what wasn't selected too
+    if wants_reports(&args) {
+        run.found = found.clone();
+    }
+    if args.yes {
+        return clean_selected(&args, &run, found, false);
+    }
+    let defaults = selector_options(&args);
+    let options = SelectorOptions {
+        // A one-off budget shouldn't replace the remembered selection
+        remember: defaults.remember && preselected.is_none(),
+        preselected,
+        // Everything was sized after the scan
+        size_on_demand: false,
+        ..defaults
+    };
+    let selection = if use_selector(&args) {
+        GroupedSelector::new(found, options).run()
+    } else {
+        prompt::select(found, &options)
+    };
+    match selection {
+        Ok(selection) => finish_selection(&args, &run, selection),
+        Err(e) => {
+            eprintln!("{} {}", "error:".red().bold(), e);
+            EXIT_ERROR
+        }
+    }
+}
+
+/// List what a dry run would clean, by type, with sizes if `sized`.
+fn print_dry_run(found: &[FoundDir], sized: bool) {
+    println!("{}\n", tr!("dry-run-header").yellow());
+    let grouped = group_by_type(found);
+    for (project_type, dirs) in &grouped {
+        if !sized {
+            println!("{} {} ({} items)", "▼".dimmed(), project_type.name().bold(), dirs.len());
             for dir in dirs {
-                println!(
-                    "    {}  {:>10}",
-                    dir.path.display(),
-                    dir.size_human()
-                );
+                println!("    {}{}", dir.path.display(), prompt::warnings(dir).red().bold());
             }
             println!();
+            continue;
+        }
+        let group_size: u64 = dirs.iter().map(|d| d.size_bytes).sum();
+        println!(
+            "{} {} ({} items, {})",
+            "▼".dimmed(),
+            project_type.name().bold(),
+            dirs.len(),
+            format_size(group_size).green()
+        );
+        for dir in dirs {
+            let mut note = dir.rebuild.as_ref().map_or_else(String::new, |r| format!("  {}", r.describe()));
+            if dir.global
+                && let Some(limit) = caches::size_limit(&dir.path)
+            {
+                note = format!("  of its {} limit", format_size(limit));
+            }
+            println!(
+                "    {}  {:>10}{}{}",
+                dir.path.display(),
+                dir.size_human(),
+                note.dimmed(),
+                prompt::warnings(dir).red().bold()
+            );
         }
+        println!();
+    }
+    if sized {
         println!(
             "{} {}",
-            "Total:".bold(),
-            format_size(total_size).green().bold()
+            tr!("total").bold(),
+            format_size(scanner::total_size(found)).green().bold()
         );
-        return;
     }
+}
 
-    let to_delete = if cli.yes {
-        found
+/// Scan and clean a directory on another machine. The safeclean there
+/// scans and deletes; this end shows what it found and asks. Returns the
+/// exit status.
+fn run_remote(args: &Args, remote: &Remote) -> i32 {
+    let run = Run {
+        started: SystemTime::now(),
+        found: Vec::new(),
+        disk_before: Vec::new(),
+    };
+    let (events, receiver) = mpsc::channel();
+    let scanning = {
+        let remote = remote.clone();
+        let forwarded = forwarded_args(args);
+        thread::spawn(move || remote::scan(&remote, &forwarded, events))
+    };
+    let scanned = |scanning: thread::JoinHandle<std::io::Result<()>>| match scanning.join() {
+        Ok(Err(e)) => {
+            eprintln!("{} {}", "error:".red().bold(), e);
+            false
+        }
+        _ => true,
+    };
+
+    let machine_output = args.format != Format::Human;
+    let selection = if !args.dry_run && !args.yes && !machine_output && use_selector(args) {
+        let options = SelectorOptions {
+            // Paths here name nothing on this machine
+            remember: false,
+            clean: false,
+            ..selector_options(args)
+        };
+        let selection = GroupedSelector::new(Vec::new(), options).run_streaming(receiver);
+        if !scanned(scanning) {
+            return EXIT_ERROR;
+        }
+        match selection {
+            Ok(Some(selection)) => selection,
+            Ok(None) => {
+                info!("{}", tr!("no-dirs-found").yellow());
+                return EXIT_NOTHING_FOUND;
+            }
+            Err(e) => {
+                eprintln!("{} {}", "error:".red().bold(), e);
+                return EXIT_ERROR;
+            }
+        }
     } else {
-        let selector = GroupedSelector::new(found);
-        match selector.run() {
-            Ok(selected) => selected,
-            Err(_) => {
-                println!("{}", "Cancelled.".yellow());
-                return;
+        if !machine_output {
+            info!("Scanning {}...", remote);
+        }
+        let mut found: Vec<FoundDir> = receiver
+            .iter()
+            .filter_map(|event| match event {
+                ScanEvent::Sized(dir) => Some(dir),
+                _ => None,
+            })
+            .collect();
+        if !scanned(scanning) {
+            return EXIT_ERROR;
+        }
+        found.sort_by_key(|dir| std::cmp::Reverse(dir.size_bytes));
+        if args.dry_run || (machine_output && !args.yes) {
+            found.sort_by(|a, b| args.sort.compare(a, b));
+            if let Some(top) = args.top {
+                found.truncate(top);
+            }
+            save_reports(args, &found, None);
+            if machine_output {
+                let _ = output::write(args.format, &found, &mut std::io::stdout().lock());
+            } else if found.is_empty() {
+                info!("{}", tr!("no-dirs-found").yellow());
+            } else {
+                print_dry_run(&found, true);
+            }
+            return if found.is_empty() { EXIT_NOTHING_FOUND } else { 0 };
+        }
+        if found.is_empty() {
+            save_reports(args, &found, None);
+            info!("{}", tr!("no-dirs-found").yellow());
+            return EXIT_NOTHING_FOUND;
+        }
+        if args.yes {
+            if let Some(code) = check_cap(args, &found) {
+                return code;
+            }
+            Selection::Pending(found)
+        } else {
+            match prompt::select(found, &selector_options(args)) {
+                Ok(selection) => selection,
+                Err(e) => {
+                    eprintln!("{} {}", "error:".red().bold(), e);
+                    return EXIT_ERROR;
+                }
             }
         }
     };
 
+    let to_delete = match selection {
+        Selection::Pending(to_delete) => to_delete,
+        // Not asked of the selector, which would delete here
+        Selection::Cleaned(result) => return report_clean(&run, &result),
+        Selection::Cancelled => {
+            info!("{}", tr!("cancelled").yellow());
+            return EXIT_CANCELLED;
+        }
+    };
     if to_delete.is_empty() {
-        println!("{}", "Nothing selected.".yellow());
-        return;
+        info!("{}", tr!("nothing-selected").yellow());
+        return 0;
+    }
+    let mut forwarded = forwarded_args(args);
+    forwarded.push(match clean_method(args) {
+        Method::Trash => "--trash".to_string(),
+        Method::Delete => "--permanent".to_string(),
+    });
+    if !machine_output {
+        info!("\nRemoving {} directories on {}...", to_delete.len(), remote.host);
+    }
+    let result = match remote::clean(remote, &forwarded, to_delete) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("{} {}", "error:".red().bold(), e);
+            return EXIT_ERROR;
+        }
+    };
+    // Recorded in the history and undo list of the machine that deleted
+    // them, not here
+    save_reports(args, &result.deleted, Some(&result));
+    if machine_output {
+        let _ = output::write_clean(args.format, &result, &mut std::io::stdout().lock());
+        return if result.failed.is_empty() { 0 } else { EXIT_PARTIAL_FAILURE };
+    }
+    report_clean(&run, &result)
+}
+
+/// The flags choosing what to scan for, to pass on to safeclean on another
+/// machine, whose config file supplies the rest.
+fn forwarded_args(args: &Args) -> Vec<String> {
+    let switches = [
+        (args.rust, "--rust"),
+        (args.node, "--node"),
+        (args.python, "--python"),
+        (args.java, "--java"),
+        (args.gradle, "--gradle"),
+        (args.dotnet, "--dotnet"),
+        (args.next, "--next"),
+        (args.nuxt, "--nuxt"),
+        (args.global, "--global"),
+        (args.docker, "--docker"),
+        (args.toolchains, "--toolchains"),
+        (args.all_users, "--all-users"),
+    ];
+    let mut forwarded: Vec<String> = switches
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, flag)| flag.to_string())
+        .collect();
+    if !args.types.is_empty() {
+        let types: Vec<&str> = args.types.iter().map(|pt| pt.flag_names()[0]).collect();
+        forwarded.push(format!("--types={}", types.join(",")));
+    }
+    for rule in &args.include_dir {
+        forwarded.push(match &rule.validator {
+            projects::Validator::Sibling(file) => format!("--include-dir={}:{}", rule.dir_name, file),
+            _ => format!("--include-dir={}", rule.dir_name),
+        });
+    }
+    forwarded.extend(args.exclude.iter().map(|path| format!("--exclude={}", path.display())));
+    let hours = |age: Duration| format!("{}h", age.as_secs_f64() / 3600.0);
+    if let Some(age) = args.older_than {
+        forwarded.push(format!("--older-than={}", hours(age)));
+    }
+    if let Some(age) = args.cache_keep {
+        forwarded.push(format!("--cache-keep={}", hours(age)));
+    }
+    if let Some(size) = args.min_size {
+        forwarded.push(format!("--min-size={}", size));
+    }
+    forwarded
+}
+
+/// Lock `roots` against other runs if this run may delete under them, so
+/// two runs never clean the same directories at once. Exits if another run
+/// holds one of them.
+fn lock_roots(args: &Args, roots: &[PathBuf]) -> Option<lock::RootLocks> {
+    // Progress output and the other formats only list results without --yes
+    let lists_only = args.progress == Some(Progress::Json) || args.format != Format::Human;
+    let may_delete = !args.dry_run && !args.demo && args.plan.is_none() && (args.yes || !lists_only);
+    if !may_delete {
+        return None;
+    }
+    match lock::lock_roots(roots) {
+        Ok(locks) => Some(locks),
+        Err(LockError::Held(root)) => {
+            eprintln!(
+                "{} another safeclean run is cleaning {} or something in it; try again once it finishes",
+                "error:".red().bold(),
+                root.display()
+            );
+            std::process::exit(EXIT_ERROR);
+        }
+        Err(LockError::Io(e)) => {
+            eprintln!("{} could not lock the scanned directories: {}", "warning:".yellow().bold(), e);
+            None
+        }
+    }
+}
+
+/// Scan, then draw a treemap of what was found. Returns the exit status.
+fn show_treemap(args: &Args) -> i32 {
+    let found = if args.demo {
+        generate_demo_data()
+    } else {
+        scan_with_spinner(args, &scan_roots(args)).0
+    };
+    if found.is_empty() {
+        info!("{}", tr!("no-dirs-found").yellow());
+        return EXIT_NOTHING_FOUND;
+    }
+    let (rows, columns) = console::Term::stdout().size();
+    let ascii = args.ascii || !selector::supports_unicode();
+    // Room for the legend below the map
+    let height = (rows as usize).saturating_sub(ProjectType::all().len() + 2);
+    for line in treemap::render(&found, columns as usize, height, ascii) {
+        println!("{}", line);
+    }
+    0
+}
+
+/// Scan, then show what appeared or grew since the previous scan of the
+/// same roots. Returns the exit status.
+fn show_diff(args: &Args) -> i32 {
+    let roots = scan_roots(args);
+    let previous = snapshot::load(&roots);
+    let (found, _) = scan_with_spinner(args, &roots);
+    let Some(previous) = previous else {
+        info!(
+            "{}",
+            "No earlier scan of these directories to compare with; this one is kept for next time".yellow()
+        );
+        return EXIT_NOTHING_FOUND;
+    };
+    let mut diff = snapshot::diff(&previous, &found);
+    if let Some(top) = args.top {
+        diff.new.truncate(top);
+        diff.grew.truncate(top);
+    }
+    let changed = !diff.new.is_empty() || !diff.grew.is_empty();
+
+    if args.format != Format::Human {
+        match serde_json::to_string_pretty(&diff) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("{} {}", "error:".red().bold(), e);
+                return EXIT_ERROR;
+            }
+        }
+        return if changed { 0 } else { EXIT_NOTHING_FOUND };
+    }
+
+    let (year, month, day) = history::date_of(diff.since);
+    println!("{}\n", format!("Since the last scan on {}-{:02}-{:02}:", year, month, day).bold());
+    if !changed {
+        println!("{}", "Nothing new and nothing grew".yellow());
+    }
+    if !diff.new.is_empty() {
+        println!(
+            "{} {} directories, {}",
+            "New:".green().bold(),
+            diff.new.len(),
+            format_size(diff.new.iter().map(|c| c.size_bytes).sum()).green()
+        );
+        for change in &diff.new {
+            println!("    {}  {:>10}  ({})", change.path.display(), format_size(change.size_bytes), change.project_type);
+        }
+    }
+    if !diff.grew.is_empty() {
+        println!(
+            "{} {} directories, +{}",
+            "Grew:".yellow().bold(),
+            diff.grew.len(),
+            format_size(diff.grew.iter().map(|c| c.grew_bytes).sum()).yellow()
+        );
+        for change in &diff.grew {
+            println!(
+                "    {}  {:>11}  (now {})",
+                change.path.display(),
+                format!("+{}", format_size(change.grew_bytes)),
+                format_size(change.size_bytes)
+            );
+        }
+    }
+    if !diff.gone.is_empty() {
+        println!(
+            "\n{}",
+            format!("{} directories found last time are gone, cleaned or removed", diff.gone.len()).dimmed()
+        );
+    }
+    if changed { 0 } else { EXIT_NOTHING_FOUND }
+}
+
+/// Warn about each of `dirs` inside a repository but not covered by its
+/// ignore rules, since cleaning it may throw away tracked, hand-written
+/// files, each whose project is open in an editor, and each mounted into a
+/// running container.
+fn warn_before_cleaning(dirs: &[FoundDir]) {
+    for dir in dirs {
+        if dir.ignored == Some(false) {
+            eprintln!(
+                "{} {} is not gitignored; it may hold tracked files",
+                "warning:".yellow().bold(),
+                dir.path.display()
+            );
+        }
+        if let Some(editor) = dir.editor {
+            eprintln!("{} {} is open in {}", "warning:".yellow().bold(), dir.project_root.display(), editor);
+        }
+        if let Some(container) = &dir.container {
+            eprintln!(
+                "{} {} is mounted into running container {}",
+                "warning:".yellow().bold(),
+                dir.path.display(),
+                container
+            );
+        }
+    }
+}
+
+/// Leave out each of `dirs` holding files that don't look like build output.
+/// Cleaning those takes a confirmation an unattended run can't give.
+fn hold_back_suspicious(dirs: Vec<FoundDir>) -> Vec<FoundDir> {
+    dirs.into_iter()
+        .filter(|dir| {
+            let Some(file) = &dir.suspicious else {
+                return true;
+            };
+            eprintln!(
+                "{} skipping {}: {} doesn't look like build output; pick it interactively to clean it",
+                "warning:".yellow().bold(),
+                dir.path.display(),
+                file.display()
+            );
+            false
+        })
+        .collect()
+}
+
+/// Clean `found` without asking and describe the outcome in the machine
+/// format. Returns the exit status.
+fn clean_unattended(args: &Args, run: &Run, found: Vec<FoundDir>) -> i32 {
+    let found = hold_back_suspicious(found);
+    if let Some(code) = check_cap(args, &found) {
+        return code;
+    }
+    warn_before_cleaning(&found);
+    let result = cleaner::clean(found, clean_method(args));
+    after_clean(args, run, &result);
+    let written = output::write_clean(args.format, &result, &mut std::io::stdout().lock());
+    if let Err(e) = written
+        && e.kind() != std::io::ErrorKind::BrokenPipe
+    {
+        eprintln!("{} {}", "error:".red().bold(), e);
+        return EXIT_ERROR;
+    }
+    if !result.failed.is_empty() {
+        EXIT_PARTIAL_FAILURE
+    } else if result.deleted.is_empty() {
+        EXIT_NOTHING_FOUND
+    } else {
+        0
+    }
+}
+
+/// Save the selected directories as a plan for `safeclean apply`. Returns
+/// the exit status.
+fn save_plan(args: &Args, selected: &[FoundDir]) -> i32 {
+    let Some(file) = &args.plan else {
+        return 0;
+    };
+    if selected.is_empty() {
+        info!("{}", tr!("nothing-selected").yellow());
+        return EXIT_NOTHING_FOUND;
+    }
+    if let Err(e) = plan::save(file, selected) {
+        eprintln!("{} could not write plan {}: {}", "error:".red().bold(), file.display(), e);
+        return EXIT_ERROR;
+    }
+    info!(
+        "Saved {} directories ({}) to {}; `safeclean apply {}` cleans them",
+        selected.len().to_string().green().bold(),
+        format_size(scanner::total_size(selected)).green().bold(),
+        file.display(),
+        file.display()
+    );
+    0
+}
+
+/// Act on how the selector finished. Returns the exit status.
+fn finish_selection(args: &Args, run: &Run, selection: Selection) -> i32 {
+    match selection {
+        Selection::Pending(to_delete) => clean_selected(args, run, to_delete, true),
+        Selection::Cleaned(result) => {
+            after_clean(args, run, &result);
+            report_clean(run, &result)
+        }
+        Selection::Cancelled => {
+            save_reports(args, &run.found, None);
+            info!("{}", tr!("cancelled").yellow());
+            EXIT_CANCELLED
+        }
     }
+}
 
-    if cli.demo {
+/// Delete the confirmed directories. `reviewed` says they were picked in the
+/// selector or the prompt, which point out anything unusual and ask before
+/// returning it. Returns the exit status.
+fn clean_selected(args: &Args, run: &Run, to_delete: Vec<FoundDir>, reviewed: bool) -> i32 {
+    if args.plan.is_some() {
+        return save_plan(args, &to_delete);
+    }
+    let to_delete = if reviewed {
+        to_delete
+    } else {
+        hold_back_suspicious(to_delete)
+    };
+    if to_delete.is_empty() {
+        save_reports(args, &run.found, None);
+        info!("{}", tr!("nothing-selected").yellow());
+        return 0;
+    }
+
+    if args.demo {
+        save_reports(args, &run.found, None);
         // Demo mode - just show what would be deleted
         let total: u64 = to_delete.iter().map(|d| d.size_bytes).sum();
         println!(
@@ -332,26 +1958,421 @@ fn main() {
             "Nothing was deleted - this is a demo.".yellow(),
             "Run without --demo to actually clean.".dimmed()
         );
+        return 0;
+    }
+
+    if let Some(code) = check_cap(args, &to_delete) {
+        save_reports(args, &run.found, None);
+        return code;
+    }
+    if !reviewed {
+        warn_before_cleaning(&to_delete);
+        if !args.yes
+            && let Some(code) = confirm_large(args, &to_delete)
+        {
+            save_reports(args, &run.found, None);
+            return code;
+        }
+    }
+
+    let method = clean_method(args);
+    let verb = match method {
+        Method::Delete => tr!("deleting"),
+        Method::Trash => tr!("trashing"),
+    };
+    info!(
+        "\n{}",
+        tr!("removing-dirs", verb = verb.red().bold().to_string(), count = to_delete.len())
+    );
+
+    let group = args.ci.then(|| {
+        let group = ci::group(&format!("Removing {} directories", to_delete.len()));
+        for dir in &to_delete {
+            println!("{}  {}", dir.path.display(), dir.size_human());
+        }
+        group
+    });
+    let result = cleaner::clean(to_delete, method);
+    drop(group);
+    after_clean(args, run, &result);
+    report_clean(run, &result)
+}
+
+/// Whether an HTML report, a metrics file or a CI summary was asked for.
+fn wants_reports(args: &Args) -> bool {
+    args.report.is_some() || args.metrics_out.is_some() || args.ci
+}
+
+/// Write the HTML report, the metrics file and the CI summary, if asked for.
+fn save_reports(args: &Args, found: &[FoundDir], cleaned: Option<&CleanResult>) {
+    if args.ci
+        && let Err(e) = ci::write_summary(found, cleaned)
+    {
+        eprintln!("{} could not write the job summary: {}", "error:".red().bold(), e);
+    }
+    if let Some(file) = &args.report {
+        match report::write(file, found, cleaned) {
+            Ok(()) => verbose!("wrote report to {}", file.display()),
+            Err(e) => eprintln!("{} could not write report {}: {}", "error:".red().bold(), file.display(), e),
+        }
+    }
+    if let Some(file) = &args.metrics_out {
+        match metrics::write(file, found, cleaned) {
+            Ok(()) => verbose!("wrote metrics to {}", file.display()),
+            Err(e) => eprintln!("{} could not write metrics {}: {}", "error:".red().bold(), file.display(), e),
+        }
+    }
+}
+
+/// Record the clean and report it, then tell the user it finished if they
+/// asked.
+fn after_clean(args: &Args, run: &Run, result: &CleanResult) {
+    record_clean(clean_method(args), run.started, result);
+    save_reports(args, &run.found, Some(result));
+    if !args.notify || (result.deleted.is_empty() && result.failed.is_empty()) {
         return;
     }
 
-    println!("\n{} {} directories...", "Deleting".red().bold(), to_delete.len());
+    let mut body = format!(
+        "Cleaned {} in {} directories",
+        format_size(result.total_cleaned()),
+        result.deleted.len()
+    );
+    if !result.failed.is_empty() {
+        body.push_str(&format!(", {} failed", result.failed.len()));
+    }
+    if let Err(e) = desktop::notify("safeclean", &body) {
+        eprintln!("{} could not show notification: {}", "warning:".yellow().bold(), e);
+    }
+}
 
-    let result = cleaner::clean(to_delete);
+/// Add the clean to the history for `safeclean stats`, and remember what went
+/// to the trash so `safeclean undo` can restore it.
+fn record_clean(method: Method, started: SystemTime, result: &CleanResult) {
+    if result.deleted.is_empty() {
+        return;
+    }
+    if let Err(e) = history::record(started, result) {
+        eprintln!("{} could not record the clean in the history: {}", "warning:".yellow().bold(), e);
+    }
+    if method != Method::Trash {
+        return;
+    }
+    let paths = result.deleted.iter().map(|d| d.path.clone()).collect();
+    if let Err(e) = undo::record(started, paths) {
+        eprintln!("{} could not record trashed directories for undo: {}", "warning:".yellow().bold(), e);
+    }
+}
 
+/// Print what was cleaned and what failed, and how free space changed.
+/// Returns the exit status.
+fn report_clean(run: &Run, result: &CleanResult) -> i32 {
     if !result.failed.is_empty() {
-        println!("\n{}", "Failed to delete:".red());
+        println!("\n{}", tr!("failed-to-delete").red());
         for (dir, err) in &result.failed {
             println!("  {} - {}", dir.path.display(), err);
         }
     }
 
     if !result.deleted.is_empty() {
-        println!(
-            "\n{} Cleaned {} in {} directories",
-            "Done!".green().bold(),
-            format_size(result.total_cleaned()).green().bold(),
-            result.deleted.len().to_string().green()
+        info!(
+            "\n{}",
+            tr!(
+                "cleaned",
+                done = tr!("done").green().bold().to_string(),
+                size = format_size(result.total_cleaned()).green().bold().to_string(),
+                count = result.deleted.len().to_string().green().to_string(),
+            )
         );
+        disk::print_change(&run.disk_before);
+    }
+
+    if result.failed.is_empty() { 0 } else { EXIT_PARTIAL_FAILURE }
+}
+
+fn run_stats(args: Args) -> i32 {
+    let mut previous = None;
+    let found = if args.demo {
+        generate_demo_data()
+    } else {
+        let roots = scan_roots(&args);
+        previous = snapshot::load(&roots);
+        scan_with_spinner(&args, &roots).0
+    };
+    if args.by_project {
+        let mut totals = stats::by_project(&found);
+        if let Some(top) = args.top {
+            totals.truncate(top);
+        }
+        if args.format == Format::Human {
+            stats::print_by_project(&totals);
+        } else {
+            match serde_json::to_string_pretty(&totals) {
+                Ok(json) => println!("{}", json),
+                Err(e) => {
+                    eprintln!("{} {}", "error:".red().bold(), e);
+                    return EXIT_ERROR;
+                }
+            }
+        }
+        return if found.is_empty() { EXIT_NOTHING_FOUND } else { 0 };
+    }
+    if args.duplicates {
+        let spinner = spinner("Comparing node_modules packages".to_string());
+        let mut report = duplicates::analyze(&found);
+        spinner.finish_and_clear();
+        if let Some(top) = args.top {
+            report.packages.truncate(top);
+            report.projects.truncate(top);
+        }
+        if args.format == Format::Human {
+            stats::print_duplicates(&report);
+        } else {
+            match serde_json::to_string_pretty(&report) {
+                Ok(json) => println!("{}", json),
+                Err(e) => {
+                    eprintln!("{} {}", "error:".red().bold(), e);
+                    return EXIT_ERROR;
+                }
+            }
+        }
+        return if report.packages.is_empty() { EXIT_NOTHING_FOUND } else { 0 };
+    }
+    stats::print(&found);
+    if let Some(previous) = previous {
+        let now = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        stats::print_growth(&snapshot::diff(&previous, &found), now);
+    }
+    if !args.demo {
+        match history::load() {
+            Ok(cleans) => stats::print_history(&cleans),
+            Err(e) => eprintln!("{} could not read the clean history: {}", "warning:".yellow().bold(), e),
+        }
+    }
+    if found.is_empty() { EXIT_NOTHING_FOUND } else { 0 }
+}
+
+fn run_list_types() -> i32 {
+    let mut rules = projects::get_cleanable_dirs();
+    match config::load(None) {
+        Ok(config) => rules.extend(config.detectors),
+        Err(e) => eprintln!("{} invalid config file {}", "warning:".yellow().bold(), e),
+    }
+    for pt in ProjectType::all() {
+        let flags = pt.flag_names();
+        let how = match pt {
+            ProjectType::INCLUDED => "--include-dir NAME[:FILE]".to_string(),
+            ProjectType::Custom(_) if pt.preselected() => "config file detector".to_string(),
+            ProjectType::Custom(_) => "config file detector, starts unselected".to_string(),
+            ProjectType::Docker => "--docker, --global".to_string(),
+            ProjectType::Toolchain => "--toolchains, --global".to_string(),
+            _ if pt.is_global() => "--global".to_string(),
+            _ => format!("--{}, --types {}", flags[0], flags.join("|")),
+        };
+        println!("{} ({})", pt.name().bold(), how.dimmed());
+
+        match pt {
+            ProjectType::INCLUDED => {
+                println!("  NAME            any directory called NAME, or only next to FILE when given");
+            }
+            ProjectType::Docker => {
+                println!("  {:<16}  untagged images no container uses", docker::Kind::Image.name());
+                println!("  {:<16}  containers that aren't running", docker::Kind::Container.name());
+                println!("  {:<16}  build cache no build is using", docker::Kind::BuildCache.name());
+            }
+            ProjectType::Toolchain => {
+                println!("  {:<16}  toolchains other than the default, overrides and RUSTUP_TOOLCHAIN", "rustup");
+                println!("  {:<16}  Node versions other than the default, active and newest", "nvm, fnm");
+                println!("  {:<16}  Python versions other than the global, active and newest", "pyenv");
+                println!("  {:<16}  {}", "", "(each kept if run within --cache-keep)".dimmed());
+            }
+            _ if pt.is_global() => {
+                for cache in caches::known_caches().into_iter().filter(|cache| cache.project_type == pt) {
+                    let note = match cache.size_limit() {
+                        Some(limit) => format!("  (limited to {})", format_size(limit)),
+                        None if cache.by_entry() => "  (entries unused for --cache-keep)".to_string(),
+                        None => String::new(),
+                    };
+                    println!("  {:<16}  {}{}", cache.tool, cache.path.display(), note.dimmed());
+                }
+                if pt == ProjectType::GlobalCache {
+                    println!("  {:<16}  pipenv, virtualenvwrapper and Poetry environments of removed projects", "virtualenvs");
+                }
+            }
+            _ => {
+                for rule in rules.iter().filter(|r| r.project_type == pt) {
+                    println!("  {:<16}  {}", rule.dir_name, rule.rule());
+                }
+                for cache in caches::known_caches().into_iter().filter(|cache| cache.project_type == pt) {
+                    let by_entry = if cache.by_entry() { ", entries unused for --cache-keep" } else { "" };
+                    println!("  {:<16}  {}{}", cache.tool, cache.path.display(), format!("  (--global{})", by_entry).dimmed());
+                }
+            }
+        }
+        println!();
+    }
+    println!(
+        "{}",
+        "Artifacts nested inside a match are skipped, and the first rule that matches a directory wins."
+            .dimmed()
+    );
+    0
+}
+
+/// Clean what a plan lists, checking each entry again first: it must still
+/// be a directory the same rule matches, outside every exclusion. Returns the
+/// exit status.
+fn run_apply(plan_file: &std::path::Path, args: Args) -> i32 {
+    if args.path.is_some() {
+        eprintln!("{} apply cleans only what the plan lists; drop the PATH", "error:".red().bold());
+        return EXIT_ERROR;
+    }
+    let plan = match plan::load(plan_file) {
+        Ok(plan) => plan,
+        Err(e) => {
+            eprintln!("{} could not read plan {}: {}", "error:".red().bold(), plan_file.display(), e);
+            return EXIT_ERROR;
+        }
+    };
+
+    // Held until the run ends, so a scheduled apply and another run never
+    // clean the same tree at once
+    let paths: Vec<PathBuf> = plan.entries.iter().map(|entry| entry.path.clone()).collect();
+    let _locks = lock_roots(&args, &paths);
+
+    let mut options = scan_options(&args);
+    let known_caches = caches::known_caches();
+    options.global |= plan.entries.iter().any(|entry| {
+        ProjectType::all().iter().any(|pt| pt.is_global() && entry.project_type == pt.name())
+            || known_caches.iter().any(|cache| cache.holds(&entry.path))
+    });
+    let mut valid = Vec::new();
+    for entry in &plan.entries {
+        let reason = match scanner::examine(&entry.path, &options) {
+            Some(dir) if options.older_than.is_some_and(|min| dir.age().is_some_and(|age| age < min)) => {
+                "modified too recently".to_string()
+            }
+            Some(dir) if dir.project_type.name() == entry.project_type => {
+                valid.push(dir);
+                continue;
+            }
+            Some(dir) => format!("now matches {}, not {}", dir.project_type.name(), entry.project_type),
+            None if !entry.path.exists() => "already gone".to_string(),
+            None => format!("no longer a cleanable {} directory", entry.project_type),
+        };
+        eprintln!("{} skipping {}: {}", "warning:".yellow().bold(), entry.path.display(), reason);
+    }
+    if valid.is_empty() {
+        info!("{}", tr!("no-dirs-found").yellow());
+        return EXIT_NOTHING_FOUND;
+    }
+
+    let run = Run {
+        started: SystemTime::now(),
+        found: valid.clone(),
+        disk_before: Vec::new(),
+    };
+    if args.dry_run {
+        let written = output::write(args.format, &valid, &mut std::io::stdout().lock());
+        if args.format == Format::Human {
+            for dir in &valid {
+                println!("{}  {:>10}", dir.path.display(), dir.size_human());
+            }
+        }
+        return if written.is_ok() { 0 } else { EXIT_ERROR };
+    }
+    if args.format != Format::Human {
+        return clean_unattended(&args, &run, valid);
+    }
+    clean_selected(&args, &run, valid, false)
+}
+
+#[cfg(unix)]
+fn run_daemon(socket: Option<PathBuf>, args: Args) -> i32 {
+    let Some(socket) = socket.or_else(daemon::default_socket) else {
+        eprintln!("{} no runtime directory for the socket; pass --socket", "error:".red().bold());
+        return EXIT_ERROR;
+    };
+    daemon::run(&socket, scan_roots(&args), scan_options(&args), clean_method(&args))
+}
+
+#[cfg(not(unix))]
+fn run_daemon(_socket: Option<PathBuf>, _args: Args) -> i32 {
+    eprintln!("{} the daemon needs Unix domain sockets, which this platform lacks", "error:".red().bold());
+    EXIT_ERROR
+}
+
+fn run_undo() -> i32 {
+    match undo::undo() {
+        Ok(count) => {
+            println!("{} Restored {} directories from the trash", "Done!".green().bold(), count);
+            0
+        }
+        Err(e) => {
+            eprintln!("{} {}", "error:".red().bold(), e);
+            EXIT_ERROR
+        }
+    }
+}
+
+fn run_config(action: ConfigAction) -> i32 {
+    let Some(file) = config::config_file() else {
+        eprintln!("{} no config directory available", "error:".red().bold());
+        return EXIT_ERROR;
+    };
+    let result = match action {
+        ConfigAction::Show => config::show(&file),
+        ConfigAction::Edit => config::edit(&file),
+    };
+    if let Err(e) = result {
+        eprintln!("{} {}: {}", "error:".red().bold(), file.display(), e);
+        return EXIT_ERROR;
+    }
+    0
+}
+
+fn run_schedule(action: ScheduleAction) -> i32 {
+    let result = match action {
+        ScheduleAction::Install { every, clean_args } => {
+            // Catch typos now rather than when the schedule fires
+            let argv = ["safeclean", "clean"].into_iter().map(String::from).chain(clean_args.iter().cloned());
+            if let Err(e) = Cli::try_parse_from(argv) {
+                let _ = e.print();
+                return EXIT_ERROR;
+            }
+            schedule::install(every, &clean_args).map(|installed| {
+                println!("Installed {} clean: {}", format!("{:?}", every).to_lowercase(), installed);
+            })
+        }
+        ScheduleAction::Remove => schedule::remove().map(|removed| {
+            println!("{}", if removed { "Schedule removed." } else { "No schedule installed." });
+        }),
+        ScheduleAction::Status => schedule::status().map(|installed| {
+            if !installed {
+                println!("No schedule installed.");
+            }
+        }),
+    };
+    if let Err(e) = result {
+        eprintln!("{} {}", "error:".red().bold(), e);
+        return EXIT_ERROR;
+    }
+    0
+}
+
+fn run_integrate_shell(uninstall: bool) -> i32 {
+    let result = if uninstall {
+        integrate::remove().map(|removed| {
+            println!("{}", if removed { "Context-menu entry removed." } else { "No context-menu entry installed." });
+        })
+    } else {
+        integrate::install().map(|installed| println!("Installed {}", installed))
+    };
+    if let Err(e) = result {
+        eprintln!("{} {}", "error:".red().bold(), e);
+        return EXIT_ERROR;
     }
+    0
 }
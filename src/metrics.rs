@@ -0,0 +1,116 @@
+use crate::cleaner::CleanResult;
+use crate::projects::ProjectType;
+use crate::scanner::FoundDir;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How long the last scan took, for the metrics file.
+static SCAN_DURATION: Mutex<Option<Duration>> = Mutex::new(None);
+
+/// Run `scan`, remembering how long it took.
+pub fn timed<T>(scan: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = scan();
+    *SCAN_DURATION.lock().unwrap() = Some(started.elapsed());
+    result
+}
+
+/// Escape a label value for the Prometheus text format.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Add a metric's HELP and TYPE lines, then its samples.
+fn metric(out: &mut String, name: &str, help: &str, samples: &[(Option<&str>, String)]) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    for (label, value) in samples {
+        match label {
+            Some(project_type) => {
+                let _ = writeln!(out, "{}{{type=\"{}\"}} {}", name, escape(project_type), value);
+            }
+            None => {
+                let _ = writeln!(out, "{} {}", name, value);
+            }
+        }
+    }
+}
+
+/// Write what was `found` and, if a clean ran, what it removed to `file` in
+/// the node_exporter textfile collector format. The file is replaced in one
+/// step so the collector never reads half of it.
+pub fn write(file: &Path, found: &[FoundDir], cleaned: Option<&CleanResult>) -> io::Result<()> {
+    let mut types = ProjectType::all();
+    for dir in found {
+        if !types.contains(&dir.project_type) {
+            types.push(dir.project_type);
+        }
+    }
+    let per_type = |value: fn(&FoundDir) -> u64| -> Vec<(Option<&str>, String)> {
+        types
+            .iter()
+            .map(|pt| {
+                let total: u64 = found.iter().filter(|d| d.project_type == *pt).map(value).sum();
+                (Some(pt.name()), total.to_string())
+            })
+            .collect()
+    };
+
+    let mut out = String::new();
+    metric(
+        &mut out,
+        "safeclean_reclaimable_bytes",
+        "Size of the cleanable directories the last scan found.",
+        &per_type(|d| d.size_bytes),
+    );
+    metric(
+        &mut out,
+        "safeclean_reclaimable_directories",
+        "Number of cleanable directories the last scan found.",
+        &per_type(|_| 1),
+    );
+    metric(
+        &mut out,
+        "safeclean_cleaned_bytes",
+        "Space the last run freed.",
+        &[(None, cleaned.map_or(0, |r| r.total_cleaned()).to_string())],
+    );
+    metric(
+        &mut out,
+        "safeclean_cleaned_directories",
+        "Directories the last run removed.",
+        &[(None, cleaned.map_or(0, |r| r.deleted.len()).to_string())],
+    );
+    metric(
+        &mut out,
+        "safeclean_clean_failures",
+        "Directories the last run failed to remove.",
+        &[(None, cleaned.map_or(0, |r| r.failed.len()).to_string())],
+    );
+    if let Some(duration) = *SCAN_DURATION.lock().unwrap() {
+        metric(
+            &mut out,
+            "safeclean_scan_duration_seconds",
+            "How long the last scan took.",
+            &[(None, format!("{:.3}", duration.as_secs_f64()))],
+        );
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    metric(
+        &mut out,
+        "safeclean_last_run_timestamp_seconds",
+        "When the last run finished, in seconds since the Unix epoch.",
+        &[(None, now.to_string())],
+    );
+
+    let name = file.file_name().and_then(|n| n.to_str()).unwrap_or("metrics");
+    let temporary = file.with_file_name(format!(".{}.tmp", name));
+    fs::write(&temporary, out)?;
+    fs::rename(&temporary, file)
+}
@@ -0,0 +1,337 @@
+use crate::cleaner::CleanResult;
+use crate::disk::{self, MountTotal};
+use crate::format_size;
+use crate::projects::Explanation;
+use crate::rebuild::RebuildCost;
+use crate::scanner::FoundDir;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// How scan results are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Interactive selector or colored listing
+    Human,
+    /// A single JSON array
+    Json,
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line
+    Ndjson,
+    /// Aligned plain-text table
+    Table,
+    /// GitHub-flavored markdown table
+    Markdown,
+    /// Bare paths, each ended by a NUL byte, for `xargs -0`; set with
+    /// `--print0`
+    #[value(skip)]
+    Print0,
+}
+
+/// One scan result in machine-readable output.
+#[derive(Serialize)]
+pub struct Record {
+    path: String,
+    #[serde(rename = "type")]
+    project_type: &'static str,
+    size_bytes: u64,
+    /// Days since the contents were last modified, if known
+    age_days: Option<u64>,
+    /// Repository or project directory the artifact belongs to
+    project: String,
+    /// Mount point of the filesystem it's on
+    mount: Option<String>,
+    /// The rule that matched and the file that confirmed it
+    matched: Explanation,
+    /// Packages to fetch or build again, from the lockfile next to it
+    rebuild: Option<RebuildCost>,
+}
+
+impl Record {
+    pub fn new(dir: &FoundDir) -> Self {
+        Record {
+            path: dir.path.to_string_lossy().into_owned(),
+            project_type: dir.project_type.name(),
+            size_bytes: dir.size_bytes,
+            age_days: dir.age_days(),
+            project: dir.project_root.to_string_lossy().into_owned(),
+            mount: disk::mount_point(&dir.path).map(|mount| mount.to_string_lossy().into_owned()),
+            matched: dir.matched.clone(),
+            rebuild: dir.rebuild.clone(),
+        }
+    }
+}
+
+/// What happened to one directory in an unattended clean.
+#[derive(Serialize)]
+struct Outcome {
+    #[serde(flatten)]
+    record: Record,
+    /// `deleted` or `failed`
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Totals of an unattended clean, followed by every directory's outcome.
+#[derive(Serialize)]
+pub struct CleanSummary {
+    deleted: usize,
+    failed: usize,
+    freed_bytes: u64,
+    /// What was freed on each filesystem
+    mounts: Vec<MountTotal>,
+    results: Vec<Outcome>,
+}
+
+impl CleanSummary {
+    pub fn new(result: &CleanResult) -> Self {
+        CleanSummary {
+            deleted: result.deleted.len(),
+            failed: result.failed.len(),
+            freed_bytes: result.total_cleaned(),
+            mounts: disk::by_mount(&result.deleted),
+            results: outcomes(result),
+        }
+    }
+}
+
+fn outcomes(result: &CleanResult) -> Vec<Outcome> {
+    let deleted = result.deleted.iter().map(|dir| Outcome {
+        record: Record::new(dir),
+        status: "deleted",
+        error: None,
+    });
+    let failed = result.failed.iter().map(|(dir, e)| Outcome {
+        record: Record::new(dir),
+        status: "failed",
+        error: Some(e.to_string()),
+    });
+    deleted.chain(failed).collect()
+}
+
+/// Write the results as a pretty-printed JSON array.
+pub fn write_json(dirs: &[FoundDir], out: &mut impl Write) -> io::Result<()> {
+    let records: Vec<Record> = dirs.iter().map(Record::new).collect();
+    serde_json::to_writer_pretty(&mut *out, &records)?;
+    writeln!(out)
+}
+
+/// Write one JSON object per line.
+pub fn write_ndjson(dirs: &[FoundDir], out: &mut impl Write) -> io::Result<()> {
+    for dir in dirs {
+        serde_json::to_writer(&mut *out, &Record::new(dir))?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Write a header row followed by one row per result. Unknown ages are left
+/// empty.
+pub fn write_csv(dirs: &[FoundDir], out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "path,type,size_bytes,age_days,project")?;
+    for dir in dirs {
+        let record = Record::new(dir);
+        writeln!(
+            out,
+            "{},{},{},{},{}",
+            csv_field(&record.path),
+            csv_field(record.project_type),
+            record.size_bytes,
+            record.age_days.map(|days| days.to_string()).unwrap_or_default(),
+            csv_field(&record.project)
+        )?;
+    }
+    Ok(())
+}
+
+/// Escape a markdown table cell.
+fn markdown_cell(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|").replace('\n', " ")
+}
+
+/// Write `rows` under `headers` as an aligned table, or a markdown table
+/// when `markdown` is set. Columns flagged in `numeric` are right-aligned.
+fn write_table(
+    markdown: bool,
+    headers: &[&str],
+    numeric: &[bool],
+    rows: &[Vec<String>],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let rows: Vec<Vec<String>> = if markdown {
+        rows.iter().map(|row| row.iter().map(|cell| markdown_cell(cell)).collect()).collect()
+    } else {
+        rows.to_vec()
+    };
+    let widths: Vec<usize> = (0..headers.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([headers[column].len(), 3])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let line = |cells: Vec<String>| {
+        if markdown {
+            format!("| {} |", cells.join(" | "))
+        } else {
+            cells.join("  ").trim_end().to_string()
+        }
+    };
+    let pad = |column: usize, text: &str| {
+        if numeric[column] {
+            format!("{:>width$}", text, width = widths[column])
+        } else {
+            format!("{:<width$}", text, width = widths[column])
+        }
+    };
+
+    writeln!(out, "{}", line(headers.iter().enumerate().map(|(i, h)| pad(i, h)).collect()))?;
+    let rule = widths
+        .iter()
+        .zip(numeric)
+        .map(|(&width, &numeric)| match (markdown, numeric) {
+            (true, true) => format!("{}:", "-".repeat(width - 1)),
+            _ => "-".repeat(width),
+        })
+        .collect();
+    writeln!(out, "{}", line(rule))?;
+    for row in &rows {
+        writeln!(out, "{}", line(row.iter().enumerate().map(|(i, cell)| pad(i, cell)).collect()))?;
+    }
+    Ok(())
+}
+
+const TABLE_HEADERS: [&str; 5] = ["Path", "Type", "Size", "Age (days)", "Project"];
+const TABLE_NUMERIC: [bool; 5] = [false, false, true, true, false];
+
+fn table_row(dir: &FoundDir) -> Vec<String> {
+    vec![
+        dir.path.display().to_string(),
+        dir.project_type.name().to_string(),
+        format_size(dir.size_bytes),
+        dir.age_days().map(|days| days.to_string()).unwrap_or_default(),
+        dir.project_root.display().to_string(),
+    ]
+}
+
+/// Write the results as a table followed by their total.
+pub fn write_tabular(markdown: bool, dirs: &[FoundDir], out: &mut impl Write) -> io::Result<()> {
+    let rows: Vec<Vec<String>> = dirs.iter().map(table_row).collect();
+    write_table(markdown, &TABLE_HEADERS, &TABLE_NUMERIC, &rows, out)?;
+    let total = format_size(dirs.iter().map(|d| d.size_bytes).sum());
+    if markdown {
+        writeln!(out, "\n**Total:** {} in {} directories", total, dirs.len())
+    } else {
+        writeln!(out, "\nTotal: {} in {} directories", total, dirs.len())
+    }
+}
+
+/// Write each path followed by a NUL byte, keeping non-UTF-8 paths intact.
+fn write_print0<'a>(paths: impl Iterator<Item = &'a FoundDir>, out: &mut impl Write) -> io::Result<()> {
+    for dir in paths {
+        out.write_all(dir.path.as_os_str().as_encoded_bytes())?;
+        out.write_all(b"\0")?;
+    }
+    Ok(())
+}
+
+/// Write the results in a machine-readable `format`. Nothing is written for
+/// [`Format::Human`].
+pub fn write(format: Format, dirs: &[FoundDir], out: &mut impl Write) -> io::Result<()> {
+    match format {
+        Format::Human => Ok(()),
+        Format::Json => write_json(dirs, out),
+        Format::Csv => write_csv(dirs, out),
+        Format::Ndjson => write_ndjson(dirs, out),
+        Format::Table => write_tabular(false, dirs, out),
+        Format::Markdown => write_tabular(true, dirs, out),
+        Format::Print0 => write_print0(dirs.iter(), out),
+    }
+}
+
+/// Describe a finished clean in a machine-readable `format`: a JSON object
+/// with totals, one row/line per directory with its status for CSV and
+/// NDJSON, a table with totals below it, or just the deleted paths for
+/// [`Format::Print0`]. Nothing is written for [`Format::Human`].
+pub fn write_clean(format: Format, result: &CleanResult, out: &mut impl Write) -> io::Result<()> {
+    match format {
+        Format::Human => Ok(()),
+        Format::Print0 => write_print0(result.deleted.iter(), out),
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, &CleanSummary::new(result))?;
+            writeln!(out)
+        }
+        Format::Ndjson => {
+            for outcome in outcomes(result) {
+                serde_json::to_writer(&mut *out, &outcome)?;
+                writeln!(out)?;
+            }
+            Ok(())
+        }
+        Format::Csv => {
+            writeln!(out, "path,type,size_bytes,age_days,project,status,error")?;
+            for outcome in outcomes(result) {
+                let record = &outcome.record;
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{}",
+                    csv_field(&record.path),
+                    csv_field(record.project_type),
+                    record.size_bytes,
+                    record.age_days.map(|days| days.to_string()).unwrap_or_default(),
+                    csv_field(&record.project),
+                    outcome.status,
+                    csv_field(outcome.error.as_deref().unwrap_or_default())
+                )?;
+            }
+            Ok(())
+        }
+        Format::Table | Format::Markdown => {
+            let markdown = format == Format::Markdown;
+            let mut headers = TABLE_HEADERS.to_vec();
+            headers.push("Status");
+            let mut numeric = TABLE_NUMERIC.to_vec();
+            numeric.push(false);
+            let with_status = |dir, status: String| {
+                let mut row = table_row(dir);
+                row.push(status);
+                row
+            };
+            let rows: Vec<Vec<String>> = result
+                .deleted
+                .iter()
+                .map(|dir| with_status(dir, "deleted".to_string()))
+                .chain(result.failed.iter().map(|(dir, e)| with_status(dir, format!("failed: {}", e))))
+                .collect();
+            write_table(markdown, &headers, &numeric, &rows, out)?;
+
+            let mut total = format!(
+                "{} in {} directories",
+                format_size(result.total_cleaned()),
+                result.deleted.len()
+            );
+            if !result.failed.is_empty() {
+                total.push_str(&format!(", {} failed", result.failed.len()));
+            }
+            if markdown {
+                writeln!(out, "\n**Cleaned:** {}", total)
+            } else {
+                writeln!(out, "\nCleaned: {}", total)
+            }
+        }
+    }
+}
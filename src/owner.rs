@@ -0,0 +1,28 @@
+use std::path::Path;
+
+/// Whether `path` belongs to a user other than the one running safeclean.
+#[cfg(unix)]
+pub fn is_foreign(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    // SAFETY: geteuid has no preconditions and can't fail
+    let user = unsafe { libc::geteuid() };
+    path.symlink_metadata().is_ok_and(|meta| meta.uid() != user)
+}
+
+/// Ownership isn't checked where files don't have a single numeric owner.
+#[cfg(not(unix))]
+pub fn is_foreign(_path: &Path) -> bool {
+    false
+}
+
+/// Whether this process may remove other users' files, i.e. runs as root.
+#[cfg(unix)]
+pub fn privileged() -> bool {
+    // SAFETY: as above
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(not(unix))]
+pub fn privileged() -> bool {
+    true
+}
@@ -0,0 +1,65 @@
+use crate::scanner::FoundDir;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bumped when the file layout changes incompatibly.
+const VERSION: u32 = 1;
+
+/// A directory picked for a later clean, as it was when picked.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub path: PathBuf,
+    #[serde(rename = "type")]
+    pub project_type: String,
+    pub size_bytes: u64,
+}
+
+/// Directories chosen by `scan --plan`, to be cleaned by `apply`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Plan {
+    pub version: u32,
+    /// Seconds since the Unix epoch when the plan was saved
+    pub created: i64,
+    pub entries: Vec<Entry>,
+}
+
+/// Write `dirs` to `file` as a plan.
+pub fn save(file: &Path, dirs: &[FoundDir]) -> io::Result<()> {
+    fs::write(file, to_json(dirs)?)
+}
+
+/// `dirs` as the contents of a plan file.
+pub fn to_json(dirs: &[FoundDir]) -> io::Result<String> {
+    let plan = Plan {
+        version: VERSION,
+        created: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64),
+        entries: dirs
+            .iter()
+            .map(|d| Entry {
+                path: d.path.clone(),
+                project_type: d.project_type.name().to_string(),
+                size_bytes: d.size_bytes,
+            })
+            .collect(),
+    };
+    let mut json = serde_json::to_string_pretty(&plan).map_err(io::Error::other)?;
+    json.push('\n');
+    Ok(json)
+}
+
+pub fn load(file: &Path) -> io::Result<Plan> {
+    let contents = fs::read_to_string(file)?;
+    let plan: Plan = serde_json::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if plan.version != VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("plan version {} is not supported (expected {})", plan.version, VERSION),
+        ));
+    }
+    Ok(plan)
+}
@@ -0,0 +1,152 @@
+use crate::cleaner::{self, CleanProgress, CleanResult, Method};
+use crate::projects::Explanation;
+use crate::scanner::{self, FoundDir, ScanEvent, ScanOptions, Skipped};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+/// How progress is reported to a wrapping program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Progress {
+    /// One JSON object per line on stdout
+    Json,
+}
+
+/// A progress event, written as a JSON object tagged with `event`.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+enum Event<'a> {
+    ScanStarted {
+        roots: &'a [PathBuf],
+    },
+    DirFound {
+        path: &'a Path,
+        #[serde(rename = "type")]
+        project_type: &'static str,
+        matched: &'a Explanation,
+        /// A per-user cache rather than part of a project
+        global: bool,
+    },
+    SizingProgress {
+        path: &'a Path,
+        size_bytes: u64,
+        age_days: Option<u64>,
+        project: &'a Path,
+        sized: usize,
+        found: usize,
+    },
+    /// Measured and found short of the size or age thresholds, so no longer
+    /// offered
+    DirSkipped {
+        path: &'a Path,
+    },
+    DeleteProgress {
+        path: &'a Path,
+        /// `started`, `deleted` or `failed`
+        status: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        done: usize,
+        total: usize,
+    },
+    Done {
+        found: usize,
+        size_bytes: u64,
+        deleted: usize,
+        failed: usize,
+        freed_bytes: u64,
+    },
+}
+
+/// Write one event per line. A reader that went away isn't our problem, so
+/// write errors are dropped.
+fn emit(event: Event) {
+    let Ok(line) = serde_json::to_string(&event) else {
+        return;
+    };
+    let mut out = io::stdout().lock();
+    let _ = writeln!(out, "{}", line);
+    let _ = out.flush();
+}
+
+/// Scan `roots`, reporting each directory as it is found and sized. Returns
+/// what was found, largest first, and what the scan passed over.
+pub fn scan(roots: &[PathBuf], options: ScanOptions) -> (Vec<FoundDir>, Skipped) {
+    emit(Event::ScanStarted { roots });
+
+    let (events, receiver) = mpsc::channel();
+    let scan_roots = roots.to_vec();
+    thread::spawn(move || scanner::scan_streaming(&scan_roots, &options, events));
+
+    let mut found = 0;
+    let mut sized = Vec::new();
+    let mut skipped = Skipped::default();
+    for event in receiver {
+        match event {
+            ScanEvent::Found(dir) => {
+                found += 1;
+                emit(Event::DirFound {
+                    path: &dir.path,
+                    project_type: dir.project_type.name(),
+                    matched: &dir.matched,
+                    global: dir.global,
+                });
+            }
+            ScanEvent::Sized(dir) => {
+                emit(Event::SizingProgress {
+                    path: &dir.path,
+                    size_bytes: dir.size_bytes,
+                    age_days: dir.age_days(),
+                    project: &dir.project_root,
+                    sized: sized.len() + 1,
+                    found,
+                });
+                sized.push(dir);
+            }
+            ScanEvent::Unwanted(dir) => {
+                found -= 1;
+                emit(Event::DirSkipped { path: &dir.path });
+            }
+            ScanEvent::Done(passed_over) => {
+                skipped = passed_over;
+                break;
+            }
+        }
+    }
+    sized.sort_by_key(|d| std::cmp::Reverse(d.size_bytes));
+    (sized, skipped)
+}
+
+/// Remove `dirs`, reporting each one as it starts and finishes.
+pub fn clean(dirs: Vec<FoundDir>, method: Method) -> CleanResult {
+    let total = dirs.len();
+    let mut done = 0;
+    cleaner::clean_with_progress(dirs, method, |progress| {
+        let (path, status, error) = match progress {
+            CleanProgress::Started(path) => (path, "started", None),
+            CleanProgress::Deleted(path) => {
+                done += 1;
+                (path, "deleted", None)
+            }
+            CleanProgress::Failed(path, e) => {
+                done += 1;
+                (path, "failed", Some(e.to_string()))
+            }
+        };
+        emit(Event::DeleteProgress { path, status, error, done, total });
+    })
+}
+
+/// Report the totals: what was found, and what a clean removed if one ran.
+pub fn done(found: &[FoundDir], result: Option<&CleanResult>) {
+    emit(Event::Done {
+        found: found.len(),
+        size_bytes: scanner::total_size(found),
+        deleted: result.map_or(0, |r| r.deleted.len()),
+        failed: result.map_or(0, |r| r.failed.len()),
+        freed_bytes: result.map_or(0, CleanResult::total_cleaned),
+    });
+}
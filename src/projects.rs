@@ -1,4 +1,11 @@
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Types created by config file detectors, with whether the selector starts
+/// with their directories selected.
+static CUSTOM_TYPES: Mutex<Vec<(&'static str, bool)>> = Mutex::new(Vec::new());
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ProjectType {
@@ -10,9 +17,61 @@ pub enum ProjectType {
     DotNet,
     NextJs,
     NuxtJs,
+    /// Directories added with `--include-dir` ([`ProjectType::INCLUDED`]) or
+    /// by a config file detector, named by its label
+    Custom(&'static str),
+    /// Per-user tool caches, scanned with `--global`
+    GlobalCache,
+    /// Go's module cache, also scanned with `--global`. Go leaves it read-only,
+    /// so it is made writable before it is removed
+    GoModules,
+    /// Old compilers and runtimes installed by rustup, nvm, fnm and pyenv;
+    /// scanned with `--toolchains` or `--global`
+    Toolchain,
+    /// Unused images, containers and build cache, removed through the Docker
+    /// daemon; scanned with `--docker` or `--global`
+    Docker,
 }
 
 impl ProjectType {
+    /// The type of `--include-dir` rules.
+    pub const INCLUDED: ProjectType = ProjectType::Custom("Custom");
+
+    /// The type of directories matched by detectors labelled `label`,
+    /// registering it on first use so that [`ProjectType::all`] lists it.
+    /// The last `selected` given for a label wins.
+    pub fn custom(label: &str, selected: bool) -> ProjectType {
+        let mut types = CUSTOM_TYPES.lock().unwrap();
+        if let Some(entry) = types.iter_mut().find(|(name, _)| *name == label) {
+            entry.1 = selected;
+            return ProjectType::Custom(entry.0);
+        }
+        let name: &'static str = Box::leak(label.to_string().into_boxed_str());
+        types.push((name, selected));
+        ProjectType::Custom(name)
+    }
+
+    /// Whether directories of this type are per-user rather than inside a
+    /// project, scanned with `--global`.
+    pub fn is_global(&self) -> bool {
+        matches!(self, ProjectType::GlobalCache | ProjectType::GoModules | ProjectType::Toolchain | ProjectType::Docker)
+    }
+
+    /// Whether the selector starts with directories of this type selected.
+    /// Caches are shared by every project, so they're only cleaned on request.
+    pub fn preselected(&self) -> bool {
+        match self {
+            ProjectType::GlobalCache | ProjectType::GoModules | ProjectType::Toolchain | ProjectType::Docker => false,
+            ProjectType::Custom(label) => CUSTOM_TYPES
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(name, _)| name == label)
+                .is_none_or(|(_, selected)| *selected),
+            _ => true,
+        }
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             ProjectType::Rust => "Rust",
@@ -23,11 +82,56 @@ impl ProjectType {
             ProjectType::DotNet => ".NET",
             ProjectType::NextJs => "Next.js",
             ProjectType::NuxtJs => "Nuxt.js",
+            ProjectType::Custom(label) => label,
+            ProjectType::GlobalCache => "Global caches",
+            ProjectType::GoModules => "Go modules",
+            ProjectType::Toolchain => "Toolchains",
+            ProjectType::Docker => "Docker",
+        }
+    }
+
+    /// Names accepted by `--types`, the first being the type's own flag.
+    /// Types that aren't chosen that way have none.
+    pub fn flag_names(&self) -> &'static [&'static str] {
+        match self {
+            ProjectType::Rust => &["rust"],
+            ProjectType::Node => &["node", "js", "npm"],
+            ProjectType::Python => &["python", "py"],
+            ProjectType::JavaMaven => &["java", "maven"],
+            ProjectType::Gradle => &["gradle"],
+            ProjectType::DotNet => &["dotnet", "csharp"],
+            ProjectType::NextJs => &["next"],
+            ProjectType::NuxtJs => &["nuxt"],
+            ProjectType::Custom(_)
+            | ProjectType::GlobalCache
+            | ProjectType::GoModules
+            | ProjectType::Toolchain
+            | ProjectType::Docker => &[],
         }
     }
 
+    /// Look up a type by its command-line flag name or alias (`rust`,
+    /// `node`, `js`, ...), ignoring case.
+    pub fn from_flag(name: &str) -> Option<ProjectType> {
+        let name = name.to_ascii_lowercase();
+        ProjectType::all()
+            .into_iter()
+            .find(|pt| pt.flag_names().contains(&name.as_str()))
+    }
+
+    /// Parse a `--types` entry, listing the valid names on failure.
+    pub fn parse_flag(name: &str) -> Result<ProjectType, String> {
+        ProjectType::from_flag(name.trim()).ok_or_else(|| {
+            format!(
+                "unknown project type '{}' (expected one of: rust, node, python, java, gradle, dotnet, next, nuxt; aliases: js, npm, py, maven, csharp)",
+                name.trim()
+            )
+        })
+    }
+
+    /// Every type: the built-in ones, then custom ones, then the global ones.
     pub fn all() -> Vec<ProjectType> {
-        vec![
+        let mut all = vec![
             ProjectType::Rust,
             ProjectType::Node,
             ProjectType::Python,
@@ -36,161 +140,319 @@ impl ProjectType {
             ProjectType::DotNet,
             ProjectType::NextJs,
             ProjectType::NuxtJs,
-        ]
+            ProjectType::INCLUDED,
+        ];
+        for (name, _) in CUSTOM_TYPES.lock().unwrap().iter() {
+            if !all.contains(&ProjectType::Custom(name)) {
+                all.push(ProjectType::Custom(name));
+            }
+        }
+        all.push(ProjectType::GlobalCache);
+        all.push(ProjectType::GoModules);
+        all.push(ProjectType::Toolchain);
+        all.push(ProjectType::Docker);
+        all
     }
+
+    /// Types created by config file detectors.
+    pub fn detector_types() -> Vec<ProjectType> {
+        CUSTOM_TYPES
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, _)| ProjectType::Custom(name))
+            .collect()
+    }
+}
+
+/// Decides whether a directory with a matching name really is an artifact.
+#[derive(Debug, Clone)]
+pub enum Validator {
+    /// The name alone is enough
+    Name,
+    /// Built-in check returning the file that confirms the match, with a
+    /// description of what it looks for
+    Check(fn(&Path) -> Option<PathBuf>, &'static str),
+    /// A file with this name must sit next to the directory
+    Sibling(String),
+    /// A file with this name must be inside the directory
+    Contains(String),
+}
+
+/// Why a directory was matched, so surprising matches can be understood.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Explanation {
+    /// Directory name the rule looks for, e.g. `target` or `bazel-*`
+    pub pattern: String,
+    /// What else the rule requires, as `list-types` shows it
+    pub rule: String,
+    /// The file that satisfied the rule, if it needs one
+    pub marker: Option<PathBuf>,
 }
 
+impl Explanation {
+    /// One line for people, e.g. "target next to Cargo.toml (found /p/Cargo.toml)".
+    pub fn describe(&self) -> String {
+        match &self.marker {
+            Some(marker) => format!("{} {} (found {})", self.pattern, self.rule, marker.display()),
+            None => format!("{}: {}", self.pattern, self.rule),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct CleanableDir {
-    pub dir_name: &'static str,
+    /// Directory name; `*` and `?` wildcards are allowed
+    pub dir_name: Cow<'static, str>,
     pub project_type: ProjectType,
-    pub validator: fn(&Path) -> bool,
+    pub validator: Validator,
 }
 
-fn has_sibling(path: &Path, filename: &str) -> bool {
-    path.parent()
-        .map(|p| p.join(filename).exists())
-        .unwrap_or(false)
+impl CleanableDir {
+    /// What must hold for a directory with this name to be cleaned.
+    pub fn rule(&self) -> String {
+        match &self.validator {
+            Validator::Name => ALWAYS.to_string(),
+            Validator::Check(_, rule) => rule.to_string(),
+            Validator::Sibling(filename) => format!("next to {}", filename),
+            Validator::Contains(filename) => format!("containing {}", filename),
+        }
+    }
+
+    /// Whether a directory called `name` is a candidate for this rule.
+    pub fn matches_name(&self, name: &str) -> bool {
+        wildcard_match(&self.dir_name, name)
+    }
+
+    pub fn is_valid(&self, path: &Path) -> bool {
+        self.explain(path).is_some()
+    }
+
+    /// Why the directory at `path` matches this rule, or `None` if it fails
+    /// the rule's check.
+    pub fn explain(&self, path: &Path) -> Option<Explanation> {
+        let marker = match &self.validator {
+            Validator::Name => None,
+            Validator::Check(check, _) => Some(check(path)?),
+            Validator::Sibling(filename) => Some(sibling(path, filename)?),
+            Validator::Contains(filename) => Some(path.join(filename)).filter(|file| file.exists()),
+        };
+        if marker.is_none() && !matches!(self.validator, Validator::Name) {
+            return None;
+        }
+        Some(Explanation {
+            pattern: self.dir_name.to_string(),
+            rule: self.rule(),
+            marker,
+        })
+    }
+
+    /// A config file detector: directories matching `pattern`, shown under
+    /// `label`, optionally only next to `sibling` or containing `contains`.
+    pub fn detector(
+        label: &str,
+        pattern: &str,
+        sibling: Option<String>,
+        contains: Option<String>,
+        selected: bool,
+    ) -> Result<CleanableDir, String> {
+        check_dir_name(pattern)?;
+        if label.trim().is_empty() {
+            return Err(format!("detector for '{}' needs a non-empty label", pattern));
+        }
+        let validator = match (sibling, contains) {
+            (Some(_), Some(_)) => {
+                return Err(format!("detector for '{}' can't have both sibling and contains", pattern));
+            }
+            (Some(sibling), None) => Validator::Sibling(sibling),
+            (None, Some(contains)) => Validator::Contains(contains),
+            (None, None) => Validator::Name,
+        };
+        Ok(CleanableDir {
+            dir_name: Cow::Owned(pattern.to_string()),
+            project_type: ProjectType::custom(label.trim(), selected),
+            validator,
+        })
+    }
+
+    /// Parse an `--include-dir` rule: `NAME` alone, or `NAME:FILE` to only
+    /// match when `FILE` sits next to the directory.
+    pub fn parse_custom(rule: &str) -> Result<CleanableDir, String> {
+        let (name, sibling) = match rule.split_once(':') {
+            Some((name, sibling)) => (name, Some(sibling)),
+            None => (rule, None),
+        };
+        check_dir_name(name)?;
+        let validator = match sibling {
+            Some("") => return Err(format!("missing file name after ':' in '{}'", rule)),
+            Some(sibling) => Validator::Sibling(sibling.to_string()),
+            None => Validator::Name,
+        };
+        Ok(CleanableDir {
+            dir_name: Cow::Owned(name.to_string()),
+            project_type: ProjectType::INCLUDED,
+            validator,
+        })
+    }
+}
+
+fn check_dir_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+        return Err(format!("'{}' is not a directory name", name));
+    }
+    Ok(())
+}
+
+/// Match `name` against a pattern where `*` matches any run of characters
+/// and `?` any single one.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let mut pattern = pattern.chars();
+    let mut rest = name.chars();
+    match pattern.next() {
+        None => name.is_empty(),
+        Some('*') => name
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([name.len()])
+            .any(|i| wildcard_match(pattern.as_str(), &name[i..])),
+        Some('?') => rest.next().is_some() && wildcard_match(pattern.as_str(), rest.as_str()),
+        Some(c) => rest.next() == Some(c) && wildcard_match(pattern.as_str(), rest.as_str()),
+    }
 }
 
-fn has_sibling_matching(path: &Path, pattern: &str) -> bool {
-    let Some(parent) = path.parent() else {
-        return false;
-    };
-    let Ok(entries) = std::fs::read_dir(parent) else {
-        return false;
-    };
-    entries.filter_map(|e| e.ok()).any(|entry| {
-        entry
-            .file_name()
-            .to_str()
-            .map(|name| name.starts_with(pattern))
-            .unwrap_or(false)
-    })
+fn sibling(path: &Path, filename: &str) -> Option<PathBuf> {
+    Some(path.parent()?.join(filename)).filter(|file| file.exists())
 }
 
-fn always_valid(_: &Path) -> bool {
-    true
+fn sibling_matching(path: &Path, pattern: &str) -> Option<PathBuf> {
+    std::fs::read_dir(path.parent()?)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .find(|entry| entry.file_name().to_str().is_some_and(|name| name.starts_with(pattern)))
+        .map(|entry| entry.path())
 }
 
-fn validate_rust(path: &Path) -> bool {
-    has_sibling(path, "Cargo.toml")
+const ALWAYS: &str = "any directory with this name";
+
+fn validate_rust(path: &Path) -> Option<PathBuf> {
+    sibling(path, "Cargo.toml")
 }
 
-fn validate_node(path: &Path) -> bool {
-    has_sibling(path, "package.json")
+fn validate_node(path: &Path) -> Option<PathBuf> {
+    sibling(path, "package.json")
 }
 
-fn validate_maven(path: &Path) -> bool {
-    has_sibling(path, "pom.xml")
+fn validate_maven(path: &Path) -> Option<PathBuf> {
+    sibling(path, "pom.xml")
 }
 
-fn validate_gradle(path: &Path) -> bool {
-    has_sibling(path, "build.gradle") || has_sibling(path, "build.gradle.kts")
+fn validate_gradle(path: &Path) -> Option<PathBuf> {
+    sibling(path, "build.gradle").or_else(|| sibling(path, "build.gradle.kts"))
 }
 
-fn validate_dotnet(path: &Path) -> bool {
-    has_sibling_matching(path, ".csproj")
-        || has_sibling_matching(path, ".fsproj")
-        || has_sibling_matching(path, ".sln")
+fn validate_dotnet(path: &Path) -> Option<PathBuf> {
+    sibling_matching(path, ".csproj")
+        .or_else(|| sibling_matching(path, ".fsproj"))
+        .or_else(|| sibling_matching(path, ".sln"))
 }
 
-fn validate_nextjs(path: &Path) -> bool {
-    has_sibling_matching(path, "next.config")
+fn validate_nextjs(path: &Path) -> Option<PathBuf> {
+    sibling_matching(path, "next.config")
 }
 
-fn validate_nuxtjs(path: &Path) -> bool {
-    has_sibling_matching(path, "nuxt.config")
+fn validate_nuxtjs(path: &Path) -> Option<PathBuf> {
+    sibling_matching(path, "nuxt.config")
 }
 
 pub fn get_cleanable_dirs() -> Vec<CleanableDir> {
     vec![
         // Rust
         CleanableDir {
-            dir_name: "target",
+            dir_name: Cow::Borrowed("target"),
             project_type: ProjectType::Rust,
-            validator: validate_rust,
+            validator: Validator::Check(validate_rust, "next to Cargo.toml"),
         },
         // Node.js
         CleanableDir {
-            dir_name: "node_modules",
+            dir_name: Cow::Borrowed("node_modules"),
             project_type: ProjectType::Node,
-            validator: validate_node,
+            validator: Validator::Check(validate_node, "next to package.json"),
         },
         // Python
         CleanableDir {
-            dir_name: ".venv",
+            dir_name: Cow::Borrowed(".venv"),
             project_type: ProjectType::Python,
-            validator: always_valid,
+            validator: Validator::Name,
         },
         CleanableDir {
-            dir_name: "venv",
+            dir_name: Cow::Borrowed("venv"),
             project_type: ProjectType::Python,
-            validator: always_valid,
+            validator: Validator::Name,
         },
         CleanableDir {
-            dir_name: "__pycache__",
+            dir_name: Cow::Borrowed("__pycache__"),
             project_type: ProjectType::Python,
-            validator: always_valid,
+            validator: Validator::Name,
         },
         CleanableDir {
-            dir_name: ".pytest_cache",
+            dir_name: Cow::Borrowed(".pytest_cache"),
             project_type: ProjectType::Python,
-            validator: always_valid,
+            validator: Validator::Name,
         },
         CleanableDir {
-            dir_name: ".mypy_cache",
+            dir_name: Cow::Borrowed(".mypy_cache"),
             project_type: ProjectType::Python,
-            validator: always_valid,
+            validator: Validator::Name,
         },
         CleanableDir {
-            dir_name: ".ruff_cache",
+            dir_name: Cow::Borrowed(".ruff_cache"),
             project_type: ProjectType::Python,
-            validator: always_valid,
+            validator: Validator::Name,
         },
         CleanableDir {
-            dir_name: ".tox",
+            dir_name: Cow::Borrowed(".tox"),
             project_type: ProjectType::Python,
-            validator: always_valid,
+            validator: Validator::Name,
         },
         // Java (Maven)
         CleanableDir {
-            dir_name: "target",
+            dir_name: Cow::Borrowed("target"),
             project_type: ProjectType::JavaMaven,
-            validator: validate_maven,
+            validator: Validator::Check(validate_maven, "next to pom.xml"),
         },
         // Gradle
         CleanableDir {
-            dir_name: "build",
+            dir_name: Cow::Borrowed("build"),
             project_type: ProjectType::Gradle,
-            validator: validate_gradle,
+            validator: Validator::Check(validate_gradle, "next to build.gradle or build.gradle.kts"),
         },
         CleanableDir {
-            dir_name: ".gradle",
+            dir_name: Cow::Borrowed(".gradle"),
             project_type: ProjectType::Gradle,
-            validator: validate_gradle,
+            validator: Validator::Check(validate_gradle, "next to build.gradle or build.gradle.kts"),
         },
         // .NET
         CleanableDir {
-            dir_name: "bin",
+            dir_name: Cow::Borrowed("bin"),
             project_type: ProjectType::DotNet,
-            validator: validate_dotnet,
+            validator: Validator::Check(validate_dotnet, "next to a file whose name starts with .csproj, .fsproj or .sln"),
         },
         CleanableDir {
-            dir_name: "obj",
+            dir_name: Cow::Borrowed("obj"),
             project_type: ProjectType::DotNet,
-            validator: validate_dotnet,
+            validator: Validator::Check(validate_dotnet, "next to a file whose name starts with .csproj, .fsproj or .sln"),
         },
         // Next.js
         CleanableDir {
-            dir_name: ".next",
+            dir_name: Cow::Borrowed(".next"),
             project_type: ProjectType::NextJs,
-            validator: validate_nextjs,
+            validator: Validator::Check(validate_nextjs, "next to a file whose name starts with next.config"),
         },
         // Nuxt.js
         CleanableDir {
-            dir_name: ".nuxt",
+            dir_name: Cow::Borrowed(".nuxt"),
             project_type: ProjectType::NuxtJs,
-            validator: validate_nuxtjs,
+            validator: Validator::Check(validate_nuxtjs, "next to a file whose name starts with nuxt.config"),
         },
     ]
 }
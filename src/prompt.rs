@@ -0,0 +1,142 @@
+use crate::format_size;
+use crate::i18n::tr;
+use crate::scanner::FoundDir;
+use crate::selector::{CONFIRM_WORD, Selection, SelectorOptions};
+use colored::Colorize;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+/// Parse a line of item numbers and ranges ("1 3-5, 8") into zero-based
+/// indexes below `len`.
+fn parse_numbers(line: &str, len: usize) -> Result<Vec<usize>, String> {
+    let mut indexes = Vec::new();
+    for word in line.split([' ', ',']).filter(|w| !w.is_empty()) {
+        let (first, last) = word.split_once('-').unwrap_or((word, word));
+        let (Ok(first), Ok(last)) = (first.parse::<usize>(), last.parse::<usize>()) else {
+            return Err(format!("'{}' is not a number or range", word));
+        };
+        if first == 0 || last > len || first > last {
+            return Err(format!("'{}' is outside 1-{}", word, len));
+        }
+        indexes.extend(first - 1..last);
+    }
+    Ok(indexes)
+}
+
+/// Why a directory deserves a second look before it goes, if it does.
+pub fn warnings(dir: &FoundDir) -> String {
+    let mut warnings = String::new();
+    if dir.ignored == Some(false) {
+        warnings.push_str("  not gitignored");
+    }
+    if let Some(editor) = dir.editor {
+        warnings.push_str(&format!("  open in {}", editor));
+    }
+    if let Some(container) = &dir.container {
+        warnings.push_str(&format!("  mounted into container {}", container));
+    }
+    if let Some(file) = &dir.suspicious {
+        let file = file.strip_prefix(&dir.path).unwrap_or(file);
+        warnings.push_str(&format!("  holds {}", file.display()));
+    }
+    warnings
+}
+
+fn print_items(found: &[FoundDir], selected: &[bool]) {
+    let width = found.len().to_string().len();
+    for (index, (dir, selected)) in found.iter().zip(selected).enumerate() {
+        eprintln!(
+            "  [{}] {:>width$}  {:>10}  {}  {}{}",
+            if *selected { "x" } else { " " },
+            index + 1,
+            dir.size_human(),
+            dir.path.display(),
+            dir.project_type.name().dimmed(),
+            warnings(dir).red().bold(),
+            width = width
+        );
+    }
+}
+
+/// Line-based stand-in for the full-screen selector, for when stderr isn't a
+/// terminal: lists the directories numbered on stderr and reads which to
+/// deselect from stdin. End of input cancels.
+pub fn select(found: Vec<FoundDir>, options: &SelectorOptions) -> io::Result<Selection> {
+    let mut selected: Vec<bool> = found
+        .iter()
+        .map(|dir| match &options.preselected {
+            Some(paths) => paths.contains(&dir.path),
+            None => options.preselect && dir.preselected(),
+        })
+        .collect();
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    print_items(&found, &selected);
+    loop {
+        let size: u64 = found.iter().zip(&selected).filter(|(_, s)| **s).map(|(d, _)| d.size_bytes).sum();
+        let count = selected.iter().filter(|s| **s).count();
+        let suspicious: Vec<&PathBuf> = found
+            .iter()
+            .zip(&selected)
+            .filter(|(_, s)| **s)
+            .filter_map(|(d, _)| d.suspicious.as_ref())
+            .collect();
+        // Large or unusual deletions take typing a word, not just Enter
+        let needs_word = options.ask_word && (options.large.exceeded(count, size) || !suspicious.is_empty());
+        eprintln!("{}", tr!("selector-summary", count = count, size = format_size(size)));
+        eprint!("{}", tr!("prompt-line"));
+        io::stderr().flush()?;
+
+        let Some(line) = lines.next().transpose()? else {
+            eprintln!();
+            return Ok(Selection::Cancelled);
+        };
+        match line.trim() {
+            "" | "y" | "yes" if !needs_word => break,
+            "" | "y" | "yes" => {
+                if options.large.exceeded(count, size) {
+                    eprintln!("{}", tr!("confirm-large"));
+                }
+                for file in &suspicious {
+                    eprintln!(
+                        "{} {} doesn't look like build output",
+                        "warning:".yellow().bold(),
+                        file.display()
+                    );
+                }
+                eprint!("{}", tr!("prompt-type-word", word = CONFIRM_WORD));
+                io::stderr().flush()?;
+                match lines.next().transpose()? {
+                    Some(word) if word.trim() == CONFIRM_WORD => break,
+                    Some(_) => continue,
+                    None => {
+                        eprintln!();
+                        return Ok(Selection::Cancelled);
+                    }
+                }
+            }
+            "q" | "quit" => return Ok(Selection::Cancelled),
+            "a" | "all" => selected.iter_mut().for_each(|s| *s = true),
+            "n" | "none" => selected.iter_mut().for_each(|s| *s = false),
+            "l" | "list" => print_items(&found, &selected),
+            numbers => match parse_numbers(numbers, found.len()) {
+                Ok(indexes) => {
+                    for index in indexes {
+                        selected[index] = !selected[index];
+                    }
+                }
+                Err(e) => eprintln!("{} {}", "error:".red().bold(), e),
+            },
+        }
+    }
+
+    Ok(Selection::Pending(
+        found
+            .into_iter()
+            .zip(selected)
+            .filter(|(_, selected)| *selected)
+            .map(|(dir, _)| dir)
+            .collect(),
+    ))
+}
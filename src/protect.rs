@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// System directories nothing is ever deleted under.
+const SYSTEM_TREES: &[&str] = if cfg!(windows) {
+    &["C:\\Windows", "C:\\Program Files", "C:\\Program Files (x86)", "C:\\ProgramData"]
+} else if cfg!(target_os = "macos") {
+    &["/System", "/Library", "/Applications", "/bin", "/sbin", "/usr", "/etc", "/private/etc", "/dev"]
+} else {
+    &["/bin", "/boot", "/dev", "/etc", "/lib", "/lib32", "/lib64", "/proc", "/sbin", "/sys", "/usr"]
+};
+
+/// Trees added with [`add`], e.g. from the config file.
+static USER_TREES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Never delete anything under `paths` either, for the rest of the process.
+pub fn add(paths: impl IntoIterator<Item = PathBuf>) {
+    let canonical = paths.into_iter().map(|path| path.canonicalize().unwrap_or(path));
+    USER_TREES.lock().unwrap().extend(canonical);
+}
+
+/// Why `path` must not be deleted, if it mustn't: it is a filesystem root,
+/// the home directory or the current directory (or holds it), or lies under
+/// a system or user-protected tree.
+pub fn check(path: &Path) -> Option<String> {
+    // A symlink is only unlinked, so where it sits counts, not what it
+    // points at
+    let path = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if path.symlink_metadata().is_ok_and(|meta| meta.file_type().is_symlink()) => {
+            parent.canonicalize().unwrap_or_else(|_| parent.to_path_buf()).join(name)
+        }
+        _ => path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+    };
+    if path.parent().is_none() {
+        return Some("it is a filesystem root".to_string());
+    }
+    if let Ok(cwd) = std::env::current_dir()
+        && cwd.canonicalize().unwrap_or(cwd).starts_with(&path)
+    {
+        return Some("it holds the current directory; run safeclean from outside it".to_string());
+    }
+    if let Some(home) = dirs::home_dir()
+        && path == home.canonicalize().unwrap_or(home)
+    {
+        return Some("it is the home directory".to_string());
+    }
+    if let Some(tree) = SYSTEM_TREES.iter().map(Path::new).find(|tree| path.starts_with(tree)) {
+        return Some(format!("{} is a system directory", tree.display()));
+    }
+    USER_TREES
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|tree| path.starts_with(tree))
+        .map(|tree| format!("{} is protected", tree.display()))
+}
@@ -0,0 +1,124 @@
+use crate::projects::ProjectType;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What regenerating an artifact would take, going by the lockfile next to
+/// it.
+#[derive(Debug, Clone, Serialize)]
+pub struct RebuildCost {
+    /// Packages the lockfile pins, each to be fetched (and for Rust,
+    /// compiled) again
+    pub packages: usize,
+    /// The lockfile they were counted in
+    pub lockfile: PathBuf,
+}
+
+impl RebuildCost {
+    /// E.g. "rebuilds 312 crates (Cargo.lock)".
+    pub fn describe(&self) -> String {
+        let lockfile = self.lockfile.file_name().unwrap_or(self.lockfile.as_os_str()).to_string_lossy();
+        if lockfile == "Cargo.lock" {
+            format!("rebuilds {} crates ({})", self.packages, lockfile)
+        } else {
+            format!("reinstalls {} packages ({})", self.packages, lockfile)
+        }
+    }
+}
+
+/// Counts the packages in a lockfile's contents.
+type Counter = fn(&str) -> Option<usize>;
+
+/// Lockfiles each kind of artifact is installed or built from, in the order
+/// they're looked for, with how to count the packages in each.
+const NODE_LOCKFILES: &[(&str, Counter)] = &[
+    ("package-lock.json", npm_packages),
+    ("npm-shrinkwrap.json", npm_packages),
+    ("yarn.lock", yarn_packages),
+    ("pnpm-lock.yaml", pnpm_packages),
+];
+const PYTHON_LOCKFILES: &[(&str, Counter)] = &[
+    ("uv.lock", toml_packages),
+    ("poetry.lock", toml_packages),
+    ("pdm.lock", toml_packages),
+    ("Pipfile.lock", pipfile_packages),
+    ("requirements.txt", requirements),
+];
+const RUST_LOCKFILES: &[(&str, Counter)] = &[("Cargo.lock", toml_packages)];
+
+/// Estimate what regenerating `artifact` costs. Only dependency
+/// directories and Rust builds have a lockfile saying; build caches and
+/// other outputs give `None`.
+pub fn estimate(artifact: &Path, project_type: ProjectType) -> Option<RebuildCost> {
+    let name = artifact.file_name()?.to_str()?;
+    let lockfiles = match (project_type, name) {
+        (ProjectType::Rust, "target") => RUST_LOCKFILES,
+        (ProjectType::Node, "node_modules") => NODE_LOCKFILES,
+        (ProjectType::Python, ".venv" | "venv" | ".tox") => PYTHON_LOCKFILES,
+        _ => return None,
+    };
+    let project = artifact.parent()?;
+    lockfiles.iter().find_map(|(file, count)| {
+        let lockfile = project.join(file);
+        let packages = count(&fs::read_to_string(&lockfile).ok()?)?;
+        Some(RebuildCost { packages, lockfile })
+    })
+}
+
+/// `[[package]]` tables, as in Cargo.lock, uv.lock and poetry.lock.
+fn toml_packages(contents: &str) -> Option<usize> {
+    Some(contents.lines().filter(|line| line.trim() == "[[package]]").count())
+}
+
+/// Entries under `packages` (lockfile v2 and later, less the root project)
+/// or `dependencies` (v1).
+fn npm_packages(contents: &str) -> Option<usize> {
+    let json: serde_json::Value = serde_json::from_str(contents).ok()?;
+    if let Some(packages) = json["packages"].as_object() {
+        return Some(packages.keys().filter(|key| !key.is_empty()).count());
+    }
+    json["dependencies"].as_object().map(|deps| deps.len())
+}
+
+/// Unindented entries, each naming a package and the ranges resolved to it.
+fn yarn_packages(contents: &str) -> Option<usize> {
+    Some(
+        contents
+            .lines()
+            .filter(|line| !line.starts_with([' ', '#']) && line.ends_with(':'))
+            .filter(|line| !line.starts_with("__metadata"))
+            .count(),
+    )
+}
+
+/// Keys directly under the top-level `packages:` map.
+fn pnpm_packages(contents: &str) -> Option<usize> {
+    let mut in_packages = false;
+    let mut count = 0;
+    for line in contents.lines() {
+        if !line.starts_with(' ') && !line.is_empty() {
+            in_packages = line == "packages:";
+        } else if in_packages && line.starts_with("  ") && !line.starts_with("   ") && line.ends_with(':') {
+            count += 1;
+        }
+    }
+    Some(count)
+}
+
+fn pipfile_packages(contents: &str) -> Option<usize> {
+    let json: serde_json::Value = serde_json::from_str(contents).ok()?;
+    let section = |name: &str| json[name].as_object().map_or(0, |deps| deps.len());
+    Some(section("default") + section("develop"))
+}
+
+/// Requirement lines, skipping comments and options such as `-r` or
+/// `--index-url`.
+fn requirements(contents: &str) -> Option<usize> {
+    Some(
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with(['#', '-']))
+            .count(),
+    )
+}
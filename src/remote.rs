@@ -0,0 +1,257 @@
+use crate::cleaner::CleanResult;
+use crate::integrate::sh_quote;
+use crate::logging::verbose;
+use crate::plan;
+use crate::projects::{Explanation, ProjectType};
+use crate::scanner::{FoundDir, ScanEvent, Skipped};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Exit status of a remote shell that couldn't find the command.
+const COMMAND_NOT_FOUND: i32 = 127;
+
+/// A directory on another machine, scanned and cleaned by the safeclean
+/// installed there.
+#[derive(Debug, Clone)]
+pub struct Remote {
+    /// `host` or `user@host`, as ssh takes it
+    pub host: String,
+    pub path: String,
+}
+
+impl Remote {
+    /// Parse `[user@]host:/path`.
+    pub fn parse(spec: &str) -> Result<Remote, String> {
+        match spec.split_once(':') {
+            Some((host, path)) if !host.is_empty() && !host.starts_with('-') && !path.is_empty() => Ok(Remote {
+                host: host.to_string(),
+                path: path.to_string(),
+            }),
+            _ => Err(format!("expected user@host:/path, got '{}'", spec)),
+        }
+    }
+}
+
+impl fmt::Display for Remote {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.host, self.path)
+    }
+}
+
+/// The `--progress json` events a scan is rebuilt from.
+#[derive(Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+enum Event {
+    DirFound {
+        path: PathBuf,
+        #[serde(rename = "type")]
+        project_type: String,
+        matched: Explanation,
+        #[serde(default)]
+        global: bool,
+    },
+    SizingProgress {
+        path: PathBuf,
+        size_bytes: u64,
+        age_days: Option<u64>,
+        project: Option<PathBuf>,
+    },
+    DirSkipped {
+        path: PathBuf,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// What `apply --format json` reports for each directory.
+#[derive(Deserialize)]
+struct Summary {
+    results: Vec<Outcome>,
+}
+
+#[derive(Deserialize)]
+struct Outcome {
+    path: PathBuf,
+    size_bytes: u64,
+    status: String,
+    error: Option<String>,
+}
+
+/// Scan `remote` with the safeclean there, passing it `args`, and send each
+/// directory on as it is found and again once sized, then
+/// [`ScanEvent::Done`]. Stops early if nobody is listening any more.
+pub fn scan(remote: &Remote, args: &[String], events: Sender<ScanEvent>) -> io::Result<()> {
+    let mut command: Vec<String> = vec!["safeclean".into(), "--progress".into(), "json".into()];
+    command.extend(args.iter().cloned());
+    // Quoted, ~ wouldn't expand, but ssh starts in the home directory anyway
+    let path = remote.path.strip_prefix("~/").unwrap_or(&remote.path);
+    command.extend(["--".to_string(), path.to_string()]);
+    // Stderr is collected rather than shown, since the selector owns the
+    // terminal while this runs
+    let mut child = ssh(&remote.host, &command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("could not run ssh: {}", e)))?;
+    let mut stderr = child.stderr.take().unwrap();
+    let errors = thread::spawn(move || {
+        let mut errors = String::new();
+        let _ = stderr.read_to_string(&mut errors);
+        errors
+    });
+
+    let mut found: HashMap<PathBuf, FoundDir> = HashMap::new();
+    for line in BufReader::new(child.stdout.take().unwrap()).lines() {
+        let line = line?;
+        let event = match serde_json::from_str::<Event>(&line) {
+            Ok(event) => event,
+            Err(e) => {
+                verbose!("ignoring output from {}: {}", remote.host, e);
+                continue;
+            }
+        };
+        let event = match event {
+            Event::DirFound { path, project_type, matched, global } => {
+                let dir = found_dir(path, &project_type, matched, global);
+                found.insert(dir.path.clone(), dir.clone());
+                ScanEvent::Found(dir)
+            }
+            Event::SizingProgress { path, size_bytes, age_days, project } => {
+                let Some(mut dir) = found.remove(&path) else {
+                    continue;
+                };
+                dir.size_bytes = size_bytes;
+                dir.modified = age_days.map(|days| SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60));
+                if let Some(project) = project {
+                    dir.project_root = project;
+                }
+                ScanEvent::Sized(dir)
+            }
+            Event::DirSkipped { path } => match found.remove(&path) {
+                Some(dir) => ScanEvent::Unwanted(dir),
+                None => continue,
+            },
+            Event::Other => continue,
+        };
+        if events.send(event).is_err() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(());
+        }
+    }
+    let status = child.wait()?;
+    // 2 is the remote's "nothing found"
+    if !status.success() && status.code() != Some(2) {
+        return Err(failed(remote, status, &errors.join().unwrap_or_default()));
+    }
+    let _ = events.send(ScanEvent::Done(Skipped::default()));
+    Ok(())
+}
+
+/// Have the safeclean on `remote` remove `dirs`, passing it `args`. Like
+/// `safeclean apply`, it checks each against its rules again first; those
+/// it skips count as failed.
+pub fn clean(remote: &Remote, args: &[String], dirs: Vec<FoundDir>) -> io::Result<CleanResult> {
+    let mut command: Vec<String> = ["safeclean", "apply", "--format", "json"].map(String::from).to_vec();
+    command.extend(args.iter().cloned());
+    command.extend(["--".to_string(), "/dev/stdin".to_string()]);
+    // The remote's warnings, such as why it skipped a directory, go straight
+    // to the terminal
+    let mut child = ssh(&remote.host, &command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("could not run ssh: {}", e)))?;
+    let plan = plan::to_json(&dirs)?;
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(plan.as_bytes())?;
+    drop(stdin);
+    let output = child.wait_with_output()?;
+    // 2 when nothing was still cleanable, 3 when some removals failed
+    if !output.status.success() && !matches!(output.status.code(), Some(2 | 3)) {
+        return Err(failed(remote, output.status, ""));
+    }
+
+    let outcomes = if output.stdout.iter().all(u8::is_ascii_whitespace) {
+        Vec::new()
+    } else {
+        serde_json::from_slice::<Summary>(&output.stdout)
+            .map_err(|e| io::Error::other(format!("unexpected answer from {}: {}", remote.host, e)))?
+            .results
+    };
+    let mut result = CleanResult {
+        deleted: Vec::new(),
+        failed: Vec::new(),
+    };
+    for mut dir in dirs {
+        match outcomes.iter().find(|outcome| outcome.path == dir.path) {
+            Some(outcome) if outcome.status == "deleted" => {
+                dir.size_bytes = outcome.size_bytes;
+                result.deleted.push(dir);
+            }
+            Some(outcome) => {
+                let error = outcome.error.clone().unwrap_or_else(|| outcome.status.clone());
+                result.failed.push((dir, io::Error::other(error)));
+            }
+            None => result.failed.push((dir, io::Error::other(format!("skipped by safeclean on {}", remote.host)))),
+        }
+    }
+    Ok(result)
+}
+
+/// A directory as the remote reported it; what it takes local checks to
+/// know, such as git status, is left unknown.
+fn found_dir(path: PathBuf, project_type: &str, matched: Explanation, global: bool) -> FoundDir {
+    let project_type = ProjectType::all()
+        .into_iter()
+        .find(|pt| pt.name() == project_type)
+        .unwrap_or_else(|| ProjectType::custom(project_type, true));
+    FoundDir {
+        project_root: path.parent().map(PathBuf::from).unwrap_or_default(),
+        path,
+        project_type,
+        size_bytes: 0,
+        modified: None,
+        git: None,
+        in_use: false,
+        editor: None,
+        global,
+        container: None,
+        matched,
+        ignored: None,
+        suspicious: None,
+        rebuild: None,
+    }
+}
+
+/// `command` run on `host`, each argument quoted for its shell.
+fn ssh(host: &str, command: &[String]) -> Command {
+    let mut ssh = Command::new("ssh");
+    // A password prompt would fight the selector for the terminal, so this
+    // takes a key or an agent
+    ssh.args(["-o", "BatchMode=yes", "--", host]);
+    ssh.arg(command.iter().map(|arg| sh_quote(arg)).collect::<Vec<_>>().join(" "));
+    ssh
+}
+
+fn failed(remote: &Remote, status: ExitStatus, stderr: &str) -> io::Error {
+    if status.code() == Some(COMMAND_NOT_FOUND) {
+        return io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("safeclean is not installed on {}, or not on the PATH ssh sessions get", remote.host),
+        );
+    }
+    match stderr.trim().trim_start_matches("error: ") {
+        "" => io::Error::other(format!("safeclean on {} failed ({})", remote.host, status)),
+        stderr => io::Error::other(format!("safeclean on {} failed: {}", remote.host, stderr)),
+    }
+}
@@ -0,0 +1,167 @@
+use crate::cleaner::CleanResult;
+use crate::format_size;
+use crate::projects::ProjectType;
+use crate::scanner::FoundDir;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Sorts a table by the clicked column; cells sort by `data-sort` when set.
+const SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable th").forEach((th, column) => {
+  th.addEventListener("click", () => {
+    const body = th.closest("table").tBodies[0];
+    const ascending = th.dataset.order !== "asc";
+    th.closest("tr").querySelectorAll("th").forEach(h => delete h.dataset.order);
+    th.dataset.order = ascending ? "asc" : "desc";
+    const key = row => {
+      const cell = row.cells[column];
+      return cell.dataset.sort !== undefined ? Number(cell.dataset.sort) : cell.textContent;
+    };
+    const rows = [...body.rows].sort((a, b) => {
+      const [x, y] = [key(a), key(b)];
+      const order = typeof x === "number" ? x - y : x.localeCompare(y);
+      return ascending ? order : -order;
+    });
+    body.append(...rows);
+  });
+});
+"#;
+
+const STYLE: &str = r#"
+body { font: 14px/1.4 system-ui, sans-serif; margin: 2em auto; max-width: 70em; color: #222; }
+h1 { font-size: 1.6em; } h2 { font-size: 1.2em; margin-top: 2em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { padding: .3em .6em; border-bottom: 1px solid #ddd; text-align: left; }
+th { cursor: pointer; user-select: none; background: #f4f4f4; }
+th[data-order=asc]::after { content: " \25B2"; } th[data-order=desc]::after { content: " \25BC"; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+.bar { background: #4caf50; height: 1em; }
+.failed { color: #c62828; }
+"#;
+
+/// Escape text for use in HTML content and attribute values.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Per-type bar chart of `dirs`' sizes.
+fn chart(html: &mut String, dirs: &[&FoundDir]) {
+    let sizes: Vec<(ProjectType, usize, u64)> = ProjectType::all()
+        .into_iter()
+        .map(|pt| {
+            let of_type = dirs.iter().filter(|d| d.project_type == pt);
+            (pt, of_type.clone().count(), of_type.map(|d| d.size_bytes).sum())
+        })
+        .filter(|(_, count, _)| *count > 0)
+        .collect();
+    let largest = sizes.iter().map(|(_, _, size)| *size).max().unwrap_or(0).max(1);
+
+    html.push_str("<table>\n<tbody>\n");
+    for (pt, count, size) in sizes {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=num>{}</td><td class=num>{}</td>\
+             <td style=\"width:50%\"><div class=bar style=\"width:{:.1}%\"></div></td></tr>",
+            escape(pt.name()),
+            count,
+            format_size(size),
+            size as f64 * 100.0 / largest as f64
+        );
+    }
+    html.push_str("</tbody>\n</table>\n");
+}
+
+/// Sortable table of `dirs`, with a status column when `status` gives one.
+fn table(html: &mut String, dirs: &[(&FoundDir, Option<String>)]) {
+    let with_status = dirs.iter().any(|(_, status)| status.is_some());
+    html.push_str("<table class=sortable>\n<thead><tr><th>Path</th><th>Type</th><th>Size</th><th>Age (days)</th><th>Project</th>");
+    if with_status {
+        html.push_str("<th>Status</th>");
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for (dir, status) in dirs {
+        let age = dir.age_days();
+        let _ = write!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td class=num data-sort={}>{}</td><td class=num data-sort={}>{}</td><td>{}</td>",
+            escape(&dir.path.display().to_string()),
+            escape(dir.project_type.name()),
+            dir.size_bytes,
+            format_size(dir.size_bytes),
+            age.map_or(-1, |days| days as i64),
+            age.map_or_else(String::new, |days| days.to_string()),
+            escape(&dir.project_root.display().to_string()),
+        );
+        if let Some(status) = status {
+            let class = if status == "deleted" { "" } else { " class=failed" };
+            let _ = write!(html, "<td{}>{}</td>", class, escape(status));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n");
+}
+
+/// Write a self-contained HTML report of what was `found` and, if a clean
+/// ran, what it removed.
+pub fn write(file: &Path, found: &[FoundDir], cleaned: Option<&CleanResult>) -> io::Result<()> {
+    let generated = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=en>\n<head>\n<meta charset=utf-8>\n\
+         <title>safeclean report</title>\n<style>{}</style>\n</head>\n<body>\n\
+         <h1>safeclean report</h1>\n<p id=generated data-time={}></p>\n",
+        STYLE, generated
+    );
+
+    let all: Vec<&FoundDir> = found.iter().collect();
+    let _ = writeln!(
+        html,
+        "<h2>Found: {} in {} directories</h2>",
+        format_size(found.iter().map(|d| d.size_bytes).sum()),
+        found.len()
+    );
+    chart(&mut html, &all);
+    let rows: Vec<(&FoundDir, Option<String>)> = found.iter().map(|d| (d, None)).collect();
+    table(&mut html, &rows);
+
+    if let Some(result) = cleaned {
+        let _ = writeln!(
+            html,
+            "<h2>Cleaned: {} in {} directories{}</h2>",
+            format_size(result.total_cleaned()),
+            result.deleted.len(),
+            if result.failed.is_empty() {
+                String::new()
+            } else {
+                format!(", {} failed", result.failed.len())
+            }
+        );
+        let deleted: Vec<&FoundDir> = result.deleted.iter().collect();
+        chart(&mut html, &deleted);
+        let rows: Vec<(&FoundDir, Option<String>)> = result
+            .deleted
+            .iter()
+            .map(|d| (d, Some("deleted".to_string())))
+            .chain(result.failed.iter().map(|(d, e)| (d, Some(format!("failed: {}", e)))))
+            .collect();
+        table(&mut html, &rows);
+    }
+
+    let _ = write!(
+        html,
+        "<script>\nconst generated = document.getElementById(\"generated\");\n\
+         generated.textContent = \"Generated \" + new Date(generated.dataset.time * 1000).toLocaleString();\n{}</script>\n\
+         </body>\n</html>\n",
+        SCRIPT
+    );
+    fs::write(file, html)
+}
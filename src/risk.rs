@@ -0,0 +1,166 @@
+use crate::projects::ProjectType;
+use crate::scanner::FoundDir;
+use std::path::{Path, PathBuf};
+
+/// Artifacts modified within this many days count as recently used.
+const RECENT_DAYS: u64 = 7;
+
+/// How much thought an item deserves before it is cleaned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    pub fn badge(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "low",
+            RiskLevel::Medium => "med",
+            RiskLevel::High => "high",
+        }
+    }
+}
+
+/// Signs that cleaning a directory could get in someone's way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Risk {
+    /// Contents changed within the last week
+    pub recent: bool,
+    /// The owning repository has uncommitted changes
+    pub dirty: bool,
+    /// A running process is working inside the project
+    pub in_use: bool,
+    /// An editor or language server has the project open
+    pub editor_open: bool,
+    /// Mounted into a running container
+    pub in_container: bool,
+    /// Inside a repository but not covered by its ignore rules, so it may
+    /// hold files someone meant to keep
+    pub not_ignored: bool,
+    /// A tool cache every project shares, so cleaning it means downloading
+    /// everything again
+    pub shared: bool,
+    /// Holds a file that doesn't look like build output
+    pub suspicious: bool,
+}
+
+impl Risk {
+    pub fn of(dir: &FoundDir) -> Self {
+        Risk {
+            recent: dir.age_days().is_some_and(|days| days < RECENT_DAYS),
+            dirty: dir.git.is_some_and(|git| git.dirty),
+            in_use: dir.in_use,
+            editor_open: dir.editor.is_some(),
+            in_container: dir.container.is_some(),
+            not_ignored: dir.ignored == Some(false),
+            // Docker objects aren't downloaded again by every project
+            shared: dir.global && dir.project_type != ProjectType::Docker,
+            suspicious: dir.suspicious.is_some(),
+        }
+    }
+
+    /// Weighted sum of the signs. A running process or container, an
+    /// unignored directory or hand-written-looking contents are worth more
+    /// than a recent edit.
+    pub fn score(&self) -> u8 {
+        3 * self.in_use as u8
+            + 3 * self.in_container as u8
+            + 3 * self.not_ignored as u8
+            + 3 * self.suspicious as u8
+            + 2 * self.editor_open as u8
+            + 2 * self.dirty as u8
+            + 2 * self.shared as u8
+            + self.recent as u8
+    }
+
+    pub fn level(&self) -> RiskLevel {
+        match self.score() {
+            0 => RiskLevel::Low,
+            1..=2 => RiskLevel::Medium,
+            _ => RiskLevel::High,
+        }
+    }
+
+    /// Human-readable list of the signs that apply.
+    pub fn reasons(&self) -> Vec<&'static str> {
+        [
+            (self.in_use, "a process is running in the project"),
+            (self.editor_open, "open in an editor"),
+            (self.in_container, "mounted into a running container"),
+            (self.not_ignored, "not gitignored"),
+            (self.suspicious, "holds files that look hand-written"),
+            (self.dirty, "uncommitted changes"),
+            (self.shared, "shared cache, every project re-downloads"),
+            (self.recent, "modified this week"),
+        ]
+        .into_iter()
+        .filter(|(applies, _)| *applies)
+        .map(|(_, reason)| reason)
+        .collect()
+    }
+}
+
+/// Source files no build tool this knows writes directly into its output
+/// directory.
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "c", "cc", "cpp", "cs", "go", "h", "hpp", "java", "jsx", "kt", "md", "py", "rb", "rs", "svelte", "swift", "ts",
+    "tsx", "vue",
+];
+
+/// Whether the entry at `path`, `depth` levels inside a cleanable directory,
+/// looks like something a person put there rather than a build tool: a
+/// `.env` file or nested repository near the top, or a database or source
+/// file directly inside.
+pub fn looks_hand_written(path: &Path, depth: usize, is_dir: bool) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    if depth == 0 || depth > 2 {
+        return false;
+    }
+    if is_dir {
+        return name == ".git";
+    }
+    if name == ".env" || name.starts_with(".env.") {
+        return true;
+    }
+    if depth > 1 || name.ends_with(".d.ts") {
+        return false;
+    }
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    matches!(extension, "db" | "sqlite" | "sqlite3") || SOURCE_EXTENSIONS.contains(&extension)
+}
+
+/// Working directories of other running processes. Only available where
+/// `/proc` exists; elsewhere nothing is reported as in use.
+pub fn process_dirs() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    // Our own process and the shell that started us are usually sitting in
+    // the directory being scanned
+    let own = [std::process::id(), parent_id()];
+
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.file_name()
+                .to_str()
+                .and_then(|name| name.parse::<u32>().ok())
+                .is_some_and(|pid| !own.contains(&pid))
+        })
+        .filter_map(|e| std::fs::read_link(e.path().join("cwd")).ok())
+        .collect()
+}
+
+#[cfg(unix)]
+fn parent_id() -> u32 {
+    std::os::unix::process::parent_id()
+}
+
+#[cfg(not(unix))]
+fn parent_id() -> u32 {
+    0
+}
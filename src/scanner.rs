@@ -1,54 +1,493 @@
-use crate::projects::{get_cleanable_dirs, ProjectType};
-use std::collections::HashSet;
+use crate::caches::{self, GlobalCache, OrphanedEnv};
+use crate::containers::Mounts;
+use crate::docker::{self, Object};
+use crate::editors::Sessions;
+use crate::exclude;
+use crate::git::{self, GitStatus};
+use crate::logging::{trace, verbose};
+use crate::owner;
+use crate::projects::{get_cleanable_dirs, CleanableDir, Explanation, ProjectType};
+use crate::rebuild::{self, RebuildCost};
+use crate::risk;
+use crate::sizing;
+use crate::toolchains::{self, Toolchain};
+use crate::units::format_size;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime};
 use walkdir::WalkDir;
 
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// What a scan passed over.
+#[derive(Debug, Clone, Default)]
+pub struct Skipped {
+    /// Paths that couldn't be read, so sizes may be short and artifacts may
+    /// have been missed
+    pub unreadable: Vec<PathBuf>,
+    /// Artifacts left alone for being modified more recently than
+    /// [`ScanOptions::older_than`]
+    pub too_recent: Vec<PathBuf>,
+}
+
+impl Skipped {
+    /// Add what another part of the same scan passed over.
+    fn extend(&mut self, other: Skipped) {
+        self.unreadable.extend(other.unreadable);
+        self.too_recent.extend(other.too_recent);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FoundDir {
     pub path: PathBuf,
     pub project_type: ProjectType,
     pub size_bytes: u64,
+    /// Most recent modification time of anything inside the directory.
+    pub modified: Option<SystemTime>,
+    /// Repository or project directory the artifact belongs to.
+    pub project_root: PathBuf,
+    /// Working-tree state of the owning repository, if it is one.
+    pub git: Option<GitStatus>,
+    /// Another running process has its working directory inside the project.
+    pub in_use: bool,
+    /// Editor or language server that seems to have the project open.
+    pub editor: Option<&'static str>,
+    /// A per-user cache or environment offered with `--global` rather than
+    /// part of a project. These start unselected whatever their type.
+    pub global: bool,
+    /// Running container the directory, or a directory holding it, is
+    /// mounted into.
+    pub container: Option<String>,
+    /// The rule that matched the directory and the file that confirmed it.
+    pub matched: Explanation,
+    /// Whether the repository's ignore rules cover the directory; `None`
+    /// outside a repository.
+    pub ignored: Option<bool>,
+    /// A file inside that doesn't look like build output, such as a `.env`,
+    /// a database or source code.
+    pub suspicious: Option<PathBuf>,
+    /// What regenerating it would take, if a lockfile says.
+    pub rebuild: Option<RebuildCost>,
 }
 
 impl FoundDir {
+    /// Whether the selector starts with it selected.
+    pub fn preselected(&self) -> bool {
+        !self.global && self.project_type.preselected()
+    }
+
     pub fn size_human(&self) -> String {
         format_size(self.size_bytes)
     }
+
+    /// Time since the directory contents were last modified.
+    pub fn age(&self) -> Option<Duration> {
+        let modified = self.modified?;
+        Some(SystemTime::now().duration_since(modified).unwrap_or_default())
+    }
+
+    /// Whole days since the directory contents were last modified.
+    pub fn age_days(&self) -> Option<u64> {
+        Some(self.age()?.as_secs() / DAY.as_secs())
+    }
+
+    pub fn age_human(&self) -> String {
+        match self.age_days() {
+            None => "unknown".to_string(),
+            Some(0) => "today".to_string(),
+            Some(1) => "1 day ago".to_string(),
+            Some(days) => format!("{} days ago", days),
+        }
+    }
+}
+
+/// What walking a directory found out about it.
+struct DirStats {
+    /// Total size of the files under it
+    size: u64,
+    /// Newest modification time seen
+    modified: Option<SystemTime>,
+    /// First entry that looks hand-written rather than built
+    suspicious: Option<PathBuf>,
+    /// Entries that couldn't be read
+    unreadable: Vec<PathBuf>,
+}
+
+fn dir_stats(path: &Path) -> DirStats {
+    let mut stats = DirStats {
+        size: 0,
+        modified: None,
+        suspicious: None,
+        unreadable: Vec::new(),
+    };
+
+    sizing::walk(
+        path,
+        |entry| {
+            if entry.is_file {
+                stats.size += entry.len;
+            }
+            if let Some(modified) = entry.modified {
+                stats.modified = Some(stats.modified.map_or(modified, |n| n.max(modified)));
+            }
+            if stats.suspicious.is_none() && risk::looks_hand_written(entry.path, entry.depth, entry.is_dir) {
+                stats.suspicious = Some(entry.path.to_path_buf());
+            }
+        },
+        |path, error| {
+            verbose!("skip {}: {}", path.display(), error);
+            stats.unreadable.push(path);
+        },
+    );
+
+    stats
+}
+
+/// Immediate children of `path` with their sizes, largest first.
+pub fn largest_entries(path: &Path, limit: usize) -> Vec<(PathBuf, u64)> {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return Vec::new();
+    };
+
+    let mut sized: Vec<(PathBuf, u64)> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| {
+            let child = e.path();
+            let size = dir_stats(&child).size;
+            (child, size)
+        })
+        .collect();
+
+    sized.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    sized.truncate(limit);
+    sized
+}
+
+/// Nearest ancestor of `path` that is a git repository, falling back to the
+/// artifact's parent directory.
+fn find_project_root(path: &Path) -> PathBuf {
+    let parent = path.parent().unwrap_or(path);
+    parent
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(parent)
+        .to_path_buf()
+}
+
+/// What a scan looks for and what it leaves out.
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    pub enabled_types: HashSet<ProjectType>,
+    /// Paths never offered for cleaning
+    pub excluded: Vec<PathBuf>,
+    /// Smaller artifacts are left alone
+    pub min_size: Option<u64>,
+    /// Artifacts modified more recently than this are left alone
+    pub older_than: Option<Duration>,
+    /// Rules checked before the built-in ones
+    pub extra_dirs: Vec<CleanableDir>,
+    /// Also offer the per-user tool caches, and what `docker` and
+    /// `toolchains` would
+    pub global: bool,
+    /// Also offer the Docker daemon's unused images, containers and build
+    /// cache
+    pub docker: bool,
+    /// Also offer compilers and runtimes the version managers installed
+    /// besides the default and active ones
+    pub toolchains: bool,
+    /// Entries of caches offered entry by entry, and toolchains, stay if used
+    /// more recently than this
+    pub cache_keep: Option<Duration>,
+    /// Also offer directories other users own
+    pub all_users: bool,
+    /// Leave sizes for [`measure_size`], going by each directory's own
+    /// modification time for its age until then
+    pub skip_sizes: bool,
+}
+
+impl ScanOptions {
+    /// Whether a measured directory passes the size and age thresholds,
+    /// noting it in `skipped` if it is only too recent.
+    pub fn wanted(&self, dir: &FoundDir, skipped: &mut Skipped) -> bool {
+        if let Some(min_size) = self.min_size
+            && !self.skip_sizes
+            && dir.size_bytes < min_size
+        {
+            verbose!("skip {}: smaller than {} bytes", dir.path.display(), min_size);
+            return false;
+        }
+        if too_recent(dir, self.older_than) {
+            verbose!("skip {}: modified too recently", dir.path.display());
+            skipped.too_recent.push(dir.path.clone());
+            return false;
+        }
+        true
+    }
+}
+
+/// Whether a measured directory was modified within `older_than`.
+pub fn too_recent(dir: &FoundDir, older_than: Option<Duration>) -> bool {
+    older_than.is_some_and(|older_than| dir.age().is_none_or(|age| age < older_than))
 }
 
-fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
+/// Progress of a streaming scan.
+#[derive(Debug)]
+pub enum ScanEvent {
+    /// A cleanable directory was found; its size, age and git status are not
+    /// known yet.
+    Found(FoundDir),
+    /// A previously reported directory, now fully measured.
+    Sized(FoundDir),
+    /// A previously reported directory that, once measured, fell short of
+    /// the size or age thresholds and is no longer offered.
+    Unwanted(FoundDir),
+    /// Discovery and sizing have both finished, passing over what this
+    /// holds.
+    Done(Skipped),
+}
 
-    if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
+/// Check a directory called `dir_name` against each rule in turn, returning
+/// it unmeasured if one matches.
+fn match_rules(path: &Path, dir_name: &str, rules: &[CleanableDir], options: &ScanOptions) -> Option<FoundDir> {
+    for cleanable in rules {
+        if !cleanable.matches_name(dir_name) {
+            continue;
+        }
+        if !options.enabled_types.contains(&cleanable.project_type) {
+            verbose!(
+                "skip {}: {} is not enabled",
+                path.display(),
+                cleanable.project_type.name()
+            );
+            continue;
+        }
+        let Some(matched) = cleanable.explain(path) else {
+            verbose!(
+                "reject {}: no {} project next to it",
+                path.display(),
+                cleanable.project_type.name()
+            );
+            continue;
+        };
+        verbose!(
+            "match {} ({}: {})",
+            path.display(),
+            cleanable.project_type.name(),
+            matched.describe()
+        );
+        return Some(FoundDir {
+            path: path.to_path_buf(),
+            project_type: cleanable.project_type,
+            size_bytes: 0,
+            modified: None,
+            project_root: find_project_root(path),
+            git: None,
+            in_use: false,
+            editor: None,
+            global: false,
+            container: None,
+            rebuild: None,
+            matched,
+            ignored: None,
+            suspicious: None,
+        });
+    }
+    None
+}
+
+/// `path`, either `cache` itself or one of its entries, as found.
+fn cache_dir(cache: &GlobalCache, path: PathBuf, keep: Option<Duration>) -> FoundDir {
+    let rule = match keep {
+        Some(keep) if cache.by_entry() => {
+            format!("in {}'s cache, unused for {} days", cache.tool, keep.as_secs() / 86_400)
+        }
+        _ => match cache.size_limit() {
+            Some(limit) => format!("where {} keeps its cache, limited to {}", cache.tool, format_size(limit)),
+            None => format!("where {} keeps its cache", cache.tool),
+        },
+    };
+    // Entries group under their cache
+    let project_root = if cache.by_entry() {
+        cache.path.clone()
     } else {
-        format!("{} B", bytes)
+        path.parent().unwrap_or(&path).to_path_buf()
+    };
+    FoundDir {
+        project_type: cache.project_type,
+        ..global_dir(path, project_root, rule, None)
     }
 }
 
-fn dir_size(path: &Path) -> u64 {
-    WalkDir::new(path)
+fn orphan_dir(env: OrphanedEnv) -> FoundDir {
+    let rule = format!("{} environment for {}, which no longer exists", env.tool, env.project.display());
+    let project_root = env.path.parent().unwrap_or(&env.path).to_path_buf();
+    global_dir(env.path, project_root, rule, Some(env.marker))
+}
+
+/// An object the Docker daemon could remove, as found. Docker sized it, so
+/// it isn't measured again.
+fn docker_dir(object: Object) -> FoundDir {
+    let path = object.path();
+    let project_root = PathBuf::from(format!("{}{}s", docker::PREFIX, object.kind.name()));
+    FoundDir {
+        project_type: ProjectType::Docker,
+        size_bytes: object.size_bytes,
+        modified: object.modified,
+        ..global_dir(path, project_root, object.description, None)
+    }
+}
+
+/// An installed toolchain, as found.
+fn toolchain_dir(toolchain: Toolchain) -> FoundDir {
+    let rule = format!("{} {}, neither the default nor active", toolchain.manager, toolchain.version);
+    let project_root = toolchain.path.parent().unwrap_or(&toolchain.path).to_path_buf();
+    FoundDir {
+        project_type: ProjectType::Toolchain,
+        ..global_dir(toolchain.path, project_root, rule, None)
+    }
+}
+
+/// Toolchains that may be removed, less those run within `keep` and those
+/// projects are `pinned` to.
+fn stale_toolchains(keep: Option<Duration>, pinned: &[String]) -> Vec<Toolchain> {
+    let now = SystemTime::now();
+    toolchains::removable(pinned)
         .into_iter()
-        .filter_map(|e| e.ok())
-        .filter_map(|e| e.metadata().ok())
-        .filter(|m| m.is_file())
-        .map(|m| m.len())
-        .sum()
+        .filter(|toolchain| {
+            keep.is_none_or(|keep| {
+                toolchains::last_used(&toolchain.path)
+                    .is_none_or(|used| now.duration_since(used).unwrap_or_default() >= keep)
+            })
+        })
+        .collect()
 }
 
-pub fn scan(root: &Path, enabled_types: &HashSet<ProjectType>) -> Vec<FoundDir> {
-    let cleanable_dirs = get_cleanable_dirs();
-    let mut found: Vec<FoundDir> = Vec::new();
+/// A per-user directory outside any project, as found.
+fn global_dir(path: PathBuf, project_root: PathBuf, rule: String, marker: Option<PathBuf>) -> FoundDir {
+    FoundDir {
+        matched: Explanation {
+            pattern: path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+            rule,
+            marker,
+        },
+        project_root,
+        path,
+        project_type: ProjectType::GlobalCache,
+        size_bytes: 0,
+        modified: None,
+        git: None,
+        in_use: false,
+        editor: None,
+        global: true,
+        container: None,
+        rebuild: None,
+        ignored: None,
+        suspicious: None,
+    }
+}
+
+/// Check a single directory against the rules as a scan would, without
+/// walking anything else, and measure it if it still matches. Excluded
+/// paths and symlinks never match.
+pub fn examine(path: &Path, options: &ScanOptions) -> Option<FoundDir> {
+    if exclude::is_excluded(path, &options.excluded) {
+        verbose!("skip {}: excluded", path.display());
+        return None;
+    }
+    if docker::parse(path).is_some() {
+        if !options.docker && !options.global {
+            return None;
+        }
+        return docker::unused().into_iter().find(|object| object.path() == path).map(docker_dir);
+    }
+    if (options.toolchains || options.global)
+        && let Some(toolchain) = stale_toolchains(options.cache_keep, &[]).into_iter().find(|t| t.path == path)
+    {
+        if !options.all_users && owner::is_foreign(path) {
+            verbose!("skip {}: owned by another user", path.display());
+            return None;
+        }
+        let mut dir = toolchain_dir(toolchain);
+        measure(&mut dir, true, &mut HashMap::new(), &Activity::load());
+        return Some(dir);
+    }
+    let cache = caches::global_caches().into_iter().find(|cache| cache.holds(path));
+    // Some cache entries, like downloaded crates, are files
+    let entry = cache.as_ref().is_some_and(|cache| cache.by_entry());
+    if !path.symlink_metadata().is_ok_and(|m| m.is_dir() || (entry && m.is_file())) {
+        verbose!("skip {}: not a directory", path.display());
+        return None;
+    }
+    if !options.all_users && owner::is_foreign(path) {
+        verbose!("skip {}: owned by another user", path.display());
+        return None;
+    }
+    let orphan = || caches::orphaned_envs().into_iter().find(|env| env.path == path);
+    let mut dir = match cache {
+        Some(cache) if options.global => cache_dir(&cache, path.to_path_buf(), options.cache_keep),
+        None if options.global && let Some(env) = orphan() => orphan_dir(env),
+        _ => {
+            let dir_name = path.file_name()?.to_str()?;
+            let mut rules = options.extra_dirs.clone();
+            rules.extend(get_cleanable_dirs());
+            match_rules(path, dir_name, &rules, options)?
+        }
+    };
+    measure(&mut dir, true, &mut HashMap::new(), &Activity::load());
+    Some(dir)
+}
+
+/// Walk `roots` and report each cleanable directory as soon as it is found,
+/// before it has been measured. Paths the walk can't read go in `unreadable`.
+fn discover(
+    roots: &[PathBuf],
+    options: &ScanOptions,
+    unreadable: &mut Vec<PathBuf>,
+    mut on_found: impl FnMut(FoundDir),
+) {
+    let mut cleanable_dirs = options.extra_dirs.clone();
+    cleanable_dirs.extend(get_cleanable_dirs());
     let mut skip_prefixes: Vec<PathBuf> = Vec::new();
+    // The cleaner refuses paths that lead through a symlink, so report
+    // canonical ones
+    let roots: Vec<PathBuf> = roots
+        .iter()
+        .map(|root| root.canonicalize().unwrap_or_else(|_| root.clone()))
+        .collect();
 
-    for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
+    let walk = |root| {
+        WalkDir::new(root).into_iter().filter_entry(|e| {
+            let skip = exclude::is_excluded(e.path(), &options.excluded);
+            if skip {
+                verbose!("skip {}: excluded", e.path().display());
+            }
+            !skip
+        })
+    };
+
+    // Toolchains the projects found along the way ask for by name
+    let mut pinned = Vec::new();
+    for entry in roots.iter().flat_map(walk) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(error) => {
+                let path = error.path().map(Path::to_path_buf).unwrap_or_default();
+                verbose!("skip {}: {}", path.display(), error);
+                unreadable.push(path);
+                continue;
+            }
+        };
         if !entry.file_type().is_dir() {
+            let name = entry.file_name();
+            if (name == "rust-toolchain" || name == "rust-toolchain.toml")
+                && let Some(toolchain) = toolchains::rustup_pin(entry.path())
+            {
+                trace!("{} pins {}", entry.path().display(), toolchain);
+                pinned.push(toolchain);
+            }
             continue;
         }
 
@@ -58,33 +497,199 @@ pub fn scan(root: &Path, enabled_types: &HashSet<ProjectType>) -> Vec<FoundDir>
         if skip_prefixes.iter().any(|prefix| path.starts_with(prefix)) {
             continue;
         }
+        trace!("visit {}", path.display());
 
         let Some(dir_name) = path.file_name().and_then(|n| n.to_str()) else {
             continue;
         };
 
-        // Check against each cleanable directory pattern
-        for cleanable in &cleanable_dirs {
-            if !enabled_types.contains(&cleanable.project_type) {
+        if let Some(dir) = match_rules(path, dir_name, &cleanable_dirs, options) {
+            skip_prefixes.push(path.to_path_buf());
+            if !options.all_users && owner::is_foreign(path) {
+                verbose!("skip {}: owned by another user", path.display());
                 continue;
             }
+            on_found(dir);
+        }
+    }
 
-            if dir_name == cleanable.dir_name && (cleanable.validator)(path) {
-                let size_bytes = dir_size(path);
-                found.push(FoundDir {
-                    path: path.to_path_buf(),
-                    project_type: cleanable.project_type,
-                    size_bytes,
-                });
-                skip_prefixes.push(path.to_path_buf());
-                break;
+    if options.docker || options.global {
+        for object in docker::unused() {
+            let path = object.path();
+            if exclude::is_excluded(&path, &options.excluded) {
+                verbose!("skip {}: excluded", path.display());
+                continue;
             }
+            verbose!("match {} ({})", path.display(), object.description);
+            on_found(docker_dir(object));
+        }
+    }
+    if options.toolchains || options.global {
+        for toolchain in stale_toolchains(options.cache_keep, &pinned) {
+            if exclude::is_excluded(&toolchain.path, &options.excluded) {
+                verbose!("skip {}: excluded", toolchain.path.display());
+                continue;
+            }
+            verbose!("match {} ({} toolchain)", toolchain.path.display(), toolchain.manager);
+            on_found(toolchain_dir(toolchain));
+        }
+    }
+    if !options.global {
+        return;
+    }
+    for cache in caches::global_caches() {
+        if exclude::is_excluded(&cache.path, &options.excluded) {
+            verbose!("skip {}: excluded", cache.path.display());
+            continue;
+        }
+        if !cache.by_entry() {
+            verbose!("match {} ({} cache)", cache.path.display(), cache.tool);
+            on_found(cache_dir(&cache, cache.path.clone(), None));
+            continue;
+        }
+        for entry in cache.stale_entries(options.cache_keep) {
+            verbose!("match {} ({} cache entry)", entry.display(), cache.tool);
+            on_found(cache_dir(&cache, entry, options.cache_keep));
+        }
+    }
+    for env in caches::orphaned_envs() {
+        if exclude::is_excluded(&env.path, &options.excluded) {
+            verbose!("skip {}: excluded", env.path.display());
+            continue;
+        }
+        verbose!("match {} ({} environment of a removed project)", env.path.display(), env.tool);
+        on_found(orphan_dir(env));
+    }
+}
+
+/// What else is going on around the scanned directories, gathered once per
+/// scan.
+struct Activity {
+    /// Working directories of other running processes
+    process_dirs: Vec<PathBuf>,
+    sessions: Sessions,
+    mounts: Mounts,
+}
+
+impl Activity {
+    fn load() -> Activity {
+        Activity {
+            process_dirs: risk::process_dirs(),
+            sessions: Sessions::load(),
+            mounts: Mounts::load(),
         }
     }
+}
+
+/// Fill in size, age, git status and what else is using the directory. Each
+/// repository is queried once, however many artifacts it holds. Without
+/// `size`, the directory isn't walked and its age is its own. Returns the
+/// paths inside it that couldn't be read.
+fn measure(
+    dir: &mut FoundDir,
+    size: bool,
+    git_cache: &mut HashMap<PathBuf, Option<GitStatus>>,
+    activity: &Activity,
+) -> Vec<PathBuf> {
+    if dir.project_type == ProjectType::Docker {
+        return Vec::new();
+    }
+    let mut unreadable = Vec::new();
+    if size {
+        unreadable = measure_size(dir);
+    } else {
+        dir.modified = dir.path.symlink_metadata().and_then(|meta| meta.modified()).ok();
+    }
+    dir.git = *git_cache
+        .entry(dir.project_root.clone())
+        .or_insert_with(|| git::status(&dir.project_root));
+    if dir.git.is_some() {
+        dir.ignored = git::is_ignored(&dir.project_root, &dir.path);
+    }
+    dir.in_use = activity.process_dirs.iter().any(|cwd| cwd.starts_with(&dir.project_root));
+    dir.editor = activity.sessions.open_in(&dir.project_root);
+    dir.container = activity.mounts.container_for(&dir.path).map(str::to_string);
+    dir.rebuild = rebuild::estimate(&dir.path, dir.project_type);
+    trace!("measured {}: {} bytes", dir.path.display(), dir.size_bytes);
+    unreadable
+}
+
+/// Scan and measure everything, largest first, along with what the scan
+/// passed over.
+pub fn scan(roots: &[PathBuf], options: &ScanOptions) -> (Vec<FoundDir>, Skipped) {
+    let mut skipped = Skipped::default();
+    let mut found: Vec<FoundDir> = Vec::new();
+    discover(roots, options, &mut skipped.unreadable, |dir| found.push(dir));
+
+    let mut git_cache = HashMap::new();
+    let activity = Activity::load();
+    for dir in &mut found {
+        let unreadable = measure(dir, !options.skip_sizes, &mut git_cache, &activity);
+        skipped.unreadable.extend(unreadable);
+    }
+    found.retain(|dir| options.wanted(dir, &mut skipped));
 
     // Sort by size descending
-    found.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
-    found
+    found.sort_by_key(|d| std::cmp::Reverse(d.size_bytes));
+    (found, skipped)
+}
+
+/// Like [`scan`], but reports directories through `events` as they are found
+/// and measures them on a separate thread so discovery isn't held up by
+/// sizing. Directories the size or age thresholds rule out once measured are
+/// reported again as [`ScanEvent::Unwanted`]. Stops early if the receiver goes
+/// away.
+pub fn scan_streaming(roots: &[PathBuf], options: &ScanOptions, events: Sender<ScanEvent>) {
+    let (to_measure, measure_queue) = mpsc::channel::<FoundDir>();
+    let mut skipped = Skipped::default();
+
+    thread::scope(|s| {
+        let sized_events = events.clone();
+        let measuring = s.spawn(move || {
+            let mut git_cache = HashMap::new();
+            let activity = Activity::load();
+            let mut skipped = Skipped::default();
+            for mut dir in measure_queue {
+                skipped.unreadable.extend(measure(&mut dir, !options.skip_sizes, &mut git_cache, &activity));
+                let event = if options.wanted(&dir, &mut skipped) {
+                    ScanEvent::Sized(dir)
+                } else {
+                    ScanEvent::Unwanted(dir)
+                };
+                if sized_events.send(event).is_err() {
+                    break;
+                }
+            }
+            skipped
+        });
+
+        discover(roots, options, &mut skipped.unreadable, |dir| {
+            let _ = events.send(ScanEvent::Found(dir.clone()));
+            let _ = to_measure.send(dir);
+        });
+        drop(to_measure);
+        skipped.extend(measuring.join().unwrap_or_default());
+    });
+
+    let _ = events.send(ScanEvent::Done(skipped));
+}
+
+/// Walk a directory a [`ScanOptions::skip_sizes`] scan left unsized to fill
+/// in its size, its age and anything inside that doesn't look like build
+/// output. Returns the paths inside it that couldn't be read.
+pub fn measure_size(dir: &mut FoundDir) -> Vec<PathBuf> {
+    if dir.project_type == ProjectType::Docker {
+        return Vec::new();
+    }
+    let stats = dir_stats(&dir.path);
+    dir.size_bytes = stats.size;
+    dir.modified = stats.modified;
+    // Installing sets the times; only running it says it's still wanted
+    if dir.project_type == ProjectType::Toolchain {
+        dir.modified = toolchains::last_used(&dir.path).or(dir.modified);
+    }
+    dir.suspicious = stats.suspicious;
+    stats.unreadable
 }
 
 pub fn total_size(dirs: &[FoundDir]) -> u64 {
@@ -0,0 +1,210 @@
+use clap::ValueEnum;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+const NAME: &str = "safeclean";
+const LAUNCHD_LABEL: &str = "com.github.jeffjose.safeclean";
+
+/// How often the scheduled clean runs.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// Write and enable a systemd timer, launchd agent or Scheduled Task that
+/// runs `safeclean clean --yes --quiet` plus `extra` arguments. Returns a
+/// description of what was installed.
+pub fn install(frequency: Frequency, extra: &[String]) -> io::Result<String> {
+    let exe = std::env::current_exe()?;
+    let mut command = vec![
+        exe.display().to_string(),
+        "clean".to_string(),
+        "--yes".to_string(),
+        "--quiet".to_string(),
+    ];
+    command.extend(extra.iter().cloned());
+
+    if cfg!(target_os = "macos") {
+        let plist = launchd_plist()?;
+        fs::create_dir_all(plist.parent().unwrap())?;
+        fs::write(&plist, launchd_contents(frequency, &command))?;
+        // Unloading first makes reinstalling pick up the new file
+        let _ = run("launchctl", &["unload", &plist.display().to_string()]);
+        run("launchctl", &["load", "-w", &plist.display().to_string()])?;
+        Ok(plist.display().to_string())
+    } else if cfg!(windows) {
+        let schedule = match frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        };
+        let task = command.iter().map(|arg| quote(arg)).collect::<Vec<_>>().join(" ");
+        run(
+            "schtasks",
+            &["/Create", "/TN", NAME, "/TR", &task, "/SC", schedule, "/ST", "03:00", "/F"],
+        )?;
+        Ok(format!("Scheduled Task '{}'", NAME))
+    } else {
+        let dir = systemd_dir()?;
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("safeclean.service"), systemd_service(&command))?;
+        fs::write(dir.join("safeclean.timer"), systemd_timer(frequency))?;
+        run("systemctl", &["--user", "daemon-reload"])?;
+        run("systemctl", &["--user", "enable", "--now", "safeclean.timer"])?;
+        Ok(dir.join("safeclean.timer").display().to_string())
+    }
+}
+
+/// Disable and delete the schedule. Returns false if none was installed.
+pub fn remove() -> io::Result<bool> {
+    if cfg!(target_os = "macos") {
+        let plist = launchd_plist()?;
+        if !plist.exists() {
+            return Ok(false);
+        }
+        let _ = run("launchctl", &["unload", "-w", &plist.display().to_string()]);
+        fs::remove_file(plist)?;
+        Ok(true)
+    } else if cfg!(windows) {
+        if run("schtasks", &["/Query", "/TN", NAME]).is_err() {
+            return Ok(false);
+        }
+        run("schtasks", &["/Delete", "/TN", NAME, "/F"])?;
+        Ok(true)
+    } else {
+        let dir = systemd_dir()?;
+        let timer = dir.join("safeclean.timer");
+        if !timer.exists() {
+            return Ok(false);
+        }
+        let _ = run("systemctl", &["--user", "disable", "--now", "safeclean.timer"]);
+        fs::remove_file(timer)?;
+        let service = dir.join("safeclean.service");
+        if service.exists() {
+            fs::remove_file(service)?;
+        }
+        let _ = run("systemctl", &["--user", "daemon-reload"]);
+        Ok(true)
+    }
+}
+
+/// Print the scheduler's view of the installed schedule. Returns false if
+/// none is installed.
+pub fn status() -> io::Result<bool> {
+    let (program, args): (&str, Vec<String>) = if cfg!(target_os = "macos") {
+        if !launchd_plist()?.exists() {
+            return Ok(false);
+        }
+        ("launchctl", vec!["list".into(), LAUNCHD_LABEL.into()])
+    } else if cfg!(windows) {
+        ("schtasks", vec!["/Query".into(), "/TN".into(), NAME.into(), "/V".into(), "/FO".into(), "LIST".into()])
+    } else {
+        if !systemd_dir()?.join("safeclean.timer").exists() {
+            return Ok(false);
+        }
+        ("systemctl", vec!["--user".into(), "list-timers".into(), "safeclean.timer".into()])
+    };
+
+    // Let the scheduler print straight to the terminal
+    let status = Command::new(program).args(&args).stdin(Stdio::null()).status()?;
+    if cfg!(windows) && !status.success() {
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Run a system command, such as the scheduler's, turning a non-zero exit
+/// into an error that carries its stderr.
+pub fn run(program: &str, args: &[&str]) -> io::Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("could not run {}: {}", program, e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!("{} failed: {}", program, stderr.trim())));
+    }
+    Ok(())
+}
+
+fn systemd_dir() -> io::Result<PathBuf> {
+    dirs::config_dir()
+        .map(|dir| dir.join("systemd").join("user"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory available"))
+}
+
+fn launchd_plist() -> io::Result<PathBuf> {
+    dirs::home_dir()
+        .map(|home| home.join("Library/LaunchAgents").join(format!("{}.plist", LAUNCHD_LABEL)))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory available"))
+}
+
+/// Double-quote an argument if it contains whitespace or quotes.
+fn quote(arg: &str) -> String {
+    if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '"') {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        arg.to_string()
+    }
+}
+
+fn systemd_service(command: &[String]) -> String {
+    let exec = command.iter().map(|arg| quote(arg)).collect::<Vec<_>>().join(" ");
+    format!(
+        "[Unit]\nDescription=Clean build artifacts with safeclean\n\n\
+         [Service]\nType=oneshot\nExecStart={}\n",
+        exec.replace('%', "%%")
+    )
+}
+
+fn systemd_timer(frequency: Frequency) -> String {
+    let calendar = match frequency {
+        Frequency::Daily => "daily",
+        Frequency::Weekly => "weekly",
+        Frequency::Monthly => "monthly",
+    };
+    format!(
+        "[Unit]\nDescription=Run safeclean {}\n\n\
+         [Timer]\nOnCalendar={}\nPersistent=true\n\n\
+         [Install]\nWantedBy=timers.target\n",
+        calendar, calendar
+    )
+}
+
+fn launchd_contents(frequency: Frequency, command: &[String]) -> String {
+    let escape = |text: &str| text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let arguments: String = command
+        .iter()
+        .map(|arg| format!("        <string>{}</string>\n", escape(arg)))
+        .collect();
+    let when = match frequency {
+        Frequency::Daily => "",
+        Frequency::Weekly => "        <key>Weekday</key><integer>0</integer>\n",
+        Frequency::Monthly => "        <key>Day</key><integer>1</integer>\n",
+    };
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+    <key>StartCalendarInterval</key>
+    <dict>
+{}        <key>Hour</key><integer>3</integer>
+        <key>Minute</key><integer>0</integer>
+    </dict>
+</dict>
+</plist>
+"#,
+        LAUNCHD_LABEL, arguments, when
+    )
+}
@@ -1,47 +1,591 @@
+use crate::cleaner::{self, CleanProgress, CleanResult, Method};
+use crate::desktop;
+use crate::exclude;
+use crate::i18n::{self, tr};
+use crate::logging;
 use crate::projects::ProjectType;
-use crate::scanner::FoundDir;
-use console::{style, Key, Term};
-use std::collections::HashMap;
-use std::io;
+use crate::risk::{Risk, RiskLevel};
+use crate::scanner::{self, FoundDir, ScanEvent};
+use crate::state::SelectorState;
+use crate::units::{self, format_size};
+use clap::ValueEnum;
+use ratatui::Frame;
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::symbols::border;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, Paragraph};
+use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, IsTerminal, Stderr};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Lines reserved below the list: scroll indicator, selection summary, and
+/// instructions.
+const FOOTER_LINES: usize = 3;
+
+/// Items untouched for at least this many days are shown as stale.
+const STALE_DAYS: u64 = 30;
+
+/// Key bindings listed in the help overlay.
+const HELP: &[(&str, &str)] = &[
+    ("↑/k ↓/j", "Move cursor"),
+    ("PgUp PgDn", "Move one page"),
+    ("Home End", "Jump to first / last line"),
+    ("gg G", "Jump to first / last line (or line N with a count)"),
+    ("Ctrl-d Ctrl-u", "Move half a page"),
+    ("<N>j <N>k", "Move N lines"),
+    ("Space", "Toggle item, or whole group on a header"),
+    ("a", "Select all"),
+    ("n A", "Deselect all"),
+    ("i", "Invert selection"),
+    ("b", "Select by age or size (e.g. 30d, 500MB)"),
+    ("/", "Filter paths by text, or by regex written as /pattern/"),
+    ("Tab", "Expand / collapse group"),
+    ("- +", "Collapse / expand all groups"),
+    ("s", "Cycle sort order (size, path, age)"),
+    ("p", "Group by project type / by project"),
+    ("x", "Never clean this path (saved to the exclude file)"),
+    ("X", "Never clean anything in this project"),
+    ("o", "Open in file manager"),
+    ("c", "Copy path to clipboard"),
+    ("v", "Toggle preview of the highlighted directory"),
+    ("w", "Why was this directory matched?"),
+    ("?", "Show this help"),
+    ("Enter", "Review selection and confirm"),
+    ("Esc q", "Cancel (Esc clears an active filter first)"),
+];
+
+/// Entries listed in the preview pane, plus its title and a spacer line.
+const PREVIEW_ENTRIES: usize = 8;
+const PREVIEW_LINES: usize = PREVIEW_ENTRIES + 2;
+
+/// Most recently modified selected items called out on the summary screen.
+const SUMMARY_RECENT: usize = 5;
+
+/// Width in cells of the per-item size bar.
+const BAR_WIDTH: usize = 16;
+
+/// Cells used by everything on an item line except the path: checkbox, git,
+/// editor and ignore markers, risk badge, size, size bar, age, and the
+/// spacing between them.
+const ITEM_FIXED_WIDTH: usize = 5 + 5 + 5 + 14 + BAR_WIDTH + 2 + 13;
+
+/// Cells used by a group header besides its label.
+const HEADER_FIXED_WIDTH: usize = 30;
+
+/// Paths are never squeezed below this, even on very narrow terminals.
+const MIN_PATH_WIDTH: usize = 12;
+
+/// Characters used to draw the selector.
+struct Glyphs {
+    checked: &'static str,
+    unchecked: &'static str,
+    partial: &'static str,
+    collapsed: &'static str,
+    expanded: &'static str,
+    bar_full: &'static str,
+    bar_empty: &'static str,
+    ellipsis: &'static str,
+    up: &'static str,
+    down: &'static str,
+    border: border::Set,
+}
+
+const UNICODE_GLYPHS: Glyphs = Glyphs {
+    checked: "[✓]",
+    unchecked: "[ ]",
+    partial: "[~]",
+    collapsed: "▶",
+    expanded: "▼",
+    bar_full: "█",
+    bar_empty: "░",
+    ellipsis: "…",
+    up: "↑",
+    down: "↓",
+    border: border::PLAIN,
+};
+
+const ASCII_GLYPHS: Glyphs = Glyphs {
+    checked: "[x]",
+    unchecked: "[ ]",
+    partial: "[~]",
+    collapsed: ">",
+    expanded: "v",
+    bar_full: "#",
+    bar_empty: ".",
+    ellipsis: "...",
+    up: "Up",
+    down: "Dn",
+    border: border::Set {
+        top_left: "+",
+        top_right: "+",
+        bottom_left: "+",
+        bottom_right: "+",
+        vertical_left: "|",
+        vertical_right: "|",
+        horizontal_top: "-",
+        horizontal_bottom: "-",
+    },
+};
+
+fn risk_style(level: RiskLevel) -> Style {
+    match level {
+        RiskLevel::Low => Style::new().green(),
+        RiskLevel::Medium => Style::new().yellow(),
+        RiskLevel::High => Style::new().red().bold(),
+    }
+}
+
+/// Whether the locale advertises UTF-8 output. Windows terminals are assumed
+/// to cope.
+pub fn supports_unicode() -> bool {
+    if cfg!(windows) {
+        return true;
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+        .to_ascii_uppercase();
+    locale.contains("UTF-8") || locale.contains("UTF8")
+}
+
+/// Whether the full-screen selector can start: stderr is a terminal that
+/// can be put in raw mode.
+pub fn can_run() -> bool {
+    io::stderr().is_terminal() && terminal::enable_raw_mode().is_ok() && terminal::disable_raw_mode().is_ok()
+}
+
+/// Shorten `text` to at most `width` characters by replacing its middle
+/// with an ellipsis, keeping both the root and the artifact name visible.
+fn truncate_middle(text: &str, width: usize, ellipsis: &str) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        return text.to_string();
+    }
+    let marker = ellipsis.chars().count();
+    if width <= marker {
+        return ellipsis.chars().take(width).collect();
+    }
+    let keep = width - marker;
+    let head = keep / 2;
+    let tail = keep - head;
+    let start: String = text.chars().take(head).collect();
+    let end: String = text.chars().skip(len - tail).collect();
+    format!("{}{}{}", start, ellipsis, end)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// Largest first
+    Size,
+    /// Alphabetical
+    Path,
+    /// Oldest first; unknown ages last
+    Age,
+}
+
+impl SortKey {
+    pub fn compare(self, a: &FoundDir, b: &FoundDir) -> Ordering {
+        match self {
+            SortKey::Size => b.size_bytes.cmp(&a.size_bytes),
+            SortKey::Path => a.path.cmp(&b.path),
+            SortKey::Age => b.age_days().cmp(&a.age_days()),
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            SortKey::Size => SortKey::Path,
+            SortKey::Path => SortKey::Age,
+            SortKey::Age => SortKey::Size,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortKey::Size => "size",
+            SortKey::Path => "path",
+            SortKey::Age => "age",
+        }
+    }
+}
+
+/// Threshold entered with the bulk-select prompt.
+#[derive(Debug, Clone, Copy)]
+enum Criterion {
+    OlderThan(Duration),
+    LargerThan(u64),
+}
+
+impl Criterion {
+    /// Parse `30d`, `>2w`, `500MB`, `>1.5G`, ...
+    fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim().trim_start_matches('>');
+        if let Ok(age) = units::parse_duration(input) {
+            return Ok(Criterion::OlderThan(age));
+        }
+        match units::parse_size(input) {
+            Ok(size) if input.trim_end().ends_with(|c: char| c.is_ascii_alphabetic()) => {
+                Ok(Criterion::LargerThan(size))
+            }
+            _ => Err(format!("'{}' is not an age (30d) or size (500MB)", input)),
+        }
+    }
+
+    fn matches(&self, dir: &FoundDir) -> bool {
+        match *self {
+            Criterion::OlderThan(age) => dir.age().is_some_and(|a| a > age),
+            Criterion::LargerThan(size) => dir.size_bytes > size,
+        }
+    }
+}
+
+/// Narrows the list to items whose path matches.
+enum Filter {
+    /// Case-insensitive substring
+    Text(String),
+    /// Pattern typed between slashes, e.g. `/clients\/(acme|beta)/`
+    Regex(Regex),
+}
+
+impl Filter {
+    /// Parse the filter prompt; an empty input means no filter.
+    fn parse(input: &str) -> Result<Option<Self>, String> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(None);
+        }
+        match input.strip_prefix('/').and_then(|rest| rest.strip_suffix('/')) {
+            Some(pattern) => Regex::new(pattern)
+                .map(|regex| Some(Filter::Regex(regex)))
+                .map_err(|e| format!("Invalid regex: {}", e)),
+            None => Ok(Some(Filter::Text(input.to_string()))),
+        }
+    }
+
+    fn matches(&self, dir: &FoundDir) -> bool {
+        let path = dir.path.display().to_string();
+        match self {
+            Filter::Text(text) => path.to_lowercase().contains(&text.to_lowercase()),
+            Filter::Regex(regex) => regex.is_match(&path),
+        }
+    }
+
+    /// The filter as it would be typed.
+    fn input(&self) -> String {
+        match self {
+            Filter::Text(text) => text.clone(),
+            Filter::Regex(regex) => format!("/{}/", regex.as_str()),
+        }
+    }
+}
+
+/// Text prompt open in the footer.
+enum Prompt {
+    /// Bulk-select by age or size
+    Criterion(String),
+    Filter(String),
+}
+
+/// Where an item is in in-selector deletion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeleteState {
+    Waiting,
+    Deleting,
+    Deleted,
+    Failed(String),
+}
 
 #[derive(Debug, Clone)]
 pub struct GroupedItem {
     pub dir: FoundDir,
     pub selected: bool,
+    /// Size, age and git status have been filled in
+    pub measured: bool,
+    /// The size is known; with `size_on_demand`, not until the item has
+    /// been highlighted or selected
+    pub sized: bool,
+    /// Set once deletion has started
+    pub delete_state: Option<DeleteState>,
+    /// Doesn't match the active filter
+    pub hidden: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// One group per project type (Rust, Node.js, ...)
+    Type,
+    /// One group per project/repository directory
+    Project,
 }
 
 #[derive(Debug)]
 pub struct Group {
-    pub project_type: ProjectType,
+    pub label: String,
     pub items: Vec<GroupedItem>,
     pub collapsed: bool,
 }
 
 impl Group {
+    /// Items not hidden by the filter, with their index in `items`.
+    pub fn visible_items(&self) -> impl Iterator<Item = (usize, &GroupedItem)> {
+        self.items.iter().enumerate().filter(|(_, i)| !i.hidden)
+    }
+
+    /// At least one item matches the filter.
+    pub fn is_visible(&self) -> bool {
+        self.items.iter().any(|i| !i.hidden)
+    }
+
     pub fn total_size(&self) -> u64 {
-        self.items.iter().map(|i| i.dir.size_bytes).sum()
+        self.visible_items().map(|(_, i)| i.dir.size_bytes).sum()
     }
 
     pub fn all_selected(&self) -> bool {
-        self.items.iter().all(|i| i.selected)
+        self.visible_items().all(|(_, i)| i.selected)
     }
 
     pub fn none_selected(&self) -> bool {
-        self.items.iter().all(|i| !i.selected)
+        self.visible_items().all(|(_, i)| !i.selected)
     }
 
     pub fn toggle_all(&mut self) {
         let new_state = !self.all_selected();
-        for item in &mut self.items {
+        for item in self.items.iter_mut().filter(|i| !i.hidden) {
             item.selected = new_state;
         }
     }
+
+    pub fn sort(&mut self, key: SortKey) {
+        self.items.sort_by(|a, b| key.compare(&a.dir, &b.dir));
+    }
+}
+
+/// Label of the group `dir` belongs to under `group_by`.
+fn group_label(dir: &FoundDir, group_by: GroupBy) -> String {
+    match group_by {
+        GroupBy::Type => dir.project_type.name().to_string(),
+        GroupBy::Project => dir.project_root.display().to_string(),
+    }
+}
+
+/// Split items into groups. Type groups follow `ProjectType::all()` order;
+/// project groups are ordered largest first.
+fn build_groups(items: Vec<GroupedItem>, group_by: GroupBy) -> Vec<Group> {
+    let group = |label: String, items: Vec<GroupedItem>| Group {
+        label,
+        items,
+        collapsed: false,
+    };
+
+    match group_by {
+        GroupBy::Type => {
+            let mut by_type: HashMap<ProjectType, Vec<GroupedItem>> = HashMap::new();
+            for item in items {
+                by_type.entry(item.dir.project_type).or_default().push(item);
+            }
+            ProjectType::all()
+                .into_iter()
+                .filter_map(|pt| by_type.remove(&pt).map(|items| group(pt.name().to_string(), items)))
+                .collect()
+        }
+        GroupBy::Project => {
+            let mut by_project: HashMap<PathBuf, Vec<GroupedItem>> = HashMap::new();
+            for item in items {
+                by_project
+                    .entry(item.dir.project_root.clone())
+                    .or_default()
+                    .push(item);
+            }
+            let mut groups: Vec<Group> = by_project
+                .into_iter()
+                .map(|(root, items)| group(root.display().to_string(), items))
+                .collect();
+            groups.sort_by_key(|g| std::cmp::Reverse(g.total_size()));
+            groups
+        }
+    }
+}
+
+/// How the selector starts and looks.
+pub struct SelectorOptions {
+    /// Items start selected (opt-out) rather than unselected (opt-in)
+    pub preselect: bool,
+    /// Draw with plain ASCII instead of Unicode box and check glyphs
+    pub ascii: bool,
+    /// Use colors; modifiers like bold and reverse are kept either way
+    pub color: bool,
+    /// Restore and save per-path selection and collapsed groups
+    pub remember: bool,
+    /// Delete the confirmed selection inside the selector, showing progress,
+    /// instead of leaving that to the caller
+    pub clean: bool,
+    /// How the selector removes directories when `clean` is set
+    pub method: Method,
+    /// Confirming a large or unusual deletion takes typing [`CONFIRM_WORD`],
+    /// whether the selector or the caller deletes it
+    pub ask_word: bool,
+    /// Select exactly these paths to start with, ignoring `preselect` and
+    /// remembered choices
+    pub preselected: Option<Vec<PathBuf>>,
+    /// Confirming a selection larger than this takes an extra key
+    pub max_delete: Option<u64>,
+    /// Items arrive unsized; size the highlighted and selected ones on a
+    /// worker thread
+    pub size_on_demand: bool,
+    /// Items sized on demand and found modified more recently than this are
+    /// dropped, as they would have been had they been sized while scanning
+    pub older_than: Option<Duration>,
+    /// Confirming a deletion past these limits takes typing [`CONFIRM_WORD`]
+    pub large: LargeDelete,
+}
+
+/// What has to be typed to confirm a large deletion.
+pub const CONFIRM_WORD: &str = "delete";
+
+/// Limits past which a deletion is large enough to confirm by typing
+/// [`CONFIRM_WORD`] rather than pressing a key.
+#[derive(Debug, Clone, Copy)]
+pub struct LargeDelete {
+    pub size: u64,
+    pub count: usize,
+}
+
+impl LargeDelete {
+    pub fn exceeded(&self, count: usize, size: u64) -> bool {
+        size > self.size || count > self.count
+    }
 }
 
+/// How the selector finished.
+pub enum Selection {
+    /// Confirmed items, left for the caller to delete
+    Pending(Vec<FoundDir>),
+    /// Confirmed items, already deleted by the selector
+    Cleaned(CleanResult),
+    /// The user backed out without confirming
+    Cancelled,
+}
+
+/// Deletion running on a worker thread while the selector shows progress.
+struct Deletion {
+    events: Receiver<(PathBuf, DeleteState)>,
+    worker: Option<JoinHandle<CleanResult>>,
+    result: Option<CleanResult>,
+}
+
+/// Sizing on a worker thread, the highlighted item ahead of selected ones.
+struct Sizer {
+    highlighted: Sender<FoundDir>,
+    selected: Sender<FoundDir>,
+    sized: Receiver<FoundDir>,
+    /// Sent off and not back yet
+    pending: HashSet<PathBuf>,
+}
+
+impl Sizer {
+    fn start() -> Sizer {
+        let (highlighted, urgent) = mpsc::channel::<FoundDir>();
+        let (selected, queued) = mpsc::channel::<FoundDir>();
+        let (results, sized) = mpsc::channel();
+        thread::spawn(move || {
+            loop {
+                let mut dir = match urgent.try_recv() {
+                    Ok(dir) => dir,
+                    Err(TryRecvError::Disconnected) => break,
+                    Err(TryRecvError::Empty) => match queued.recv_timeout(TICK) {
+                        Ok(dir) => dir,
+                        Err(RecvTimeoutError::Timeout) => continue,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    },
+                };
+                scanner::measure_size(&mut dir);
+                if results.send(dir).is_err() {
+                    break;
+                }
+            }
+        });
+        Sizer {
+            highlighted,
+            selected,
+            sized,
+            pending: HashSet::new(),
+        }
+    }
+}
+
+/// Refresh interval while waiting for keys, so streamed scan results show up.
+const TICK: Duration = Duration::from_millis(100);
+
 pub struct GroupedSelector {
     groups: Vec<Group>,
     cursor: usize,
+    scroll: usize,
+    sort: SortKey,
+    group_by: GroupBy,
+    show_help: bool,
+    /// Showing the final summary before returning the selection.
+    confirming: bool,
+    show_preview: bool,
+    preview_cache: HashMap<PathBuf, Vec<(PathBuf, u64)>>,
+    /// Pending vim count prefix, e.g. the 5 in `5j`.
+    count: Option<usize>,
+    /// First `g` of `gg` has been pressed.
+    pending_g: bool,
+    /// Text being typed into the footer prompt, when one is open.
+    prompt: Option<Prompt>,
+    /// Only items matching this are shown and affected by bulk selection.
+    filter: Option<Filter>,
+    /// One-off message shown in the footer until the next key press.
+    status: Option<String>,
+    /// Height of the list area in the last frame, used for paging.
+    page_height: usize,
+    /// Width of the list area in the last frame, used to fit paths.
+    page_width: usize,
     max_path_len: usize,
+    max_size: u64,
+    glyphs: &'static Glyphs,
+    color: bool,
+    preselect: bool,
+    preselected: Option<Vec<PathBuf>>,
+    remember: bool,
+    /// Choices from previous runs, applied to items as they arrive
+    remembered: SelectorState,
+    /// A streaming scan is still delivering results
+    scanning: bool,
+    /// Items received so far, including any later excluded
+    found: usize,
+    /// Excluded from here on; streamed items under these are left out
+    excluded: Vec<PathBuf>,
+    clean: bool,
+    method: Method,
+    ask_word: bool,
+    max_delete: Option<u64>,
+    large: LargeDelete,
+    /// Typed so far towards the confirmation word
+    typed: String,
+    deletion: Option<Deletion>,
+    /// With `size_on_demand`
+    sizer: Option<Sizer>,
+    older_than: Option<Duration>,
+}
+
+/// What the cursor is on, independent of line numbers.
+enum Anchor {
+    Group(String),
+    Item(PathBuf),
 }
 
 enum CursorPosition {
@@ -50,52 +594,299 @@ enum CursorPosition {
 }
 
 impl GroupedSelector {
-    pub fn new(found: Vec<FoundDir>) -> Self {
-        let mut by_type: HashMap<ProjectType, Vec<FoundDir>> = HashMap::new();
+    pub fn new(found: Vec<FoundDir>, options: SelectorOptions) -> Self {
+        let remembered = if options.remember {
+            SelectorState::load()
+        } else {
+            SelectorState::default()
+        };
 
-        for dir in found {
-            by_type.entry(dir.project_type).or_default().push(dir);
+        let mut selector = Self {
+            groups: Vec::new(),
+            cursor: 0,
+            scroll: 0,
+            sort: SortKey::Size,
+            group_by: GroupBy::Type,
+            show_help: false,
+            confirming: false,
+            show_preview: false,
+            preview_cache: HashMap::new(),
+            count: None,
+            pending_g: false,
+            prompt: None,
+            filter: None,
+            status: None,
+            page_height: 1,
+            page_width: 80,
+            max_path_len: 0,
+            max_size: 0,
+            glyphs: if options.ascii {
+                &ASCII_GLYPHS
+            } else {
+                &UNICODE_GLYPHS
+            },
+            color: options.color,
+            preselect: options.preselect,
+            preselected: options.preselected,
+            remember: options.remember,
+            remembered,
+            scanning: false,
+            found: found.len(),
+            excluded: Vec::new(),
+            clean: options.clean,
+            method: options.method,
+            ask_word: options.ask_word,
+            max_delete: options.max_delete,
+            large: options.large,
+            typed: String::new(),
+            deletion: None,
+            sizer: options.size_on_demand.then(Sizer::start),
+            older_than: options.older_than,
+        };
+
+        let items = found
+            .into_iter()
+            .map(|dir| selector.new_item(dir, true))
+            .collect();
+        selector.groups = build_groups(items, GroupBy::Type);
+        for group in &mut selector.groups {
+            group.collapsed = selector.remembered.collapsed.contains(&group.label);
+        }
+
+        selector
+    }
+
+    /// Wrap a found directory, restoring its remembered selection.
+    fn new_item(&mut self, dir: FoundDir, measured: bool) -> GroupedItem {
+        self.max_path_len = self
+            .max_path_len
+            .max(dir.path.display().to_string().chars().count());
+        self.max_size = self.max_size.max(dir.size_bytes);
+        let selected = match &self.preselected {
+            Some(paths) => paths.contains(&dir.path),
+            None if !dir.preselected() => false,
+            None => self
+                .remembered
+                .selected
+                .get(&dir.path)
+                .copied()
+                .unwrap_or(self.preselect),
+        };
+        let hidden = self.filter.as_ref().is_some_and(|f| !f.matches(&dir));
+        GroupedItem {
+            dir,
+            selected,
+            measured,
+            sized: measured && self.sizer.is_none(),
+            delete_state: None,
+            hidden,
         }
+    }
 
-        let max_path_len = by_type
-            .values()
-            .flat_map(|v| v.iter())
-            .map(|d| d.path.display().to_string().len())
-            .max()
-            .unwrap_or(50);
+    fn cursor_anchor(&self) -> Option<Anchor> {
+        if self.groups.is_empty() {
+            return None;
+        }
+        Some(match self.cursor_position() {
+            CursorPosition::GroupHeader(gi) => Anchor::Group(self.groups[gi].label.clone()),
+            CursorPosition::Item(gi, ii) => Anchor::Item(self.groups[gi].items[ii].dir.path.clone()),
+        })
+    }
 
-        let type_order = ProjectType::all();
-        let mut groups: Vec<Group> = Vec::new();
+    /// Move the cursor back onto what it was on before the list changed.
+    fn restore_anchor(&mut self, anchor: Option<Anchor>) {
+        let found = self.groups.iter().enumerate().find_map(|(gi, g)| match &anchor {
+            Some(Anchor::Group(label)) if &g.label == label => Some((gi, None)),
+            Some(Anchor::Item(path)) => g
+                .items
+                .iter()
+                .position(|i| &i.dir.path == path)
+                .map(|ii| (gi, Some(ii))),
+            _ => None,
+        });
+        if let Some((gi, ii)) = found {
+            self.cursor = self.line_of(gi, ii);
+        }
+    }
 
-        for pt in type_order {
-            if let Some(dirs) = by_type.remove(&pt) {
-                let items = dirs
-                    .into_iter()
-                    .map(|dir| GroupedItem { dir, selected: true })
-                    .collect();
-                groups.push(Group {
-                    project_type: pt,
-                    items,
-                    collapsed: false,
-                });
+    /// Add a directory reported by a streaming scan.
+    fn insert(&mut self, dir: FoundDir) {
+        self.found += 1;
+        if self.excluded.iter().any(|target| dir.path.starts_with(target)) {
+            return;
+        }
+        let anchor = self.cursor_anchor();
+
+        let label = group_label(&dir, self.group_by);
+        let item = self.new_item(dir, false);
+        match self.groups.iter_mut().find(|g| g.label == label) {
+            Some(group) => group.items.push(item),
+            None => {
+                let group = Group {
+                    collapsed: self.remembered.collapsed.contains(&label),
+                    label,
+                    items: vec![item],
+                };
+                // Keep type groups in their usual order
+                let position = match self.group_by {
+                    GroupBy::Type => {
+                        let rank = |label: &str| {
+                            ProjectType::all().iter().position(|pt| pt.name() == label)
+                        };
+                        let new_rank = rank(&group.label);
+                        self.groups
+                            .iter()
+                            .position(|g| rank(&g.label) > new_rank)
+                            .unwrap_or(self.groups.len())
+                    }
+                    GroupBy::Project => self.groups.len(),
+                };
+                self.groups.insert(position, group);
             }
         }
 
-        Self {
-            groups,
-            cursor: 0,
-            max_path_len,
+        self.restore_anchor(anchor);
+    }
+
+    /// Replace a streamed item's placeholder with its measured version.
+    fn update_measured(&mut self, mut dir: FoundDir) {
+        self.max_size = self.max_size.max(dir.size_bytes);
+        let on_demand = self.sizer.is_some();
+        if let Some(item) = self
+            .groups
+            .iter_mut()
+            .flat_map(|g| g.items.iter_mut())
+            .find(|i| i.dir.path == dir.path)
+        {
+            // Sized on demand before the scan got to it
+            if item.sized {
+                dir.size_bytes = item.dir.size_bytes;
+                dir.modified = item.dir.modified;
+                dir.suspicious = item.dir.suspicious.take();
+            }
+            item.dir = dir;
+            item.measured = true;
+            item.sized |= !on_demand;
+        }
+    }
+
+    /// Send the highlighted item, then every selected one, off to be sized,
+    /// unless they are or will be already.
+    fn request_sizes(&mut self) {
+        let highlighted = match self.cursor_position() {
+            CursorPosition::Item(gi, ii) if !self.groups.is_empty() => Some(&self.groups[gi].items[ii]),
+            _ => None,
+        };
+        let Some(sizer) = self.sizer.as_mut() else {
+            return;
+        };
+        let wanted = highlighted
+            .into_iter()
+            .map(|item| (item, &sizer.highlighted))
+            .chain(
+                self.groups
+                    .iter()
+                    .flat_map(|g| g.items.iter())
+                    .filter(|i| i.selected)
+                    .map(|item| (item, &sizer.selected)),
+            );
+        for (item, queue) in wanted {
+            if !item.sized && !sizer.pending.contains(&item.dir.path) && queue.send(item.dir.clone()).is_ok() {
+                sizer.pending.insert(item.dir.path.clone());
+            }
+        }
+    }
+
+    /// Fill in sizes the worker has measured since the last poll.
+    fn poll_sizes(&mut self) {
+        let Some(sizer) = self.sizer.as_mut() else {
+            return;
+        };
+        let mut too_recent = Vec::new();
+        while let Ok(dir) = sizer.sized.try_recv() {
+            sizer.pending.remove(&dir.path);
+            // Something inside changed since, which the directory's own
+            // modification time didn't show
+            if scanner::too_recent(&dir, self.older_than) {
+                too_recent.push(dir.path);
+                continue;
+            }
+            self.max_size = self.max_size.max(dir.size_bytes);
+            if let Some(item) = self
+                .groups
+                .iter_mut()
+                .flat_map(|g| g.items.iter_mut())
+                .find(|i| i.dir.path == dir.path)
+            {
+                item.dir.size_bytes = dir.size_bytes;
+                item.dir.modified = dir.modified;
+                item.dir.suspicious = dir.suspicious;
+                item.sized = true;
+            }
+        }
+        if !too_recent.is_empty() {
+            self.drop_items(|item| too_recent.contains(&item.dir.path));
         }
     }
 
+    /// Take the items `gone` matches off the list, keeping the cursor where
+    /// it was if its item stays.
+    fn drop_items(&mut self, gone: impl Fn(&GroupedItem) -> bool) {
+        let anchor = self.cursor_anchor();
+        for group in &mut self.groups {
+            group.items.retain(|i| !gone(i));
+        }
+        self.groups.retain(|g| !g.items.is_empty());
+        if self.filter.is_some() && !self.groups.iter().any(|g| g.is_visible()) {
+            self.set_filter(None);
+        }
+        self.cursor = self.cursor.min(self.total_lines().saturating_sub(1));
+        self.restore_anchor(anchor);
+    }
+
+    /// Selected items whose size isn't known yet.
+    fn unsized_selected(&self) -> usize {
+        self.groups
+            .iter()
+            .flat_map(|g| g.items.iter())
+            .filter(|i| i.selected && !i.sized)
+            .count()
+    }
+
+    fn apply_scan_event(&mut self, event: ScanEvent) {
+        match event {
+            ScanEvent::Found(dir) => self.insert(dir),
+            ScanEvent::Sized(dir) => self.update_measured(dir),
+            ScanEvent::Unwanted(dir) => {
+                self.found = self.found.saturating_sub(1);
+                self.drop_items(|item| item.dir.path == dir.path);
+            }
+            ScanEvent::Done(_) => self.finish_scan(),
+        }
+    }
+
+    /// Sizes are all known now, so put everything in its proper order.
+    fn finish_scan(&mut self) {
+        self.scanning = false;
+        let anchor = self.cursor_anchor();
+        for group in &mut self.groups {
+            group.sort(self.sort);
+        }
+        if self.group_by == GroupBy::Project {
+            self.groups.sort_by_key(|g| std::cmp::Reverse(g.total_size()));
+        }
+        self.restore_anchor(anchor);
+    }
+
     fn total_lines(&self) -> usize {
         self.groups
             .iter()
+            .filter(|g| g.is_visible())
             .map(|g| {
                 if g.collapsed {
                     1
                 } else {
-                    1 + g.items.len()
+                    1 + g.visible_items().count()
                 }
             })
             .sum()
@@ -104,12 +895,15 @@ impl GroupedSelector {
     fn cursor_position(&self) -> CursorPosition {
         let mut line = 0;
         for (gi, group) in self.groups.iter().enumerate() {
+            if !group.is_visible() {
+                continue;
+            }
             if line == self.cursor {
                 return CursorPosition::GroupHeader(gi);
             }
             line += 1;
             if !group.collapsed {
-                for ii in 0..group.items.len() {
+                for (ii, _) in group.visible_items() {
                     if line == self.cursor {
                         return CursorPosition::Item(gi, ii);
                     }
@@ -120,99 +914,669 @@ impl GroupedSelector {
         CursorPosition::GroupHeader(0)
     }
 
-    fn format_size(bytes: u64) -> String {
-        const KB: u64 = 1024;
-        const MB: u64 = KB * 1024;
-        const GB: u64 = MB * 1024;
+    /// Line index of a group header (`item == None`) or of an item in it.
+    fn line_of(&self, group: usize, item: Option<usize>) -> usize {
+        let mut line = 0;
+        for g in self.groups[..group].iter().filter(|g| g.is_visible()) {
+            line += 1;
+            if !g.collapsed {
+                line += g.visible_items().count();
+            }
+        }
+        match item {
+            Some(ii) if !self.groups[group].collapsed => {
+                line + 1 + self.groups[group].visible_items().take_while(|(i, _)| *i < ii).count()
+            }
+            _ => line,
+        }
+    }
+
+    /// Number and total size of the currently selected items.
+    fn selection_summary(&self) -> (usize, u64) {
+        self.groups
+            .iter()
+            .flat_map(|g| g.items.iter())
+            .filter(|i| i.selected)
+            .fold((0, 0), |(count, size), i| (count + 1, size + i.dir.size_bytes))
+    }
 
-        if bytes >= GB {
-            format!("{:.1} GB", bytes as f64 / GB as f64)
-        } else if bytes >= MB {
-            format!("{:.1} MB", bytes as f64 / MB as f64)
-        } else if bytes >= KB {
-            format!("{:.1} KB", bytes as f64 / KB as f64)
+    /// Bar proportional to `bytes` relative to the largest item.
+    fn size_bar(&self, bytes: u64) -> String {
+        let filled = if self.max_size == 0 {
+            0
         } else {
-            format!("{} B", bytes)
+            ((bytes as f64 / self.max_size as f64) * BAR_WIDTH as f64).round() as usize
+        };
+        // Anything non-empty gets at least one cell so it doesn't look like zero
+        let filled = if bytes > 0 { filled.clamp(1, BAR_WIDTH) } else { 0 };
+        format!(
+            "{}{}",
+            self.glyphs.bar_full.repeat(filled),
+            self.glyphs.bar_empty.repeat(BAR_WIDTH - filled)
+        )
+    }
+
+    /// Adjust the scroll offset so the cursor stays inside the viewport.
+    fn scroll_to_cursor(&mut self, height: usize) {
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + height {
+            self.scroll = self.cursor + 1 - height;
         }
+        let max_scroll = self.total_lines().saturating_sub(height);
+        self.scroll = self.scroll.min(max_scroll);
     }
 
-    fn render(&self, term: &Term) -> io::Result<()> {
-        let mut output = String::new();
+    fn list_lines(&self) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+        let cursor = self.cursor_position();
+        let path_width = self
+            .max_path_len
+            .min(self.page_width.saturating_sub(ITEM_FIXED_WIDTH))
+            .max(MIN_PATH_WIDTH);
+        let label_width = self
+            .page_width
+            .saturating_sub(HEADER_FIXED_WIDTH)
+            .max(MIN_PATH_WIDTH);
 
         for (gi, group) in self.groups.iter().enumerate() {
-            let is_group_cursor = matches!(self.cursor_position(), CursorPosition::GroupHeader(i) if i == gi);
+            if !group.is_visible() {
+                continue;
+            }
+            let is_group_cursor = matches!(cursor, CursorPosition::GroupHeader(i) if i == gi);
 
             // Group header
             let checkbox = if group.all_selected() {
-                style("[✓]").green()
+                Span::styled(self.glyphs.checked, Style::new().green())
             } else if group.none_selected() {
-                style("[ ]").dim()
+                Span::styled(self.glyphs.unchecked, Style::new().dim())
             } else {
-                style("[~]").yellow()
+                Span::styled(self.glyphs.partial, Style::new().yellow())
             };
 
-            let collapse_indicator = if group.collapsed { "▶" } else { "▼" };
+            let collapse_indicator = if group.collapsed {
+                self.glyphs.collapsed
+            } else {
+                self.glyphs.expanded
+            };
 
-            let header = format!(
-                "{} {} {} ({} items, {})",
+            let header = Line::from(vec![
                 checkbox,
-                collapse_indicator,
-                group.project_type.name(),
-                group.items.len(),
-                Self::format_size(group.total_size())
-            );
+                Span::raw(format!(
+                    " {} {} ({} items, {})",
+                    collapse_indicator,
+                    truncate_middle(&group.label, label_width, self.glyphs.ellipsis),
+                    group.visible_items().count(),
+                    format_size(group.total_size())
+                )),
+            ]);
 
             if is_group_cursor {
-                output.push_str(&format!("{}\n", style(header).reverse()));
+                lines.push(header.reversed());
             } else {
-                output.push_str(&format!("{}\n", style(header).bold()));
+                lines.push(header.bold());
             }
 
             // Items (if not collapsed)
             if !group.collapsed {
-                for (ii, item) in group.items.iter().enumerate() {
+                for (ii, item) in group.visible_items() {
                     let is_item_cursor =
-                        matches!(self.cursor_position(), CursorPosition::Item(g, i) if g == gi && i == ii);
+                        matches!(cursor, CursorPosition::Item(g, i) if g == gi && i == ii);
 
                     let checkbox = if item.selected {
-                        style("  [✓]").green()
+                        Span::styled(format!("  {}", self.glyphs.checked), Style::new().green())
                     } else {
-                        style("  [ ]").dim()
+                        Span::styled(format!("  {}", self.glyphs.unchecked), Style::new().dim())
                     };
 
-                    let path_str = item.dir.path.display().to_string();
-                    let size_str = item.dir.size_human();
+                    let (size, bar, age) = if item.measured && item.sized {
+                        (
+                            item.dir.size_human(),
+                            self.size_bar(item.dir.size_bytes),
+                            item.dir.age_human(),
+                        )
+                    } else {
+                        (self.glyphs.ellipsis.to_string(), " ".repeat(BAR_WIDTH), String::new())
+                    };
+                    let age_style = match item.dir.age_days() {
+                        Some(0) => Style::new().red(),
+                        Some(days) if days >= STALE_DAYS => Style::new().green(),
+                        Some(_) => Style::new().yellow(),
+                        None => Style::new().dim(),
+                    };
 
-                    let line = format!(
-                        "{} {:<width$}  {:>10}",
-                        checkbox,
-                        path_str,
-                        size_str,
-                        width = self.max_path_len
+                    // Hint that the owning repository is in active use
+                    let git = item.dir.git.unwrap_or_default();
+                    let git_marker = format!(
+                        "{}{}{}",
+                        if git.dirty { '*' } else { ' ' },
+                        if git.stashed { '$' } else { ' ' },
+                        if item.dir.editor.is_some() { '@' } else { ' ' }
                     );
 
+                    let risk = if item.measured && item.sized {
+                        let level = Risk::of(&item.dir).level();
+                        Span::styled(format!(" {:<4}", level.badge()), risk_style(level))
+                    } else {
+                        Span::raw("     ")
+                    };
+
+                    let bar = match &item.delete_state {
+                        None => Span::styled(bar, Style::new().cyan()),
+                        Some(state) => {
+                            let (label, style) = match state {
+                                DeleteState::Waiting => ("waiting".to_string(), Style::new().dim()),
+                                DeleteState::Deleting => {
+                                    (format!("deleting{}", self.glyphs.ellipsis), Style::new().yellow())
+                                }
+                                DeleteState::Deleted => ("deleted".to_string(), Style::new().green()),
+                                DeleteState::Failed(e) => (format!("failed: {}", e), Style::new().red()),
+                            };
+                            Span::styled(format!("{:<width$.width$}", label, width = BAR_WIDTH), style)
+                        }
+                    };
+
+                    // An unignored build directory may hold hand-written files
+                    let unignored = Span::styled(
+                        if item.dir.ignored == Some(false) { "!" } else { " " },
+                        Style::new().red().bold(),
+                    );
+
+                    let line = Line::from(vec![
+                        checkbox,
+                        Span::styled(format!(" {}", git_marker), Style::new().yellow().bold()),
+                        unignored,
+                        risk,
+                        Span::raw(format!(
+                            " {:<width$}  {:>10} ",
+                            truncate_middle(
+                                &item.dir.path.display().to_string(),
+                                path_width,
+                                self.glyphs.ellipsis
+                            ),
+                            size,
+                            width = path_width
+                        )),
+                        bar,
+                        Span::raw("  "),
+                        Span::styled(format!("{:>13}", age), age_style),
+                    ]);
+                    let line = if item.delete_state == Some(DeleteState::Deleted) {
+                        line.crossed_out().dim()
+                    } else {
+                        line
+                    };
+
                     if is_item_cursor {
-                        output.push_str(&format!("{}\n", style(line).reverse()));
+                        lines.push(line.reversed());
                     } else {
-                        output.push_str(&format!("{}\n", line));
+                        lines.push(line);
                     }
                 }
             }
         }
 
+        lines
+    }
+
+    /// Largest entries of the highlighted item, computed once per path.
+    fn preview_lines(&mut self) -> Vec<Line<'static>> {
+        let CursorPosition::Item(gi, ii) = self.cursor_position() else {
+            return vec![Line::styled(
+                "Preview: move to an item to see its contents",
+                Style::new().dim(),
+            )];
+        };
+        let path = self.groups[gi].items[ii].dir.path.clone();
+        let entries = self
+            .preview_cache
+            .entry(path.clone())
+            .or_insert_with(|| scanner::largest_entries(&path, PREVIEW_ENTRIES));
+
+        let risk = Risk::of(&self.groups[gi].items[ii].dir);
+        let mut title = vec![
+            Span::styled("Preview: ", Style::new().bold()),
+            Span::raw(path.display().to_string()),
+        ];
+        if risk.level() != RiskLevel::Low {
+            title.push(Span::styled(
+                format!("  {} risk: {}", risk.level().badge(), risk.reasons().join(", ")),
+                risk_style(risk.level()),
+            ));
+        }
+        let mut lines = vec![Line::from(title)];
+        if entries.is_empty() {
+            lines.push(Line::styled("  (empty or unreadable)", Style::new().dim()));
+        }
+        for (entry, size) in entries.iter() {
+            let name = entry.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+            lines.push(Line::raw(format!("  {:>10}  {}", format_size(*size), name)));
+        }
+        lines
+    }
+
+    fn footer_lines(&self, total: usize, height: usize) -> Vec<Line<'static>> {
+        let key = |k: &str| Span::styled(k.to_string(), Style::new().cyan());
+
+        if let Some(deletion) = &self.deletion {
+            return self.deletion_footer(deletion);
+        }
+
+        // Prompt, status message, or the scroll position when the list doesn't fit
+        let position = if let Some(prompt) = &self.prompt {
+            let (label, input) = match prompt {
+                Prompt::Criterion(input) => (tr!("prompt-criterion"), input),
+                Prompt::Filter(input) => (tr!("prompt-filter"), input),
+            };
+            Line::from(vec![
+                Span::styled(label, Style::new().cyan()),
+                Span::raw(format!("{}_", input)),
+            ])
+        } else if let Some(status) = &self.status {
+            Line::styled(status.clone(), Style::new().yellow())
+        } else if self.scanning {
+            let measured = self
+                .groups
+                .iter()
+                .flat_map(|g| g.items.iter())
+                .filter(|i| i.measured)
+                .count();
+            Line::styled(
+                format!("Scanning{} {} found, {} sized", self.glyphs.ellipsis, self.found, measured),
+                Style::new().cyan(),
+            )
+        } else if let Some(sizer) = self.sizer.as_ref().filter(|s| !s.pending.is_empty()) {
+            Line::styled(
+                format!("Sizing{} {} to go", self.glyphs.ellipsis, sizer.pending.len()),
+                Style::new().cyan(),
+            )
+        } else if let Some(filter) = &self.filter {
+            let items = self.groups.iter().flat_map(|g| g.items.iter());
+            let shown = items.clone().filter(|i| !i.hidden).count();
+            Line::from(vec![
+                Span::styled("Filter: ", Style::new().cyan()),
+                Span::raw(filter.input()),
+                Span::styled(
+                    format!("  ({} of {} items, Esc clears)", shown, items.count()),
+                    Style::new().dim(),
+                ),
+            ])
+        } else if total > height {
+            Line::styled(
+                format!(
+                    "-- {}-{} of {} --",
+                    self.scroll + 1,
+                    (self.scroll + height).min(total),
+                    total
+                ),
+                Style::new().dim(),
+            )
+        } else {
+            Line::raw("")
+        };
+
+        // Selection summary
+        let (count, size) = self.selection_summary();
+        let summary = message_line(
+            "selector-summary",
+            vec![
+                ("count", Span::styled(count.to_string(), Style::new().green().bold())),
+                ("size", Span::styled(format_size(size), Style::new().green().bold())),
+            ],
+        );
+
         // Instructions
-        output.push_str(&format!(
-            "\n{} navigate  {} toggle  {} expand/collapse  {} confirm\n",
-            style("↑↓").cyan(),
-            style("Space").cyan(),
-            style("Tab").cyan(),
-            style("Enter").cyan()
+        let instructions = message_line(
+            "selector-instructions",
+            vec![
+                ("nav", key(&format!("{}{}", self.glyphs.up, self.glyphs.down))),
+                ("toggle", key("Space")),
+                ("expand", key("Tab")),
+                ("sort", key("s")),
+                ("order", Span::raw(self.sort.label())),
+                ("confirm", key("Enter")),
+                ("help", key("?")),
+            ],
+        );
+
+        vec![position, summary, instructions]
+    }
+
+    fn deletion_footer(&self, deletion: &Deletion) -> Vec<Line<'static>> {
+        let items: Vec<&GroupedItem> = self.groups.iter().flat_map(|g| g.items.iter()).collect();
+        let finished = items
+            .iter()
+            .filter(|i| matches!(i.delete_state, Some(DeleteState::Deleted | DeleteState::Failed(_))))
+            .count();
+        let freed: u64 = items
+            .iter()
+            .filter(|i| i.delete_state == Some(DeleteState::Deleted))
+            .map(|i| i.dir.size_bytes)
+            .sum();
+
+        let progress = Line::from(vec![
+            Span::raw(format!("Deleted {}/{}, ", finished, items.len())),
+            Span::styled(format_size(freed), Style::new().green().bold()),
+            Span::raw(" freed"),
+        ]);
+        let hint = if deletion.result.is_some() {
+            Line::styled("Done. Press any key to exit", Style::new().cyan())
+        } else {
+            Line::styled(format!("Deleting{}", self.glyphs.ellipsis), Style::new().yellow())
+        };
+        vec![Line::raw(""), progress, hint]
+    }
+
+    fn draw_help(&self, frame: &mut Frame) {
+        let mut lines = Vec::new();
+        for (keys, description) in HELP {
+            let keys = keys
+                .replace('↑', self.glyphs.up)
+                .replace('↓', self.glyphs.down);
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<14}", keys), Style::new().cyan()),
+                Span::raw(format!(" {}", description)),
+            ]));
+        }
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            "  * uncommitted changes   $ stashes in the owning repository",
+            Style::new().yellow(),
+        ));
+        lines.push(Line::styled("  @ open in an editor or language server", Style::new().yellow()));
+        lines.push(Line::styled(
+            "  ! not gitignored, so it may hold tracked files",
+            Style::new().red(),
+        ));
+        lines.push(Line::raw(""));
+        lines.push(Line::styled("  Press any key to return", Style::new().dim()));
+
+        let area = frame.area();
+        let width = 66.min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::bordered()
+                    .border_set(self.glyphs.border)
+                    .title(" Keyboard shortcuts "),
+            ),
+            popup,
+        );
+    }
+
+    fn summary_lines(&self) -> Vec<Line<'static>> {
+        let selected: Vec<&FoundDir> = self
+            .groups
+            .iter()
+            .flat_map(|g| g.items.iter())
+            .filter(|i| i.selected)
+            .map(|i| &i.dir)
+            .collect();
+        let (count, size) = self.selection_summary();
+
+        let mut lines = vec![
+            message_line(
+                match self.method {
+                    Method::Delete => "confirm-delete",
+                    Method::Trash => "confirm-trash",
+                },
+                vec![
+                    ("count", Span::styled(count.to_string(), Style::new().green().bold())),
+                    ("size", Span::styled(format_size(size), Style::new().green().bold())),
+                ],
+            ),
+            Line::raw(""),
+        ];
+
+        for pt in ProjectType::all() {
+            let dirs: Vec<&&FoundDir> = selected.iter().filter(|d| d.project_type == pt).collect();
+            if dirs.is_empty() {
+                continue;
+            }
+            let group_size: u64 = dirs.iter().map(|d| d.size_bytes).sum();
+            lines.push(Line::raw(format!(
+                "  {:<16} {:>4} items  {:>10}",
+                pt.name(),
+                dirs.len(),
+                format_size(group_size)
+            )));
+        }
+
+        let risky = selected
+            .iter()
+            .filter(|d| Risk::of(d).level() == RiskLevel::High)
+            .count();
+        if risky > 0 {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled(
+                format!("{} high-risk directories selected (press v in the list to see why)", risky),
+                risk_style(RiskLevel::High),
+            ));
+        }
+
+        // Unignored directories may be hand-written content that merely
+        // shares a build directory's name
+        let unignored: Vec<&&FoundDir> = selected.iter().filter(|d| d.ignored == Some(false)).collect();
+        if !unignored.is_empty() {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled(
+                "Not gitignored, so they may hold tracked files:",
+                Style::new().red().bold(),
+            ));
+            for dir in unignored.iter().take(SUMMARY_RECENT) {
+                lines.push(Line::styled(format!("  {}", dir.path.display()), Style::new().red()));
+            }
+            if unignored.len() > SUMMARY_RECENT {
+                lines.push(Line::styled(
+                    format!("  ... and {} more", unignored.len() - SUMMARY_RECENT),
+                    Style::new().dim(),
+                ));
+            }
+        }
+
+        let open: Vec<&&FoundDir> = selected.iter().filter(|d| d.editor.is_some()).collect();
+        if !open.is_empty() {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled("Open in an editor:", Style::new().yellow().bold()));
+            for dir in open.iter().take(SUMMARY_RECENT) {
+                lines.push(Line::styled(
+                    format!("  {}  ({})", dir.path.display(), dir.editor.unwrap_or_default()),
+                    Style::new().yellow(),
+                ));
+            }
+            if open.len() > SUMMARY_RECENT {
+                lines.push(Line::styled(
+                    format!("  ... and {} more", open.len() - SUMMARY_RECENT),
+                    Style::new().dim(),
+                ));
+            }
+        }
+
+        // A live container loses files from under it
+        let mounted: Vec<&&FoundDir> = selected.iter().filter(|d| d.container.is_some()).collect();
+        if !mounted.is_empty() {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled("Mounted into a running container:", Style::new().red().bold()));
+            for dir in mounted.iter().take(SUMMARY_RECENT) {
+                lines.push(Line::styled(
+                    format!("  {}  ({})", dir.path.display(), dir.container.as_deref().unwrap_or_default()),
+                    Style::new().red(),
+                ));
+            }
+            if mounted.len() > SUMMARY_RECENT {
+                lines.push(Line::styled(
+                    format!("  ... and {} more", mounted.len() - SUMMARY_RECENT),
+                    Style::new().dim(),
+                ));
+            }
+        }
+
+        let suspicious: Vec<&&FoundDir> = selected.iter().filter(|d| d.suspicious.is_some()).collect();
+        if !suspicious.is_empty() {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled(
+                "Holding files that don't look like build output:",
+                Style::new().red().bold(),
+            ));
+            for dir in suspicious.iter().take(SUMMARY_RECENT) {
+                let file = dir.suspicious.as_deref().unwrap_or(&dir.path);
+                lines.push(Line::styled(format!("  {}", file.display()), Style::new().red()));
+            }
+            if suspicious.len() > SUMMARY_RECENT {
+                lines.push(Line::styled(
+                    format!("  ... and {} more", suspicious.len() - SUMMARY_RECENT),
+                    Style::new().dim(),
+                ));
+            }
+        }
+
+        // Recently touched directories are the likeliest to still be in use
+        let mut recent: Vec<&&FoundDir> = selected
+            .iter()
+            .filter(|d| d.age_days().is_some_and(|days| days < STALE_DAYS))
+            .collect();
+        recent.sort_by_key(|d| d.age_days());
+        if !recent.is_empty() {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled("Recently modified:", Style::new().yellow().bold()));
+            for dir in recent.iter().take(SUMMARY_RECENT) {
+                let style = if dir.age_days() == Some(0) {
+                    Style::new().red()
+                } else {
+                    Style::new().yellow()
+                };
+                lines.push(Line::styled(
+                    format!("  {}  ({})", dir.path.display(), dir.age_human()),
+                    style,
+                ));
+            }
+            if recent.len() > SUMMARY_RECENT {
+                lines.push(Line::styled(
+                    format!("  ... and {} more", recent.len() - SUMMARY_RECENT),
+                    Style::new().dim(),
+                ));
+            }
+        }
+
+        lines.push(Line::raw(""));
+        let over_cap = self.max_delete.filter(|cap| size > *cap);
+        if let Some(cap) = over_cap {
+            lines.push(message_line(
+                "confirm-over-cap",
+                vec![("cap", Span::styled(format_size(cap), Style::new().red().bold()))],
+            ));
+        }
+        if self.needs_word() {
+            let (count, size) = self.selection_summary();
+            if self.large.exceeded(count, size) {
+                lines.push(Line::raw(tr!("confirm-large")));
+            }
+            lines.push(message_line(
+                "confirm-type-word",
+                vec![
+                    ("word", Span::styled(CONFIRM_WORD, Style::new().cyan().bold())),
+                    ("typed", Span::styled(self.typed.clone(), Style::new().bold())),
+                ],
+            ));
+            return lines;
+        }
+        if over_cap.is_some() {
+            lines.push(message_line(
+                "confirm-keys",
+                vec![
+                    ("yes", Span::styled("Y (shift)", Style::new().cyan())),
+                    ("no", Span::styled("n/Esc", Style::new().cyan())),
+                ],
+            ));
+            return lines;
+        }
+        lines.push(message_line(
+            "confirm-keys",
+            vec![
+                ("yes", Span::styled("y/Enter", Style::new().cyan())),
+                ("no", Span::styled("n/Esc", Style::new().cyan())),
+            ],
         ));
+        lines
+    }
+
+    /// Whether the selection is larger than the `--max-delete` cap, so
+    /// confirming it takes `Y` rather than `y` or Enter.
+    fn over_cap(&self) -> bool {
+        self.max_delete.is_some_and(|cap| self.selection_summary().1 > cap)
+    }
+
+    /// Whether the deletion is large enough, or includes anything that looks
+    /// hand-written, so that confirming it takes typing [`CONFIRM_WORD`].
+    fn needs_word(&self) -> bool {
+        let (count, size) = self.selection_summary();
+        let suspicious = self
+            .groups
+            .iter()
+            .flat_map(|g| g.items.iter())
+            .any(|i| i.selected && i.dir.suspicious.is_some());
+        self.ask_word && (suspicious || self.large.exceeded(count, size))
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        if self.confirming {
+            let summary = Paragraph::new(self.summary_lines()).block(
+                Block::bordered()
+                    .border_set(self.glyphs.border)
+                    .title(" Confirm deletion "),
+            );
+            frame.render_widget(summary, frame.area());
+        } else {
+            self.draw_list(frame);
+        }
+
+        if !self.color {
+            for cell in frame.buffer_mut().content.iter_mut() {
+                cell.set_fg(Color::Reset).set_bg(Color::Reset);
+            }
+        }
+    }
 
-        term.clear_screen()?;
-        term.write_str(&output)?;
+    fn draw_list(&mut self, frame: &mut Frame) {
+        let mut constraints = vec![Constraint::Min(1)];
+        if self.show_preview {
+            constraints.push(Constraint::Length(PREVIEW_LINES as u16));
+        }
+        constraints.push(Constraint::Length(FOOTER_LINES as u16));
+        let areas = Layout::vertical(constraints).split(frame.area());
+
+        let list_area = areas[0];
+        self.page_height = (list_area.height as usize).max(1);
+        self.page_width = list_area.width as usize;
+        self.scroll_to_cursor(self.page_height);
+
+        let lines = self.list_lines();
+        let total = lines.len();
+        let visible: Vec<Line> = lines
+            .into_iter()
+            .skip(self.scroll)
+            .take(self.page_height)
+            .collect();
+        frame.render_widget(Paragraph::new(visible), list_area);
+
+        if self.show_preview {
+            frame.render_widget(Paragraph::new(self.preview_lines()), areas[1]);
+        }
 
-        Ok(())
+        let footer = self.footer_lines(total, self.page_height);
+        frame.render_widget(Paragraph::new(footer), areas[areas.len() - 1]);
+
+        if self.show_help {
+            self.draw_help(frame);
+        }
     }
 
     fn move_up(&mut self) {
@@ -228,6 +1592,28 @@ impl GroupedSelector {
         }
     }
 
+    fn page_up(&mut self, page: usize) {
+        self.cursor = self.cursor.saturating_sub(page);
+    }
+
+    fn page_down(&mut self, page: usize) {
+        let last = self.total_lines().saturating_sub(1);
+        self.cursor = (self.cursor + page).min(last);
+    }
+
+    fn move_to_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    fn move_to_end(&mut self) {
+        self.cursor = self.total_lines().saturating_sub(1);
+    }
+
+    /// Jump to a zero-based line, clamped to the list.
+    fn move_to_line(&mut self, line: usize) {
+        self.cursor = line.min(self.total_lines().saturating_sub(1));
+    }
+
     fn toggle_current(&mut self) {
         match self.cursor_position() {
             CursorPosition::GroupHeader(gi) => {
@@ -239,36 +1625,489 @@ impl GroupedSelector {
         }
     }
 
+    /// Items not hidden by the filter, which bulk selection applies to.
+    fn visible_items_mut(&mut self) -> impl Iterator<Item = &mut GroupedItem> {
+        self.groups
+            .iter_mut()
+            .flat_map(|g| g.items.iter_mut())
+            .filter(|i| !i.hidden)
+    }
+
+    fn set_all(&mut self, selected: bool) {
+        for item in self.visible_items_mut() {
+            item.selected = selected;
+        }
+    }
+
+    fn invert_selection(&mut self) {
+        for item in self.visible_items_mut() {
+            item.selected = !item.selected;
+        }
+    }
+
+    /// Show only items matching `filter`, or everything if it is `None`.
+    fn set_filter(&mut self, filter: Option<Filter>) {
+        for item in self.groups.iter_mut().flat_map(|g| g.items.iter_mut()) {
+            item.hidden = filter.as_ref().is_some_and(|f| !f.matches(&item.dir));
+        }
+        self.filter = filter;
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    /// Apply what was typed into the filter prompt. A filter that matches
+    /// nothing is rejected rather than leaving an empty list.
+    fn apply_filter(&mut self, input: &str) {
+        let filter = match Filter::parse(input) {
+            Ok(filter) => filter,
+            Err(e) => {
+                self.status = Some(e);
+                return;
+            }
+        };
+        if let Some(f) = &filter
+            && !self.groups.iter().flat_map(|g| g.items.iter()).any(|i| f.matches(&i.dir))
+        {
+            self.status = Some(format!("No paths match {}", f.input()));
+            return;
+        }
+        self.set_filter(filter);
+    }
+
+    /// Collapse or expand every group, keeping the cursor in the same group.
+    fn set_all_collapsed(&mut self, collapsed: bool) {
+        let (gi, ii) = match self.cursor_position() {
+            CursorPosition::GroupHeader(gi) => (gi, None),
+            CursorPosition::Item(gi, ii) => (gi, Some(ii)),
+        };
+        for group in &mut self.groups {
+            group.collapsed = collapsed;
+        }
+        self.cursor = self.line_of(gi, ii);
+    }
+
     fn toggle_collapse(&mut self) {
         if let CursorPosition::GroupHeader(gi) = self.cursor_position() {
             self.groups[gi].collapsed = !self.groups[gi].collapsed;
         }
     }
 
-    pub fn run(mut self) -> io::Result<Vec<FoundDir>> {
-        let term = Term::stderr();
-        term.hide_cursor()?;
+    /// Switch to the next sort order, keeping the cursor on the same item.
+    fn cycle_sort(&mut self) {
+        let anchor = self.cursor_anchor();
+
+        self.sort = self.sort.next();
+        for group in &mut self.groups {
+            group.sort(self.sort);
+        }
+
+        self.restore_anchor(anchor);
+    }
+
+    /// Switch between grouping by type and by project. Selection state is
+    /// kept; the cursor returns to the top.
+    fn toggle_grouping(&mut self) {
+        self.group_by = match self.group_by {
+            GroupBy::Type => GroupBy::Project,
+            GroupBy::Project => GroupBy::Type,
+        };
+        let items = std::mem::take(&mut self.groups)
+            .into_iter()
+            .flat_map(|g| g.items)
+            .collect();
+        self.groups = build_groups(items, self.group_by);
+        for group in &mut self.groups {
+            group.sort(self.sort);
+        }
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    /// Keep only the confirmed items and start deleting them on a worker
+    /// thread.
+    fn start_deletion(&mut self) {
+        self.confirming = false;
+        // Hidden items are deleted too, so show them
+        self.set_filter(None);
+        for group in &mut self.groups {
+            group.items.retain(|i| i.selected);
+            group.collapsed = false;
+            for item in &mut group.items {
+                item.delete_state = Some(DeleteState::Waiting);
+            }
+        }
+        self.groups.retain(|g| !g.items.is_empty());
+        self.cursor = 0;
+
+        let dirs: Vec<FoundDir> = self
+            .groups
+            .iter()
+            .flat_map(|g| g.items.iter())
+            .map(|i| i.dir.clone())
+            .collect();
+        let method = self.method;
+        let (events, receiver) = mpsc::channel();
+        let worker = thread::spawn(move || {
+            cleaner::clean_with_progress(dirs, method, |progress| {
+                let update = match progress {
+                    CleanProgress::Started(path) => (path.to_path_buf(), DeleteState::Deleting),
+                    CleanProgress::Deleted(path) => (path.to_path_buf(), DeleteState::Deleted),
+                    CleanProgress::Failed(path, e) => (path.to_path_buf(), DeleteState::Failed(e.to_string())),
+                };
+                let _ = events.send(update);
+            })
+        });
 
-        loop {
-            self.render(&term)?;
+        self.deletion = Some(Deletion {
+            events: receiver,
+            worker: Some(worker),
+            result: None,
+        });
+    }
 
-            match term.read_key()? {
-                Key::ArrowUp | Key::Char('k') => self.move_up(),
-                Key::ArrowDown | Key::Char('j') => self.move_down(),
-                Key::Char(' ') => self.toggle_current(),
-                Key::Tab => self.toggle_collapse(),
-                Key::Enter => break,
-                Key::Escape | Key::Char('q') => {
-                    term.show_cursor()?;
-                    term.clear_screen()?;
-                    return Ok(Vec::new());
+    /// Apply progress from the deletion worker, collecting its result once it
+    /// has finished.
+    fn poll_deletion(&mut self) {
+        let Some(deletion) = self.deletion.as_mut() else {
+            return;
+        };
+        while let Ok((path, state)) = deletion.events.try_recv() {
+            if let Some(item) = self
+                .groups
+                .iter_mut()
+                .flat_map(|g| g.items.iter_mut())
+                .find(|i| i.dir.path == path)
+            {
+                item.delete_state = Some(state);
+            }
+        }
+        if deletion.worker.as_ref().is_some_and(|w| w.is_finished())
+            && let Some(worker) = deletion.worker.take()
+        {
+            deletion.result = worker.join().ok();
+            // Drain anything sent between the last poll and the worker exiting
+            while let Ok((path, state)) = deletion.events.try_recv() {
+                if let Some(item) = self
+                    .groups
+                    .iter_mut()
+                    .flat_map(|g| g.items.iter_mut())
+                    .find(|i| i.dir.path == path)
+                {
+                    item.delete_state = Some(state);
+                }
+            }
+        }
+    }
+
+    /// Handle a key press. Returns `Some(confirmed)` once the user is done.
+    fn handle_key(&mut self, key: KeyEvent) -> Option<bool> {
+        if self.show_help {
+            self.show_help = false;
+            return None;
+        }
+
+        // Deletion can't be interrupted; wait for it, then any key exits
+        if let Some(deletion) = &self.deletion {
+            return deletion.result.is_some().then_some(true);
+        }
+
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Some(false);
+        }
+
+        self.status = None;
+
+        if self.prompt.is_some() {
+            self.handle_prompt_key(key);
+            return None;
+        }
+
+        if self.confirming && self.needs_word() {
+            match key.code {
+                KeyCode::Enter if self.typed == CONFIRM_WORD && self.clean => self.start_deletion(),
+                KeyCode::Enter if self.typed == CONFIRM_WORD => return Some(true),
+                KeyCode::Esc => {
+                    self.confirming = false;
+                    self.typed.clear();
+                }
+                KeyCode::Backspace => {
+                    self.typed.pop();
                 }
+                KeyCode::Char(c) => self.typed.push(c),
                 _ => {}
             }
+            return None;
+        }
+
+        if self.confirming {
+            let confirm = if self.over_cap() {
+                matches!(key.code, KeyCode::Char('Y'))
+            } else {
+                matches!(key.code, KeyCode::Enter | KeyCode::Char('y'))
+            };
+            match key.code {
+                _ if confirm && self.clean => self.start_deletion(),
+                _ if confirm => return Some(true),
+                KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => self.confirming = false,
+                _ => {}
+            }
+            return None;
+        }
+
+        // Everything was excluded; there is nothing left to select
+        if self.groups.is_empty() && !self.scanning {
+            return Some(true);
+        }
+
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        // Vim-style count prefix, e.g. `5j`; a leading 0 isn't a count
+        if let KeyCode::Char(c @ '0'..='9') = key.code
+            && (c != '0' || self.count.is_some())
+        {
+            let digit = c as usize - '0' as usize;
+            self.count = Some(self.count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+            return None;
+        }
+        let count = self.count.take();
+        let repeat = count.unwrap_or(1);
+        let pending_g = std::mem::take(&mut self.pending_g);
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => (0..repeat).for_each(|_| self.move_up()),
+            KeyCode::Down | KeyCode::Char('j') => (0..repeat).for_each(|_| self.move_down()),
+            KeyCode::Char('d') if ctrl => self.page_down((self.page_height / 2).max(1) * repeat),
+            KeyCode::Char('u') if ctrl => self.page_up((self.page_height / 2).max(1) * repeat),
+            // `gg` goes to the top, or to line N with a count
+            KeyCode::Char('g') if pending_g => self.move_to_line(count.map_or(0, |n| n.saturating_sub(1))),
+            KeyCode::Char('g') => {
+                self.pending_g = true;
+                self.count = count;
+            }
+            KeyCode::Char('G') => match count {
+                Some(n) => self.move_to_line(n.saturating_sub(1)),
+                None => self.move_to_end(),
+            },
+            KeyCode::PageUp => self.page_up(self.page_height),
+            KeyCode::PageDown => self.page_down(self.page_height),
+            KeyCode::Home => self.move_to_start(),
+            KeyCode::End => self.move_to_end(),
+            KeyCode::Char(' ') => self.toggle_current(),
+            KeyCode::Tab => self.toggle_collapse(),
+            KeyCode::Char('-') => self.set_all_collapsed(true),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.set_all_collapsed(false),
+            KeyCode::Char('s') => self.cycle_sort(),
+            KeyCode::Char('p') => self.toggle_grouping(),
+            KeyCode::Char('a') => self.set_all(true),
+            KeyCode::Char('n') | KeyCode::Char('A') => self.set_all(false),
+            KeyCode::Char('i') => self.invert_selection(),
+            KeyCode::Char('b') => self.prompt = Some(Prompt::Criterion(String::new())),
+            KeyCode::Char('/') => {
+                let input = self.filter.as_ref().map(Filter::input).unwrap_or_default();
+                self.prompt = Some(Prompt::Filter(input));
+            }
+            KeyCode::Char('x') => self.exclude_current(false),
+            KeyCode::Char('X') => self.exclude_current(true),
+            KeyCode::Char('o') => self.open_current(),
+            KeyCode::Char('c') => self.copy_current(),
+            KeyCode::Char('v') => self.show_preview = !self.show_preview,
+            KeyCode::Char('w') => self.explain_current(),
+            KeyCode::Char('?') => self.show_help = true,
+            // Nothing to confirm when nothing is selected
+            KeyCode::Enter if self.scanning => {
+                self.status = Some("Still scanning; wait for it to finish before confirming".to_string());
+            }
+            KeyCode::Enter if self.selection_summary().0 == 0 => return Some(true),
+            KeyCode::Enter if self.unsized_selected() > 0 => {
+                self.status = Some(format!(
+                    "Still sizing {} selected directories; confirm once they're done",
+                    self.unsized_selected()
+                ));
+            }
+            KeyCode::Enter => self.confirming = true,
+            KeyCode::Esc if self.filter.is_some() => self.set_filter(None),
+            KeyCode::Esc | KeyCode::Char('q') => return Some(false),
+            _ => {}
+        }
+        None
+    }
+
+    /// Persistently exclude the highlighted item's path, or its whole project,
+    /// and drop everything it covers from the list.
+    fn exclude_current(&mut self, whole_project: bool) {
+        let CursorPosition::Item(gi, ii) = self.cursor_position() else {
+            return;
+        };
+        let dir = &self.groups[gi].items[ii].dir;
+        let target = if whole_project {
+            dir.project_root.clone()
+        } else {
+            dir.path.clone()
+        };
+
+        if let Err(e) = exclude::add(&target) {
+            self.status = Some(format!("Could not save exclusion: {}", e));
+            return;
+        }
+
+        self.drop_items(|i| i.dir.path.starts_with(&target));
+        self.status = Some(format!("Excluded {} from future scans", target.display()));
+        self.excluded.push(target);
+    }
+
+    /// Select exactly the items matching the prompt's criterion.
+    fn apply_criterion(&mut self, input: &str) {
+        let criterion = match Criterion::parse(input) {
+            Ok(criterion) => criterion,
+            Err(e) => {
+                self.status = Some(e);
+                return;
+            }
+        };
+
+        let mut count = 0;
+        for item in self.visible_items_mut() {
+            item.selected = criterion.matches(&item.dir);
+            if item.selected {
+                count += 1;
+            }
+        }
+        self.status = Some(format!("Selected {} items matching {}", count, input.trim()));
+    }
+
+    /// Keys typed while a footer prompt is open.
+    fn handle_prompt_key(&mut self, key: KeyEvent) {
+        let Some(Prompt::Criterion(input) | Prompt::Filter(input)) = self.prompt.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => match self.prompt.take() {
+                Some(Prompt::Criterion(input)) => self.apply_criterion(&input),
+                Some(Prompt::Filter(input)) => self.apply_filter(&input),
+                None => {}
+            },
+            KeyCode::Esc => self.prompt = None,
+            _ => {}
+        }
+    }
+
+    fn open_current(&mut self) {
+        let CursorPosition::Item(gi, ii) = self.cursor_position() else {
+            return;
+        };
+        let path = &self.groups[gi].items[ii].dir.path;
+        self.status = Some(match desktop::open_in_file_manager(path) {
+            Ok(()) => format!("Opened {}", path.display()),
+            Err(e) => format!("Could not open file manager: {}", e),
+        });
+    }
+
+    fn copy_current(&mut self) {
+        let CursorPosition::Item(gi, ii) = self.cursor_position() else {
+            return;
+        };
+        let path = self.groups[gi].items[ii].dir.path.display().to_string();
+        self.status = Some(match desktop::copy_to_clipboard(&path) {
+            Ok(()) => format!("Copied {}", path),
+            Err(e) => format!("Could not copy path: {}", e),
+        });
+    }
+
+    /// Say which rule matched the highlighted item and what confirmed it.
+    fn explain_current(&mut self) {
+        let CursorPosition::Item(gi, ii) = self.cursor_position() else {
+            return;
+        };
+        let dir = &self.groups[gi].items[ii].dir;
+        let mut status = format!("{}: {}", dir.project_type.name(), dir.matched.describe());
+        if let Some(rebuild) = &dir.rebuild {
+            status.push_str(&format!("; cleaning it {}", rebuild.describe()));
+        }
+        self.status = Some(status);
+    }
+
+    /// Merge this session's choices into the saved state.
+    fn save_state(&self) -> io::Result<()> {
+        let mut state = SelectorState::load();
+        for group in &self.groups {
+            if group.collapsed {
+                state.collapsed.insert(group.label.clone());
+            } else {
+                state.collapsed.remove(&group.label);
+            }
+            for item in &group.items {
+                state.selected.insert(item.dir.path.clone(), item.selected);
+            }
+        }
+        state.save()
+    }
+
+    pub fn run(self) -> io::Result<Selection> {
+        Ok(self
+            .run_with(None)?
+            .unwrap_or(Selection::Pending(Vec::new())))
+    }
+
+    /// Review results of a streaming scan while it is still running. Returns
+    /// `None` if the scan finished without finding anything.
+    pub fn run_streaming(mut self, events: Receiver<ScanEvent>) -> io::Result<Option<Selection>> {
+        self.scanning = true;
+        self.run_with(Some(events))
+    }
+
+    fn run_with(mut self, events: Option<Receiver<ScanEvent>>) -> io::Result<Option<Selection>> {
+        let mut tui = Tui::enter()?;
+
+        let confirmed = loop {
+            self.request_sizes();
+            self.poll_sizes();
+            tui.terminal.draw(|frame| self.draw(frame))?;
+
+            // Resize events just fall through to the next redraw
+            if event::poll(TICK)?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+                && let Some(confirmed) = self.handle_key(key)
+            {
+                break confirmed;
+            }
+
+            if let Some(events) = &events {
+                loop {
+                    match events.try_recv() {
+                        Ok(event) => self.apply_scan_event(event),
+                        Err(TryRecvError::Empty) => break,
+                        Err(TryRecvError::Disconnected) => {
+                            if self.scanning {
+                                self.finish_scan();
+                            }
+                            break;
+                        }
+                    }
+                }
+            }
+
+            self.poll_deletion();
+
+            if !self.scanning && self.found == 0 {
+                return Ok(None);
+            }
+        };
+
+        drop(tui);
+
+        if !confirmed {
+            return Ok(Some(Selection::Cancelled));
         }
 
-        term.show_cursor()?;
-        term.clear_screen()?;
+        if self.remember {
+            // Losing remembered choices isn't worth failing the run over
+            let _ = self.save_state();
+        }
 
         let selected: Vec<FoundDir> = self
             .groups
@@ -278,6 +2117,60 @@ impl GroupedSelector {
             .map(|i| i.dir)
             .collect();
 
-        Ok(selected)
+        if let Some(result) = self.deletion.and_then(|d| d.result) {
+            return Ok(Some(Selection::Cleaned(result)));
+        }
+        Ok(Some(Selection::Pending(selected)))
     }
 }
+
+/// Raw-mode alternate screen on stderr, restored when dropped (including on
+/// early returns via `?`).
+struct Tui {
+    terminal: Terminal<CrosstermBackend<Stderr>>,
+}
+
+impl Tui {
+    fn enter() -> io::Result<Self> {
+        logging::hold();
+        terminal::enable_raw_mode()?;
+        execute!(io::stderr(), EnterAlternateScreen)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stderr()))?;
+        terminal.hide_cursor()?;
+        Ok(Self { terminal })
+    }
+}
+
+impl Drop for Tui {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(io::stderr(), LeaveAlternateScreen);
+        let _ = self.terminal.show_cursor();
+        logging::release();
+    }
+}
+
+/// Translate message `id` into a line whose variables keep their own styling,
+/// wherever the translation places them.
+fn message_line(id: &str, values: Vec<(&'static str, Span<'static>)>) -> Line<'static> {
+    // Mark each variable so the translated text can be split around it
+    let mut args = fluent::FluentArgs::new();
+    for (index, (name, _)) in values.iter().enumerate() {
+        args.set(*name, format!("\u{1}{}\u{1}", index));
+    }
+    let text = i18n::message(id, Some(&args));
+
+    let mut values: Vec<Option<Span<'static>>> = values.into_iter().map(|(_, span)| Some(span)).collect();
+    let spans = text
+        .split('\u{1}')
+        .enumerate()
+        .filter_map(|(part, piece)| {
+            if part % 2 == 0 {
+                (!piece.is_empty()).then(|| Span::raw(piece.to_string()))
+            } else {
+                piece.parse::<usize>().ok().and_then(|index| values.get_mut(index)?.take())
+            }
+        })
+        .collect::<Vec<_>>();
+    Line::from(spans)
+}
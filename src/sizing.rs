@@ -0,0 +1,357 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// What sizing needs to know about something in the directory being sized.
+#[derive(Debug)]
+pub struct Entry<'a> {
+    pub path: &'a Path,
+    /// 0 for the directory itself, 1 for what's directly in it
+    pub depth: usize,
+    pub is_dir: bool,
+    /// A regular file, the only kind whose length counts
+    pub is_file: bool,
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// Call `visit` with `root` and everything under it, symlinks as themselves,
+/// and `failed` with what couldn't be read. Entries removed while the walk
+/// runs are left out quietly.
+///
+/// Each entry is looked at once, relative to the directory it was listed
+/// in: on Windows the listing already carries what's needed, and on Linux
+/// one `statx` asks only for the type, size and modification time, of the
+/// descriptor opened to list it where the listing says it's a directory.
+pub fn walk(root: &Path, mut visit: impl FnMut(&Entry), mut failed: impl FnMut(PathBuf, io::Error)) {
+    imp::walk(root, &mut visit, &mut failed)
+}
+
+fn vanished(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::NotFound
+}
+
+#[cfg(not(all(target_os = "linux", any(target_env = "gnu", target_env = "musl"))))]
+mod imp {
+    use super::{vanished, Entry};
+    use std::fs::{self, Metadata};
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    pub fn walk(root: &Path, visit: &mut dyn FnMut(&Entry), failed: &mut dyn FnMut(PathBuf, io::Error)) {
+        let meta = match root.symlink_metadata() {
+            Ok(meta) => meta,
+            Err(e) => return failed(root.to_path_buf(), e),
+        };
+        visit(&entry(root, 0, &meta));
+        if !meta.is_dir() {
+            return;
+        }
+        let mut pending = vec![(root.to_path_buf(), 1)];
+        while let Some((dir, depth)) = pending.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) if vanished(&e) => continue,
+                Err(e) => {
+                    failed(dir, e);
+                    continue;
+                }
+            };
+            for entry in entries {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        failed(dir.clone(), e);
+                        break;
+                    }
+                };
+                let path = entry.path();
+                // Free on Windows; elsewhere a stat relative to the open
+                // directory, never following a symlink
+                let meta = match entry.metadata() {
+                    Ok(meta) => meta,
+                    Err(e) if vanished(&e) => continue,
+                    Err(e) => {
+                        failed(path, e);
+                        continue;
+                    }
+                };
+                visit(&entry(&path, depth, &meta));
+                if meta.is_dir() {
+                    pending.push((path, depth + 1));
+                }
+            }
+        }
+    }
+
+    fn entry<'a>(path: &'a Path, depth: usize, meta: &Metadata) -> Entry<'a> {
+        Entry {
+            path,
+            depth,
+            is_dir: meta.is_dir(),
+            is_file: meta.is_file(),
+            len: meta.len(),
+            modified: meta.modified().ok(),
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", any(target_env = "gnu", target_env = "musl")))]
+mod imp {
+    use super::{vanished, Entry};
+    use std::ffi::{CStr, CString, OsStr};
+    use std::io;
+    use std::mem::MaybeUninit;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    /// Room for a few hundred entries per `getdents64`, so most directories
+    /// list in one call and a second, empty one.
+    const LISTING_BUFFER: usize = 32 * 1024;
+
+    /// Set once `statx` turns out to be missing, or blocked by an old
+    /// container's seccomp profile, to use `fstatat` from then on.
+    static NO_STATX: AtomicBool = AtomicBool::new(false);
+
+    struct Stat {
+        is_dir: bool,
+        is_file: bool,
+        len: u64,
+        modified: Option<SystemTime>,
+    }
+
+    /// A directory still to be listed, opened only when its turn comes so
+    /// that the open descriptors are bounded by the depth, not the width.
+    struct Pending {
+        parent: Rc<OwnedFd>,
+        name: CString,
+        path: PathBuf,
+        /// Its own depth; what it holds is one deeper
+        depth: usize,
+        /// Already passed to `visit`, when the listing didn't say it was a
+        /// directory and it had to be looked at to find out
+        visited: bool,
+    }
+
+    pub fn walk(root: &Path, visit: &mut dyn FnMut(&Entry), failed: &mut dyn FnMut(PathBuf, io::Error)) {
+        let Ok(name) = CString::new(root.as_os_str().as_bytes()) else {
+            return failed(root.to_path_buf(), io::ErrorKind::InvalidInput.into());
+        };
+        let stat = match stat_at(libc::AT_FDCWD, &name, 0) {
+            Ok(stat) => stat,
+            Err(e) => return failed(root.to_path_buf(), e),
+        };
+        visit(&entry(root, 0, &stat));
+        if !stat.is_dir {
+            return;
+        }
+        let fd = match open_dir(libc::AT_FDCWD, &name) {
+            Ok(fd) => fd,
+            Err(e) => return failed(root.to_path_buf(), e),
+        };
+
+        let mut buf = vec![0u8; LISTING_BUFFER];
+        let mut names = Vec::new();
+        let mut pending = Vec::new();
+        let mut next = Some((Rc::new(fd), root.to_path_buf(), 1));
+        while let Some((fd, dir, depth)) = next.take() {
+            names.clear();
+            if let Err(e) = list(fd.as_raw_fd(), &mut buf, &mut names) {
+                failed(dir.clone(), e);
+            }
+            for (name, kind) in names.drain(..) {
+                let path = dir.join(OsStr::from_bytes(name.to_bytes()));
+                // Directories are looked at once open, through the
+                // descriptor listing them needs anyway
+                let visited = kind != libc::DT_DIR;
+                if visited {
+                    let child = match stat_at(fd.as_raw_fd(), &name, 0) {
+                        Ok(child) => child,
+                        Err(e) if vanished(&e) => continue,
+                        Err(e) => {
+                            failed(path, e);
+                            continue;
+                        }
+                    };
+                    visit(&entry(&path, depth, &child));
+                    if !child.is_dir {
+                        continue;
+                    }
+                }
+                pending.push(Pending {
+                    parent: fd.clone(),
+                    name,
+                    path,
+                    depth,
+                    visited,
+                });
+            }
+            drop(fd);
+            while let Some(dir) = pending.pop() {
+                if let Some(fd) = open_pending(&dir, visit, failed) {
+                    next = Some((Rc::new(fd), dir.path, dir.depth + 1));
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Open a directory waiting to be listed, passing it to `visit` first if
+    /// it hasn't been. `None` if it can't be listed, or is no longer a
+    /// directory.
+    fn open_pending(
+        dir: &Pending,
+        visit: &mut dyn FnMut(&Entry),
+        failed: &mut dyn FnMut(PathBuf, io::Error),
+    ) -> Option<OwnedFd> {
+        let opened = open_dir(dir.parent.as_raw_fd(), &dir.name);
+        if dir.visited {
+            return match opened {
+                Ok(fd) => Some(fd),
+                Err(e) if vanished(&e) => None,
+                Err(e) => {
+                    failed(dir.path.clone(), e);
+                    None
+                }
+            };
+        }
+        let stat = match &opened {
+            Ok(fd) => stat_at(fd.as_raw_fd(), c"", libc::AT_EMPTY_PATH),
+            // Swapped for something else since the listing, or unreadable:
+            // still counted, as itself
+            Err(_) => stat_at(dir.parent.as_raw_fd(), &dir.name, 0),
+        };
+        match stat {
+            Ok(stat) => visit(&entry(&dir.path, dir.depth, &stat)),
+            Err(e) if vanished(&e) => return None,
+            Err(e) => {
+                failed(dir.path.clone(), e);
+                return None;
+            }
+        }
+        match opened {
+            Ok(fd) => Some(fd),
+            Err(e) if vanished(&e) || matches!(e.raw_os_error(), Some(libc::ENOTDIR | libc::ELOOP)) => None,
+            Err(e) => {
+                failed(dir.path.clone(), e);
+                None
+            }
+        }
+    }
+
+    fn entry<'a>(path: &'a Path, depth: usize, stat: &Stat) -> Entry<'a> {
+        Entry {
+            path,
+            depth,
+            is_dir: stat.is_dir,
+            is_file: stat.is_file,
+            len: stat.len,
+            modified: stat.modified,
+        }
+    }
+
+    fn open_dir(parent: RawFd, name: &CStr) -> io::Result<OwnedFd> {
+        let flags = libc::O_RDONLY | libc::O_DIRECTORY | libc::O_NOFOLLOW | libc::O_CLOEXEC;
+        // SAFETY: `name` is a valid C string for the duration of the call
+        let fd = unsafe { libc::openat(parent, name.as_ptr(), flags) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: openat just returned this descriptor and nothing else owns it
+        Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+    }
+
+    /// Add the names in the directory open as `fd`, less "." and "..", to
+    /// `names` with the type the listing gives (`DT_UNKNOWN` where the
+    /// filesystem doesn't say), reading the raw listing rather than through
+    /// `opendir`, which would stat the directory again first.
+    fn list(fd: RawFd, buf: &mut [u8], names: &mut Vec<(CString, u8)>) -> io::Result<()> {
+        loop {
+            // SAFETY: the kernel writes at most `buf.len()` bytes into `buf`
+            let read = unsafe { libc::syscall(libc::SYS_getdents64, fd, buf.as_mut_ptr(), buf.len()) };
+            if read < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if read == 0 {
+                return Ok(());
+            }
+            let read = read as usize;
+            let mut offset = 0;
+            while offset < read {
+                // Each record is an 8-byte inode, an 8-byte offset, a 2-byte
+                // length, a 1-byte type and the name
+                let length = u16::from_ne_bytes([buf[offset + 16], buf[offset + 17]]) as usize;
+                let kind = buf[offset + 18];
+                if let Ok(name) = CStr::from_bytes_until_nul(&buf[offset + 19..offset + length])
+                    && name != c"."
+                    && name != c".."
+                {
+                    names.push((name.to_owned(), kind));
+                }
+                offset += length;
+            }
+        }
+    }
+
+    /// Type, size and modification time of `name` in the directory open as
+    /// `dir`, not following it if it's a symlink. With `AT_EMPTY_PATH` in
+    /// `flags` and an empty name, of what `dir` itself is.
+    fn stat_at(dir: RawFd, name: &CStr, flags: libc::c_int) -> io::Result<Stat> {
+        let flags = flags | libc::AT_SYMLINK_NOFOLLOW;
+        if !NO_STATX.load(Ordering::Relaxed) {
+            let mut buf = MaybeUninit::<libc::statx>::zeroed();
+            // Cached attributes will do, even on network filesystems
+            let flags = flags | libc::AT_NO_AUTOMOUNT | libc::AT_STATX_DONT_SYNC;
+            let mask = libc::STATX_TYPE | libc::STATX_SIZE | libc::STATX_MTIME;
+            // SAFETY: `name` is a valid C string and `buf` large enough for
+            // the statx the kernel writes
+            if unsafe { libc::statx(dir, name.as_ptr(), flags, mask, buf.as_mut_ptr()) } == 0 {
+                // SAFETY: statx succeeded, so it filled `buf` in
+                let stx = unsafe { buf.assume_init() };
+                let kind = stx.stx_mode as libc::mode_t & libc::S_IFMT;
+                return Ok(Stat {
+                    is_dir: kind == libc::S_IFDIR,
+                    is_file: kind == libc::S_IFREG,
+                    len: stx.stx_size,
+                    modified: (stx.stx_mask & libc::STATX_MTIME != 0)
+                        .then(|| time(stx.stx_mtime.tv_sec, stx.stx_mtime.tv_nsec))
+                        .flatten(),
+                });
+            }
+            let error = io::Error::last_os_error();
+            if !matches!(error.raw_os_error(), Some(libc::ENOSYS | libc::EPERM)) {
+                return Err(error);
+            }
+            NO_STATX.store(true, Ordering::Relaxed);
+        }
+
+        let mut buf = MaybeUninit::<libc::stat>::zeroed();
+        // SAFETY: as for statx
+        if unsafe { libc::fstatat(dir, name.as_ptr(), buf.as_mut_ptr(), flags) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: fstatat succeeded, so it filled `buf` in
+        let st = unsafe { buf.assume_init() };
+        let kind = st.st_mode & libc::S_IFMT;
+        Ok(Stat {
+            is_dir: kind == libc::S_IFDIR,
+            is_file: kind == libc::S_IFREG,
+            len: st.st_size as u64,
+            modified: time(st.st_mtime, st.st_mtime_nsec as u32),
+        })
+    }
+
+    fn time(secs: i64, nanos: u32) -> Option<SystemTime> {
+        if secs >= 0 {
+            UNIX_EPOCH.checked_add(Duration::new(secs as u64, nanos))
+        } else {
+            UNIX_EPOCH
+                .checked_sub(Duration::from_secs(secs.unsigned_abs()))?
+                .checked_add(Duration::from_nanos(nanos.into()))
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::lock;
+use crate::scanner::FoundDir;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// What one scan of a root found, saved for `--diff` to compare against.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Seconds since the Unix epoch when the scan finished; the oldest of
+    /// them when several roots' snapshots are merged
+    pub time: i64,
+    /// Size of each directory found
+    pub sizes: BTreeMap<PathBuf, u64>,
+}
+
+/// A directory that appeared or grew since the previous scan.
+#[derive(Debug, Serialize)]
+pub struct Change {
+    pub path: PathBuf,
+    #[serde(rename = "type")]
+    pub project_type: &'static str,
+    /// Repository or project directory the artifact belongs to
+    pub project: PathBuf,
+    pub size_bytes: u64,
+    /// How much larger it is than last time; its whole size when new
+    pub grew_bytes: u64,
+}
+
+impl Change {
+    /// The artifact as `project/path/inside/it`, e.g. `webapp/node_modules`.
+    pub fn label(&self) -> String {
+        let name = self.project.file_name().unwrap_or(self.project.as_os_str());
+        match self.path.strip_prefix(&self.project) {
+            Ok(inside) => Path::new(name).join(inside).display().to_string(),
+            Err(_) => self.path.display().to_string(),
+        }
+    }
+}
+
+/// How the latest scan differs from the one before it.
+#[derive(Debug, Serialize)]
+pub struct Diff {
+    /// When the earlier scan finished, in seconds since the Unix epoch
+    pub since: i64,
+    /// Directories the earlier scan didn't find, largest first
+    pub new: Vec<Change>,
+    /// Directories larger than last time, by how much they grew
+    pub grew: Vec<Change>,
+    /// Directories found last time and not now, since cleaned or removed
+    pub gone: Vec<PathBuf>,
+}
+
+fn snapshot_file(root: &Path) -> Option<PathBuf> {
+    let name = root.file_name().and_then(|n| n.to_str()).unwrap_or("root");
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("safeclean").join("scans").join(format!("{}-{:016x}.json", name, lock::hash(root))))
+}
+
+/// The previous scan of `roots` (canonical paths), merged, or `None` if none
+/// of them has been scanned before. Unreadable snapshots count as missing.
+pub fn load(roots: &[PathBuf]) -> Option<Snapshot> {
+    let snapshots: Vec<Snapshot> = roots
+        .iter()
+        .filter_map(|root| snapshot_file(root))
+        .filter_map(|file| fs::read_to_string(file).ok())
+        .filter_map(|contents| serde_json::from_str(&contents).ok())
+        .collect();
+    let time = snapshots.iter().map(|s| s.time).min()?;
+    Some(Snapshot {
+        time,
+        sizes: snapshots.into_iter().flat_map(|s| s.sizes).collect(),
+    })
+}
+
+/// Save what a scan of `roots` found, replacing each root's previous
+/// snapshot.
+pub fn save(roots: &[PathBuf], found: &[FoundDir]) -> io::Result<()> {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    for root in roots {
+        let Some(file) = snapshot_file(root) else {
+            continue;
+        };
+        let snapshot = Snapshot {
+            time,
+            sizes: found
+                .iter()
+                .filter(|d| d.path.starts_with(root))
+                .map(|d| (d.path.clone(), d.size_bytes))
+                .collect(),
+        };
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(file, serde_json::to_string(&snapshot).map_err(io::Error::other)?)?;
+    }
+    Ok(())
+}
+
+/// Compare what was `found` now against the `previous` scan.
+pub fn diff(previous: &Snapshot, found: &[FoundDir]) -> Diff {
+    let change = |dir: &FoundDir, grew_bytes| Change {
+        path: dir.path.clone(),
+        project_type: dir.project_type.name(),
+        project: dir.project_root.clone(),
+        size_bytes: dir.size_bytes,
+        grew_bytes,
+    };
+    let mut new = Vec::new();
+    let mut grew = Vec::new();
+    for dir in found {
+        match previous.sizes.get(&dir.path) {
+            None => new.push(change(dir, dir.size_bytes)),
+            Some(&before) if dir.size_bytes > before => grew.push(change(dir, dir.size_bytes - before)),
+            Some(_) => {}
+        }
+    }
+    new.sort_by_key(|c| std::cmp::Reverse(c.size_bytes));
+    grew.sort_by_key(|c| std::cmp::Reverse(c.grew_bytes));
+    let now: HashSet<&PathBuf> = found.iter().map(|d| &d.path).collect();
+    let gone = previous
+        .sizes
+        .keys()
+        .filter(|path| !now.contains(path))
+        .cloned()
+        .collect();
+    Diff {
+        since: previous.time,
+        new,
+        grew,
+        gone,
+    }
+}
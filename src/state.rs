@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Selector choices remembered between runs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SelectorState {
+    /// Last selection state of each path the user has seen
+    #[serde(default)]
+    pub selected: HashMap<PathBuf, bool>,
+    /// Labels of groups the user left collapsed
+    #[serde(default)]
+    pub collapsed: HashSet<String>,
+}
+
+fn state_file() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("safeclean").join("selector-state.json"))
+}
+
+impl SelectorState {
+    /// Load the saved state; a missing or unreadable file is an empty state.
+    pub fn load() -> Self {
+        state_file()
+            .and_then(|file| fs::read_to_string(file).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&mut self) -> io::Result<()> {
+        let Some(file) = state_file() else {
+            return Ok(());
+        };
+        // Forget paths that have since been cleaned or moved
+        self.selected.retain(|path, _| path.exists());
+
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(file, contents)
+    }
+}
@@ -0,0 +1,271 @@
+use crate::duplicates::Report;
+use crate::format_size;
+use crate::history::{self, Clean};
+use crate::projects::ProjectType;
+use crate::scanner::FoundDir;
+use crate::selector;
+use crate::snapshot::Diff;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Artifacts untouched for at least this many days are called out as stale.
+const STALE_DAYS: u64 = 30;
+
+/// How many of the fastest-growing artifacts are listed.
+const GROWTH_SHOWN: usize = 10;
+
+/// How many months of history the trend shows.
+const TREND_MONTHS: usize = 12;
+
+/// Width of the longest bar in the monthly trend.
+const TREND_WIDTH: u64 = 30;
+
+/// How many duplicated packages and projects sharing them are listed.
+const DUPLICATES_SHOWN: usize = 10;
+
+/// Print reclaimable space per project type, with totals and how much of it
+/// has gone stale.
+pub fn print(found: &[FoundDir]) {
+    if found.is_empty() {
+        println!("{}", "No cleanable directories found.".yellow());
+        return;
+    }
+
+    println!("{}", "Reclaimable space".bold());
+    for pt in ProjectType::all() {
+        let dirs: Vec<&FoundDir> = found.iter().filter(|d| d.project_type == pt).collect();
+        if dirs.is_empty() {
+            continue;
+        }
+        let size: u64 = dirs.iter().map(|d| d.size_bytes).sum();
+        println!("  {:<16} {:>5} dirs  {:>10}", pt.name(), dirs.len(), format_size(size));
+    }
+
+    let total: u64 = found.iter().map(|d| d.size_bytes).sum();
+    println!(
+        "  {:<16} {:>5} dirs  {:>10}",
+        "Total".bold(),
+        found.len(),
+        format_size(total).green().bold()
+    );
+
+    let stale: Vec<&FoundDir> = found
+        .iter()
+        .filter(|d| d.age_days().is_some_and(|days| days >= STALE_DAYS))
+        .collect();
+    if !stale.is_empty() {
+        let stale_size: u64 = stale.iter().map(|d| d.size_bytes).sum();
+        println!(
+            "\n{} dirs ({}) untouched for {}+ days",
+            stale.len(),
+            format_size(stale_size).green(),
+            STALE_DAYS
+        );
+    }
+}
+
+/// Print the artifacts that grew the most since the previous scan, and how
+/// many appeared.
+pub fn print_growth(diff: &Diff, now: i64) {
+    if diff.grew.is_empty() && diff.new.is_empty() {
+        return;
+    }
+    let elapsed = (now - diff.since).max(0);
+    let period = if elapsed < 2 * 3600 {
+        "in the last hour or so".to_string()
+    } else if elapsed < 2 * 86_400 {
+        format!("in {} hours", elapsed / 3600)
+    } else {
+        format!("in {} days", elapsed / 86_400)
+    };
+
+    println!("\n{}", "Growth since the last scan".bold());
+    for change in diff.grew.iter().take(GROWTH_SHOWN) {
+        println!(
+            "  {} grew {} {} (now {})",
+            change.label(),
+            format_size(change.grew_bytes).yellow(),
+            period,
+            format_size(change.size_bytes)
+        );
+    }
+    if diff.grew.len() > GROWTH_SHOWN {
+        println!("  {}", format!("... and {} more", diff.grew.len() - GROWTH_SHOWN).dimmed());
+    }
+    if !diff.new.is_empty() {
+        println!(
+            "  {} new dirs ({}) appeared {}",
+            diff.new.len(),
+            format_size(diff.new.iter().map(|c| c.size_bytes).sum()).yellow(),
+            period
+        );
+    }
+}
+
+/// Everything reclaimable in one project, whatever produced it.
+#[derive(Debug, Serialize)]
+pub struct ProjectTotal {
+    pub project: PathBuf,
+    pub size_bytes: u64,
+    pub dirs: usize,
+    /// Project types of its artifacts, in `ProjectType::all()` order
+    pub types: Vec<&'static str>,
+    /// Days since its most recently modified artifact changed, if known
+    pub age_days: Option<u64>,
+}
+
+/// Reclaimable space per project (repository, or the artifact's parent
+/// directory outside one), largest first.
+pub fn by_project(found: &[FoundDir]) -> Vec<ProjectTotal> {
+    let mut projects: HashMap<&PathBuf, Vec<&FoundDir>> = HashMap::new();
+    for dir in found {
+        projects.entry(&dir.project_root).or_default().push(dir);
+    }
+    let mut totals: Vec<ProjectTotal> = projects
+        .into_iter()
+        .map(|(project, dirs)| ProjectTotal {
+            project: project.clone(),
+            size_bytes: dirs.iter().map(|d| d.size_bytes).sum(),
+            dirs: dirs.len(),
+            types: ProjectType::all()
+                .into_iter()
+                .filter(|pt| dirs.iter().any(|d| d.project_type == *pt))
+                .map(|pt| pt.name())
+                .collect(),
+            age_days: dirs.iter().filter_map(|d| d.age_days()).min(),
+        })
+        .collect();
+    totals.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes).then_with(|| a.project.cmp(&b.project)));
+    totals
+}
+
+/// Print projects ranked by reclaimable space.
+pub fn print_by_project(totals: &[ProjectTotal]) {
+    if totals.is_empty() {
+        println!("{}", "No cleanable directories found.".yellow());
+        return;
+    }
+
+    println!("{}", "Reclaimable space by project".bold());
+    for total in totals {
+        let age = total
+            .age_days
+            .map_or_else(String::new, |days| format!(", untouched {} days", days));
+        println!(
+            "  {:>10} {:>5} dirs  {}  {}",
+            format_size(total.size_bytes),
+            total.dirs,
+            total.project.display(),
+            format!("({}{})", total.types.join(", "), age).dimmed()
+        );
+    }
+    println!(
+        "  {:>10} {:>5} dirs  in {} projects",
+        format_size(totals.iter().map(|t| t.size_bytes).sum()).green().bold(),
+        totals.iter().map(|t| t.dirs).sum::<usize>(),
+        totals.len()
+    );
+}
+
+/// Print the packages installed identically in several `node_modules`, and
+/// the projects whose `node_modules` are most duplicated elsewhere: stale
+/// ones are worth cleaning, active ones would share with pnpm.
+pub fn print_duplicates(report: &Report) {
+    if report.packages.is_empty() {
+        println!("{}", "No package is installed identically in more than one place.".yellow());
+        return;
+    }
+
+    println!(
+        "{} {} of {} in node_modules is duplicated",
+        "Duplicated packages:".bold(),
+        format_size(report.duplicated_bytes).green().bold(),
+        format_size(report.total_bytes)
+    );
+    for package in report.packages.iter().take(DUPLICATES_SHOWN) {
+        println!(
+            "  {:>10}  {}@{}  {}",
+            format_size(package.duplicated_bytes),
+            package.name,
+            package.version,
+            format!("({} copies of {})", package.copies, format_size(package.size_bytes)).dimmed()
+        );
+    }
+    if report.packages.len() > DUPLICATES_SHOWN {
+        println!("  {}", format!("... and {} more", report.packages.len() - DUPLICATES_SHOWN).dimmed());
+    }
+
+    println!("
+{}", "Projects sharing the most".bold());
+    for project in report.projects.iter().take(DUPLICATES_SHOWN) {
+        let percent = project.shared_bytes * 100 / project.size_bytes.max(1);
+        let advice = match project.age_days {
+            Some(days) if days >= STALE_DAYS => format!("untouched {} days, clean it", days),
+            _ => "in use, pnpm would share it".to_string(),
+        };
+        println!(
+            "  {:>10}  {}  {}",
+            format_size(project.shared_bytes),
+            project.project.display(),
+            format!("({}% of its node_modules; {})", percent, advice).dimmed()
+        );
+    }
+    if report.projects.len() > DUPLICATES_SHOWN {
+        println!("  {}", format!("... and {} more", report.projects.len() - DUPLICATES_SHOWN).dimmed());
+    }
+    println!(
+        "
+{}",
+        "pnpm keeps one copy of each package version for every project, linked into each node_modules.".dimmed()
+    );
+}
+
+/// Print how much past cleans have reclaimed: the total, per project type,
+/// and per month.
+pub fn print_history(cleans: &[Clean]) {
+    if cleans.is_empty() {
+        return;
+    }
+
+    let total: u64 = cleans.iter().map(Clean::bytes).sum();
+    println!(
+        "\n{} {} in {} cleans",
+        "Reclaimed so far:".bold(),
+        format_size(total).green().bold(),
+        cleans.len()
+    );
+
+    let mut by_type: BTreeMap<&str, (usize, u64)> = BTreeMap::new();
+    for entry in cleans.iter().flat_map(|c| &c.entries) {
+        let (count, bytes) = by_type.entry(&entry.project_type).or_default();
+        *count += 1;
+        *bytes += entry.bytes;
+    }
+    let mut by_type: Vec<_> = by_type.into_iter().collect();
+    by_type.sort_by_key(|(_, (_, bytes))| std::cmp::Reverse(*bytes));
+    for (name, (count, bytes)) in by_type {
+        println!("  {:<16} {:>5} dirs  {:>10}", name, count, format_size(bytes));
+    }
+
+    let mut by_month: BTreeMap<(i64, u32), u64> = BTreeMap::new();
+    for clean in cleans {
+        *by_month.entry(history::month_of(clean.time)).or_default() += clean.bytes();
+    }
+    let recent: Vec<_> = by_month.into_iter().rev().take(TREND_MONTHS).collect();
+    let largest = recent.iter().map(|(_, bytes)| *bytes).max().unwrap_or(0).max(1);
+    let bar = if selector::supports_unicode() { "█" } else { "#" };
+
+    println!("\n{}", "By month".bold());
+    for ((year, month), bytes) in recent.into_iter().rev() {
+        let width = (bytes * TREND_WIDTH).div_ceil(largest) as usize;
+        println!(
+            "  {}-{:02}  {:>10}  {}",
+            year,
+            month,
+            format_size(bytes),
+            bar.repeat(width).green()
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use crate::caches::tool_home;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// An installed compiler or runtime version that is neither the default nor
+/// active in this shell.
+#[derive(Debug)]
+pub struct Toolchain {
+    /// The version manager that installed it, e.g. "rustup"
+    pub manager: &'static str,
+    /// E.g. "1.70.0-x86_64-unknown-linux-gnu" or "v16.20.2"
+    pub version: String,
+    pub path: PathBuf,
+}
+
+/// Programs whose access time says when a toolchain was last run, since
+/// installing sets every file's times at once and running it never changes
+/// them otherwise.
+const LAUNCHERS: &[&str] = &["bin/rustc", "bin/cargo", "bin/node", "installation/bin/node", "bin/python"];
+
+/// When any of the toolchain at `path` was last run, going by its
+/// launchers' access times. Filesystems mounted `noatime` make this the
+/// install time.
+pub fn last_used(path: &Path) -> Option<SystemTime> {
+    LAUNCHERS
+        .iter()
+        .filter_map(|launcher| fs::metadata(path.join(launcher)).ok())
+        .filter_map(|meta| meta.accessed().ok())
+        .max()
+}
+
+/// Toolchains rustup, nvm, fnm and pyenv installed besides the defaults and
+/// the active ones, and rustup toolchains other than those `pinned` by
+/// projects. Each manager's newest version always stays too.
+pub fn removable(pinned: &[String]) -> Vec<Toolchain> {
+    let mut toolchains = rustup(pinned);
+    toolchains.extend(nvm());
+    toolchains.extend(fnm());
+    toolchains.extend(pyenv());
+    toolchains
+}
+
+/// Directories under `dir`, by name and real path; symlinks such as pyenv's
+/// virtualenv aliases aren't toolchains of their own.
+fn versions(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = dir.canonicalize().and_then(fs::read_dir) else {
+        return Vec::new();
+    };
+    let mut versions: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| Some((entry.file_name().into_string().ok()?, entry.path())))
+        .collect();
+    versions.sort();
+    versions
+}
+
+/// The numbers in a version name, for finding the newest, e.g. [3, 11, 4]
+/// for "3.11.4".
+fn version_key(name: &str) -> Vec<u64> {
+    name.split(|c: char| !c.is_ascii_digit())
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
+/// `versions` less the newest, going by `key`, and those `keep` says to, as
+/// toolchains of `manager`.
+fn prune(
+    manager: &'static str,
+    mut versions: Vec<(String, PathBuf)>,
+    key: impl Fn(&str) -> Vec<u64>,
+    keep: impl Fn(&str, &Path) -> bool,
+) -> Vec<Toolchain> {
+    if let Some(newest) = versions
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, (name, _))| key(name))
+        .map(|(i, _)| i)
+    {
+        versions.remove(newest);
+    }
+    versions
+        .into_iter()
+        .filter(|(name, path)| !keep(name, path))
+        .map(|(version, path)| Toolchain { manager, version, path })
+        .collect()
+}
+
+/// Whether a toolchain called `name` is `wanted`, given as rustup and nvm
+/// take it: whole, or without the rest of the name ("stable" for
+/// "stable-x86_64-unknown-linux-gnu", "18" for "v18.19.0").
+fn names(name: &str, wanted: &str) -> bool {
+    let name = name.trim_start_matches('v');
+    let wanted = wanted.trim().trim_start_matches('v');
+    !wanted.is_empty()
+        && (name == wanted || name.starts_with(&format!("{}-", wanted)) || name.starts_with(&format!("{}.", wanted)))
+}
+
+/// A rustup toolchain's name without its host, e.g. "nightly-2024-05-01" for
+/// "nightly-2024-05-01-x86_64-unknown-linux-gnu".
+fn without_host(name: &str) -> String {
+    let mut parts = name.split('-');
+    let channel = parts.next().unwrap_or_default();
+    let date = parts.take_while(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+    std::iter::once(channel).chain(date).collect::<Vec<_>>().join("-")
+}
+
+/// The toolchain a `rust-toolchain` or `rust-toolchain.toml` file pins its
+/// project to: the `[toolchain]` table's channel, or the legacy file's one
+/// line.
+pub fn rustup_pin(file: &Path) -> Option<String> {
+    let contents = fs::read_to_string(file).ok()?;
+    if let Ok(table) = contents.parse::<toml::Table>() {
+        return table.get("toolchain")?.get("channel")?.as_str().map(String::from);
+    }
+    contents.lines().map(str::trim).find(|line| !line.is_empty()).map(String::from)
+}
+
+fn rustup(pinned: &[String]) -> Vec<Toolchain> {
+    let Some(home) = tool_home("RUSTUP_HOME", ".rustup") else {
+        return Vec::new();
+    };
+    let settings: toml::Table = fs::read_to_string(home.join("settings.toml"))
+        .ok()
+        .and_then(|contents| contents.parse().ok())
+        .unwrap_or_default();
+    let mut kept: Vec<String> = settings
+        .get("default_toolchain")
+        .and_then(|default| default.as_str())
+        .map(String::from)
+        .into_iter()
+        .collect();
+    // Toolchains pinned to directories with `rustup override`
+    if let Some(overrides) = settings.get("overrides").and_then(|o| o.as_table()) {
+        kept.extend(overrides.values().filter_map(|t| t.as_str()).map(String::from));
+    }
+    kept.extend(std::env::var("RUSTUP_TOOLCHAIN").ok());
+    kept.extend(pinned.iter().cloned());
+    prune(
+        "rustup",
+        versions(&home.join("toolchains")),
+        |name| version_key(&without_host(name)),
+        |name, _| kept.iter().any(|wanted| names(name, wanted)),
+    )
+}
+
+fn nvm() -> Vec<Toolchain> {
+    let Some(home) = tool_home("NVM_DIR", ".nvm") else {
+        return Vec::new();
+    };
+    let default = fs::read_to_string(home.join("alias").join("default")).unwrap_or_default();
+    // Set in shells where `nvm use` ran
+    let active = std::env::var_os("NVM_BIN").and_then(|bin| fs::canonicalize(bin).ok());
+    prune("nvm", versions(&home.join("versions").join("node")), version_key, |name, path| {
+        names(name, &default) || active.as_ref().is_some_and(|bin| bin.starts_with(path))
+    })
+}
+
+fn fnm() -> Vec<Toolchain> {
+    let home = std::env::var_os("FNM_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::data_dir().map(|data| data.join("fnm")).filter(|dir| dir.exists()))
+        .or_else(|| dirs::home_dir().map(|home| home.join(".fnm")));
+    let Some(home) = home else {
+        return Vec::new();
+    };
+    // Aliases are links to a version's `installation` directory
+    let default = fs::canonicalize(home.join("aliases").join("default")).ok();
+    let active = std::env::var_os("FNM_MULTISHELL_PATH").and_then(|link| fs::canonicalize(link).ok());
+    prune("fnm", versions(&home.join("node-versions")), version_key, |_, path| {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        [&default, &active].into_iter().flatten().any(|link| link.starts_with(&path))
+    })
+}
+
+fn pyenv() -> Vec<Toolchain> {
+    let Some(home) = tool_home("PYENV_ROOT", ".pyenv") else {
+        return Vec::new();
+    };
+    let mut kept: Vec<String> = fs::read_to_string(home.join("version"))
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect();
+    if let Ok(active) = std::env::var("PYENV_VERSION") {
+        kept.extend(active.split(':').map(String::from));
+    }
+    // pyenv-virtualenv environments live inside the version they were made
+    // from, linked to from beside it
+    let linked: Vec<PathBuf> = fs::read_dir(home.join("versions"))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_symlink()))
+        .filter_map(|entry| fs::canonicalize(entry.path()).ok())
+        .collect();
+    prune("pyenv", versions(&home.join("versions")), version_key, |name, path| {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        kept.iter().any(|wanted| wanted == name) || linked.iter().any(|link| link.starts_with(&path))
+    })
+}
@@ -0,0 +1,157 @@
+use crate::format_size;
+use crate::projects::ProjectType;
+use crate::scanner::FoundDir;
+use colored::{Color, ColoredString, Colorize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Colors the project types' tiles cycle through.
+const PALETTE: &[Color] = &[
+    Color::Blue,
+    Color::Green,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Yellow,
+    Color::Red,
+];
+
+/// Fills that tell neighbouring tiles apart, even without colors.
+const SHADES: &[char] = &['▓', '▒', '░'];
+const ASCII_SHADES: &[char] = &['#', '+', '.'];
+
+/// Terminal rows the map takes at most, leaving room for the legend.
+const MAX_HEIGHT: usize = 24;
+
+/// One character of the map.
+#[derive(Clone, Copy)]
+struct Cell {
+    ch: char,
+    color: Option<Color>,
+    /// Part of a tile's label rather than its fill
+    label: bool,
+}
+
+/// One project type's share of the map.
+struct Band {
+    project_type: ProjectType,
+    total: u64,
+    /// Size of each project's artifacts of this type, largest first
+    projects: Vec<(PathBuf, u64)>,
+}
+
+/// Each cell's share of `length` cells in proportion to `sizes`, as
+/// (offset, length). Items too small for a whole cell get none.
+fn split(sizes: &[u64], length: usize) -> Vec<(usize, usize)> {
+    let total: u64 = sizes.iter().sum::<u64>().max(1);
+    let mut before = 0;
+    sizes
+        .iter()
+        .map(|size| {
+            let start = (before as u128 * length as u128 / total as u128) as usize;
+            before += size;
+            let end = (before as u128 * length as u128 / total as u128) as usize;
+            (start, end - start)
+        })
+        .collect()
+}
+
+/// Reclaimable space as a treemap `width` columns wide: a band per project
+/// type, largest first, split into a tile per project. Returns the map's
+/// lines followed by a legend of the types' colors.
+pub fn render(found: &[FoundDir], width: usize, height: usize, ascii: bool) -> Vec<String> {
+    let shades = if ascii { ASCII_SHADES } else { SHADES };
+    let height = height.clamp(4, MAX_HEIGHT);
+
+    // Sizes per type, then per project within it, largest first
+    let mut bands: Vec<Band> = ProjectType::all()
+        .into_iter()
+        .filter_map(|pt| {
+            let mut projects: HashMap<&PathBuf, u64> = HashMap::new();
+            for dir in found.iter().filter(|d| d.project_type == pt) {
+                *projects.entry(&dir.project_root).or_default() += dir.size_bytes;
+            }
+            let mut projects: Vec<(PathBuf, u64)> = projects.into_iter().map(|(p, s)| (p.clone(), s)).collect();
+            projects.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            let total = projects.iter().map(|(_, size)| size).sum();
+            (total > 0).then_some(Band {
+                project_type: pt,
+                total,
+                projects,
+            })
+        })
+        .collect();
+    bands.sort_by_key(|band| std::cmp::Reverse(band.total));
+
+    let mut grid = vec![vec![Cell { ch: ' ', color: None, label: false }; width]; height];
+    // Cells are about twice as tall as they are wide, so a wide map splits
+    // types into columns and a narrow one into rows
+    let columns = width >= height * 2;
+    let band_sizes: Vec<u64> = bands.iter().map(|band| band.total).collect();
+    let mut tile = 0;
+    for ((i, band), (offset, length)) in
+        bands.iter().enumerate().zip(split(&band_sizes, if columns { width } else { height }))
+    {
+        let color = PALETTE[i % PALETTE.len()];
+        let project_sizes: Vec<u64> = band.projects.iter().map(|(_, size)| *size).collect();
+        let across = if columns { height } else { width };
+        for ((project, size), (start, span)) in band.projects.iter().zip(split(&project_sizes, across)) {
+            if span == 0 || length == 0 {
+                continue;
+            }
+            let (x, y, w, h) = if columns {
+                (offset, start, length, span)
+            } else {
+                (start, offset, span, length)
+            };
+            let fill = shades[tile % shades.len()];
+            tile += 1;
+            for row in &mut grid[y..y + h] {
+                for cell in &mut row[x..x + w] {
+                    *cell = Cell { ch: fill, color: Some(color), label: false };
+                }
+            }
+            let name = project.file_name().unwrap_or(project.as_os_str()).to_string_lossy();
+            let label = format!("{} {}", name, format_size(*size));
+            let label = if label.chars().count() <= w { label } else { name.into_owned() };
+            for (cell, ch) in grid[y][x..x + w].iter_mut().zip(label.chars()) {
+                *cell = Cell { ch, color: Some(color), label: true };
+            }
+        }
+    }
+
+    let mut lines: Vec<String> = grid.iter().map(|row| render_row(row)).collect();
+    lines.push(String::new());
+    lines.push(format!("By type, {}:", if columns { "left to right" } else { "top to bottom" }));
+    for (i, band) in bands.iter().enumerate() {
+        lines.push(format!(
+            "{} {:<16} {:>10}  {} projects",
+            shades[0].to_string().color(PALETTE[i % PALETTE.len()]),
+            band.project_type.name(),
+            format_size(band.total),
+            band.projects.len()
+        ));
+    }
+    lines
+}
+
+/// A row of cells, with runs of the same style colored together.
+fn render_row(row: &[Cell]) -> String {
+    let mut line = String::new();
+    let mut run = String::new();
+    let style = |text: &str, cell: &Cell| -> ColoredString {
+        let text = match cell.color {
+            Some(color) => text.color(color),
+            None => text.normal(),
+        };
+        if cell.label { text.bold().reversed() } else { text }
+    };
+    for (i, cell) in row.iter().enumerate() {
+        run.push(cell.ch);
+        let last = row.get(i + 1).is_none_or(|next| (next.color, next.label) != (cell.color, cell.label));
+        if last {
+            line.push_str(&style(&run, cell).to_string());
+            run.clear();
+        }
+    }
+    line
+}
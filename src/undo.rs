@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directories moved to the trash by the most recent clean.
+#[derive(Debug, Serialize, Deserialize)]
+struct LastTrashed {
+    /// Seconds since the Unix epoch when the clean started
+    time: i64,
+    paths: Vec<PathBuf>,
+}
+
+fn record_file() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("safeclean").join("last-trashed.json"))
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Remember what a clean moved to the trash so [`undo`] can put it back.
+/// `started` is when the clean began.
+pub fn record(started: SystemTime, paths: Vec<PathBuf>) -> io::Result<()> {
+    let Some(file) = record_file() else {
+        return Ok(());
+    };
+    let time = started
+        .duration_since(UNIX_EPOCH)
+        .map_or_else(|_| now(), |d| d.as_secs() as i64);
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let contents =
+        serde_json::to_string_pretty(&LastTrashed { time, paths }).map_err(io::Error::other)?;
+    fs::write(file, contents)
+}
+
+/// Restore the directories trashed by the last clean, returning how many were
+/// put back.
+#[cfg(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+))]
+pub fn undo() -> Result<usize, String> {
+    let file = record_file().ok_or("no state directory available")?;
+    let contents = fs::read_to_string(&file)
+        .map_err(|_| "Nothing to undo: no clean has moved anything to the trash".to_string())?;
+    let last: LastTrashed = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+
+    let items: Vec<trash::TrashItem> = trash::os_limited::list()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|item| item.time_deleted >= last.time && last.paths.contains(&item.original_path()))
+        .collect();
+    if items.is_empty() {
+        return Err("Nothing to undo: the trashed directories are no longer in the trash".to_string());
+    }
+
+    let count = items.len();
+    trash::os_limited::restore_all(items).map_err(|e| e.to_string())?;
+    let _ = fs::remove_file(file);
+    Ok(count)
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+)))]
+pub fn undo() -> Result<usize, String> {
+    Err("Undo isn't supported on this platform; restore the directories from the Trash".to_string())
+}
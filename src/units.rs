@@ -0,0 +1,95 @@
+use clap::ValueEnum;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+/// Split "500MB" into ("500", "MB").
+fn split_number(input: &str) -> (&str, &str) {
+    let input = input.trim();
+    let end = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    (&input[..end], input[end..].trim())
+}
+
+/// Parse a size such as `500MB`, `1.5G`, or `2048` (bytes). Units are
+/// powers of 1024 whichever [`SizeFormat`] sizes are shown in.
+pub fn parse_size(input: &str) -> Result<u64, String> {
+    let (number, unit) = split_number(input);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", input))?;
+
+    let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        _ => return Err(format!("unknown size unit '{}' in '{}'", unit, input)),
+    };
+
+    Ok((value * multiplier as f64) as u64)
+}
+
+/// Parse a duration such as `12h`, `30d`, or `2w`; a bare `0` is no time.
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let (number, unit) = split_number(input);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}'", input))?;
+
+    let seconds = match unit {
+        "" if value == 0.0 => 0,
+        "h" => HOUR,
+        "d" => DAY,
+        "w" => 7 * DAY,
+        _ => return Err(format!("expected a duration like 12h, 30d or 2w, got '{}'", input)),
+    };
+
+    Ok(Duration::from_secs_f64(value * seconds as f64))
+}
+
+/// How sizes are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum SizeFormat {
+    /// Powers of 1024: KiB, MiB, GiB
+    #[default]
+    Binary,
+    /// Powers of 1000: kB, MB, GB
+    Si,
+    /// Exact byte counts
+    Bytes,
+}
+
+static SIZE_FORMAT: AtomicU8 = AtomicU8::new(SizeFormat::Binary as u8);
+
+/// Show every size from now on in `format`.
+pub fn set_size_format(format: SizeFormat) {
+    SIZE_FORMAT.store(format as u8, Ordering::Relaxed);
+}
+
+/// A byte count for people, in the format set with [`set_size_format`].
+pub fn format_size(bytes: u64) -> String {
+    let (base, units) = match SIZE_FORMAT.load(Ordering::Relaxed) {
+        f if f == SizeFormat::Bytes as u8 => return format!("{} B", bytes),
+        f if f == SizeFormat::Si as u8 => (1000.0, ["kB", "MB", "GB", "TB"]),
+        _ => (1024.0, ["KiB", "MiB", "GiB", "TiB"]),
+    };
+    let mut value = bytes as f64;
+    if value < base {
+        return format!("{} B", bytes);
+    }
+    let mut unit = units[0];
+    value /= base;
+    for next in &units[1..] {
+        if value < base {
+            break;
+        }
+        value /= base;
+        unit = next;
+    }
+    format!("{:.1} {}", value, unit)
+}
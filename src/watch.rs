@@ -0,0 +1,53 @@
+use crate::desktop;
+use crate::format_size;
+use crate::logging::{info, verbose};
+use crate::scanner::{self, ScanOptions};
+use colored::Colorize;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+/// How often and when to report reclaimable space.
+pub struct WatchOptions {
+    pub interval: Duration,
+    /// Report once reclaimable space reaches this many bytes
+    pub threshold: u64,
+    /// Also show a desktop notification
+    pub notify: bool,
+}
+
+/// Rescan `roots` every interval, forever, and report each time reclaimable
+/// space rises to the threshold. Nothing is ever deleted.
+pub fn run(roots: &[PathBuf], scan: &ScanOptions, watch: &WatchOptions) -> ! {
+    let locations: Vec<String> = roots.iter().map(|root| root.display().to_string()).collect();
+    info!(
+        "Watching {}; reporting when {} or more can be reclaimed",
+        locations.join(", "),
+        format_size(watch.threshold)
+    );
+
+    // Only report crossings, not every scan while above the threshold
+    let mut above = false;
+    loop {
+        let (found, _) = scanner::scan(roots, scan);
+        let total = scanner::total_size(&found);
+        verbose!("{} reclaimable in {} directories", format_size(total), found.len());
+
+        if total >= watch.threshold && !above {
+            let message = format!(
+                "{} can be reclaimed in {} directories; run safeclean to review",
+                format_size(total),
+                found.len()
+            );
+            println!("{} {}", "safeclean:".yellow().bold(), message);
+            if watch.notify
+                && let Err(e) = desktop::notify("safeclean", &message)
+            {
+                eprintln!("{} could not show notification: {}", "warning:".yellow().bold(), e);
+            }
+        }
+        above = total >= watch.threshold;
+
+        thread::sleep(watch.interval);
+    }
+}
@@ -0,0 +1,56 @@
+//! Runs that could delete under the same directory must shut each other out,
+//! and runs on separate directories must not.
+// The lock directory only moves with XDG_STATE_HOME here
+#![cfg(target_os = "linux")]
+
+use safeclean::lock::{self, LockError, RootLocks};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Once;
+
+/// Keep the lock files out of the real state directory.
+fn isolate_lock_dir() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        let state = tempfile::tempdir().unwrap().keep();
+        // SAFETY: runs once, before any test reads the environment
+        unsafe { std::env::set_var("XDG_STATE_HOME", state) };
+    });
+}
+
+/// Lock `roots` as a run would, giving the root another run holds if it
+/// can't.
+fn lock(roots: &[&PathBuf]) -> Result<RootLocks, PathBuf> {
+    let roots: Vec<PathBuf> = roots.iter().map(|root| root.to_path_buf()).collect();
+    match lock::lock_roots(&roots) {
+        Ok(locks) => Ok(locks),
+        Err(LockError::Held(root)) => Err(root),
+        Err(LockError::Io(e)) => panic!("could not lock {:?}: {}", roots, e),
+    }
+}
+
+#[test]
+fn nested_roots_conflict_and_siblings_do_not() {
+    isolate_lock_dir();
+    let base = tempfile::tempdir().unwrap();
+    let base = base.path().canonicalize().unwrap();
+    let (a, nested, sibling) = (base.join("a"), base.join("a/b"), base.join("c"));
+    fs::create_dir_all(&nested).unwrap();
+    fs::create_dir_all(&sibling).unwrap();
+
+    // One run's duplicate and nested roots don't get in each other's way
+    let first = lock(&[&nested, &a, &a]).expect("one run's own roots conflicted");
+
+    assert_eq!(lock(&[&a]).err(), Some(a.clone()));
+    assert_eq!(lock(&[&nested]).err(), Some(a.clone()));
+    assert_eq!(lock(&[&base]).err(), Some(base.clone()));
+    assert_eq!(lock(&[&sibling, &nested]).err(), Some(a.clone()));
+    let second = lock(&[&sibling]).expect("a sibling root conflicted");
+    assert_eq!(lock(&[&base]).err(), Some(base.clone()));
+
+    drop(first);
+    assert!(lock(&[&nested]).is_ok());
+    assert_eq!(lock(&[&sibling]).err(), Some(sibling.clone()));
+    drop(second);
+    assert!(lock(&[&base]).is_ok());
+}
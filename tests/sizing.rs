@@ -0,0 +1,107 @@
+//! Sizing's own walk must see exactly what walking the tree with walkdir
+//! does.
+
+use safeclean::sizing;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// What a walk saw: each entry's depth, whether it is a directory and, for
+/// files, its length; and the newest modification time.
+#[derive(Debug, PartialEq)]
+struct Seen {
+    entries: BTreeMap<PathBuf, (usize, bool, u64)>,
+    newest: Option<SystemTime>,
+}
+
+fn walked(root: &Path) -> Seen {
+    let mut seen = Seen {
+        entries: BTreeMap::new(),
+        newest: None,
+    };
+    sizing::walk(
+        root,
+        |entry| {
+            let len = if entry.is_file { entry.len } else { 0 };
+            seen.entries.insert(entry.path.to_path_buf(), (entry.depth, entry.is_dir, len));
+            seen.newest = seen.newest.max(entry.modified);
+        },
+        |path, error| panic!("could not read {}: {}", path.display(), error),
+    );
+    seen
+}
+
+fn walked_by_walkdir(root: &Path) -> Seen {
+    let mut seen = Seen {
+        entries: BTreeMap::new(),
+        newest: None,
+    };
+    for entry in WalkDir::new(root).follow_root_links(false) {
+        let entry = entry.unwrap();
+        let meta = entry.metadata().unwrap();
+        let len = if meta.is_file() { meta.len() } else { 0 };
+        seen.entries.insert(entry.path().to_path_buf(), (entry.depth(), meta.is_dir(), len));
+        seen.newest = seen.newest.max(meta.modified().ok());
+    }
+    seen
+}
+
+/// A tree like an installed `node_modules`: nested packages, empty files and
+/// directories, files dated long ago, and one directory too large to list
+/// in one go.
+fn fixture(root: &Path) {
+    for package in 0..20 {
+        let lib = root.join(format!("pkg{}/lib/nested", package));
+        fs::create_dir_all(&lib).unwrap();
+        fs::write(root.join(format!("pkg{}/package.json", package)), "{}").unwrap();
+        fs::write(lib.join("index.js"), "x".repeat(package * 37)).unwrap();
+        fs::write(lib.join("empty.js"), "").unwrap();
+    }
+    fs::create_dir_all(root.join("empty")).unwrap();
+    let wide = root.join("wide");
+    fs::create_dir(&wide).unwrap();
+    for i in 0..1000 {
+        fs::write(wide.join(format!("{}{}", "n".repeat(120), i)), i.to_string()).unwrap();
+    }
+    // As npm extracts them
+    let old = SystemTime::UNIX_EPOCH + Duration::from_secs(499_162_500);
+    let file = fs::File::options().write(true).open(root.join("pkg3/package.json")).unwrap();
+    file.set_modified(old).unwrap();
+}
+
+#[test]
+fn matches_walkdir() {
+    let root = tempfile::tempdir().unwrap();
+    fixture(root.path());
+    let seen = walked(root.path());
+    assert_eq!(seen.entries.len(), 1 + 20 * 6 + 1 + 1 + 1000);
+    assert_eq!(seen, walked_by_walkdir(root.path()));
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_count_as_themselves() {
+    let root = tempfile::tempdir().unwrap();
+    let outside = tempfile::tempdir().unwrap();
+    fs::write(outside.path().join("big"), "x".repeat(4096)).unwrap();
+    fixture(root.path());
+    std::os::unix::fs::symlink(outside.path(), root.path().join("pkg1/escape")).unwrap();
+    std::os::unix::fs::symlink(outside.path().join("big"), root.path().join("pkg2/big")).unwrap();
+
+    let seen = walked(root.path());
+    assert_eq!(seen.entries[&root.path().join("pkg1/escape")], (2, false, 0));
+    assert_eq!(seen.entries[&root.path().join("pkg2/big")], (2, false, 0));
+    assert!(!seen.entries.contains_key(&root.path().join("pkg1/escape/big")));
+    assert_eq!(seen, walked_by_walkdir(root.path()));
+}
+
+#[test]
+fn a_file_is_walked_alone() {
+    let root = tempfile::tempdir().unwrap();
+    let file = root.path().join("file");
+    fs::write(&file, "0123456789").unwrap();
+    let seen = walked(&file);
+    assert_eq!(seen.entries.into_iter().collect::<Vec<_>>(), vec![(file, (0, false, 10))]);
+}
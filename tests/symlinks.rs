@@ -0,0 +1,122 @@
+//! Cleaning must never reach through a symlink to something outside the
+//! directory it was asked to remove.
+#![cfg(unix)]
+
+use safeclean::{Cleaner, FoundDir, Method, Scanner};
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use tempfile::TempDir;
+
+/// Keep the audit log out of the real data directory.
+fn isolate_audit_log() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        let data = tempfile::tempdir().unwrap().keep();
+        // SAFETY: runs once, before any test reads the environment
+        unsafe { std::env::set_var("XDG_DATA_HOME", data) };
+    });
+}
+
+/// A Rust project under `root` whose `target/` holds a 10-byte file.
+fn rust_project(root: &Path) -> PathBuf {
+    let project = root.join("app");
+    fs::create_dir_all(project.join("target/debug")).unwrap();
+    fs::write(project.join("Cargo.toml"), "[package]\n").unwrap();
+    fs::write(project.join("target/debug/app"), "0123456789").unwrap();
+    project
+}
+
+/// A directory elsewhere holding a file nothing may delete.
+fn precious() -> (TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("precious.txt");
+    fs::write(&file, "keep me, please").unwrap();
+    (dir, file)
+}
+
+fn scan(root: &Path) -> Vec<FoundDir> {
+    isolate_audit_log();
+    Scanner::new(vec![root.to_path_buf()]).scan()
+}
+
+#[test]
+fn symlink_inside_candidate_is_unlinked_not_followed() {
+    let root = tempfile::tempdir().unwrap();
+    let (outside, file) = precious();
+    let project = rust_project(root.path());
+    symlink(outside.path(), project.join("target/escape")).unwrap();
+
+    let found = scan(root.path());
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].size_bytes, 10, "sizing followed the symlink");
+
+    let result = Cleaner::new(Method::Delete).clean(found);
+    assert_eq!(result.deleted.len(), 1);
+    assert!(!project.join("target").exists());
+    assert!(file.exists());
+}
+
+#[test]
+fn candidate_swapped_for_symlink_only_loses_the_link() {
+    let root = tempfile::tempdir().unwrap();
+    let (outside, file) = precious();
+    let project = rust_project(root.path());
+
+    let found = scan(root.path());
+    fs::remove_dir_all(project.join("target")).unwrap();
+    symlink(outside.path(), project.join("target")).unwrap();
+
+    let result = Cleaner::new(Method::Delete).clean(found);
+    assert_eq!(result.deleted.len(), 1);
+    assert!(project.join("target").symlink_metadata().is_err());
+    assert!(file.exists());
+}
+
+#[test]
+fn parent_swapped_for_symlink_is_refused() {
+    let root = tempfile::tempdir().unwrap();
+    let elsewhere = tempfile::tempdir().unwrap();
+    let project = rust_project(root.path());
+    let victim = rust_project(elsewhere.path());
+
+    let found = scan(root.path());
+    fs::remove_dir_all(&project).unwrap();
+    symlink(&victim, &project).unwrap();
+
+    let result = Cleaner::new(Method::Delete).clean(found);
+    assert!(result.deleted.is_empty());
+    assert_eq!(result.failed.len(), 1);
+    assert!(result.failed[0].1.to_string().contains("symlink"));
+    assert!(victim.join("target/debug/app").exists());
+}
+
+#[test]
+fn symlinked_candidate_is_not_offered() {
+    let root = tempfile::tempdir().unwrap();
+    let (outside, file) = precious();
+    let project = root.path().join("app");
+    fs::create_dir(&project).unwrap();
+    fs::write(project.join("Cargo.toml"), "[package]\n").unwrap();
+    symlink(outside.path(), project.join("target")).unwrap();
+
+    assert!(scan(root.path()).is_empty());
+    assert!(file.exists());
+}
+
+#[test]
+fn symlink_loop_inside_candidate_is_removed() {
+    let root = tempfile::tempdir().unwrap();
+    let project = rust_project(root.path());
+    symlink(project.join("target"), project.join("target/debug/loop")).unwrap();
+    symlink(&project, project.join("target/up")).unwrap();
+
+    let found = scan(root.path());
+    assert_eq!(found.len(), 1);
+
+    let result = Cleaner::new(Method::Delete).clean(found);
+    assert_eq!(result.deleted.len(), 1);
+    assert!(!project.join("target").exists());
+    assert!(project.join("Cargo.toml").exists());
+}